@@ -1,97 +1,316 @@
-use std::fs;
-use std::io::{self, Read};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::Path;
 
-/// 实现cat命令功能，查看文件内容
-pub fn display_files(paths: &[&str], number_lines: bool, number_nonblank: bool) {
-    if paths.is_empty() {
-        // 如果没有提供文件路径，则从标准输入读取
-        read_from_stdin(number_lines, number_nonblank);
-        return;
+use crate::error::{CatError, Result};
+use crate::json::{ToJson, Value};
+use crate::streams::{Output, Streams};
+use crate::style;
+
+/// 复制内容时每次读取的字节数
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// 文件内容，不是合法UTF-8的文件按二进制返回
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Content {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl ToJson for Content {
+    /// 文本输出为 `content` 字段，二进制内容只输出大小
+    fn to_json(&self) -> Value {
+        match self {
+            Content::Text(text) => Value::object([
+                ("binary", false.into()),
+                ("size", text.len().into()),
+                ("content", text.as_str().into()),
+            ]),
+            Content::Binary(bytes) => Value::object([
+                ("binary", true.into()),
+                ("size", bytes.len().into()),
+                ("content", Value::Null),
+            ]),
+        }
+    }
+}
+
+/// 读取单个文件的内容，路径不存在或是目录时返回错误
+pub fn read(path: impl AsRef<Path>) -> Result<Content> {
+    let file_path = path.as_ref();
+    let mut bytes = Vec::new();
+    if let Err(err) = open(file_path)?.read_to_end(&mut bytes) {
+        return Err(CatError::Failed(tr!("cat.read_failed", file_path.display(), err)));
+    }
+    Ok(match String::from_utf8(bytes) {
+        Ok(text) => Content::Text(text),
+        Err(err) => Content::Binary(err.into_bytes()),
+    })
+}
+
+/// 打开要显示的文件。除了普通文件，管道和设备文件（例如 `/dev/stdin`）也可以读取
+fn open(file_path: &Path) -> Result<File> {
+    // 检查文件是否存在
+    if !file_path.exists() {
+        return Err(CatError::Failed(tr!("cat.not_found", file_path.display())));
     }
     
+    // 检查是否是目录
+    if file_path.is_dir() {
+        return Err(CatError::Failed(tr!("cat.not_file", file_path.display())));
+    }
+    
+    File::open(file_path).map_err(|err| CatError::Failed(tr!("cat.read_failed", file_path.display(), err)))
+}
+
+/// 给文本加上行号，`nonblank_only` 为真时空行不编号（对应 `cat -b`）
+pub fn number(content: &str, nonblank_only: bool) -> String {
+    let mut result = String::new();
+    let mut line_number = 1;
+    
+    for line in content.lines() {
+        if nonblank_only && line.trim().is_empty() {
+            result.push('\n');
+        } else {
+            result.push_str(&format!("{:6}  {}\n", line_number, line));
+            line_number += 1;
+        }
+    }
+    result
+}
+
+/// 实现cat命令功能，查看文件内容。路径 `-` 和没有路径时读取标准输入。
+/// 内容分块原样复制到输出，不会整个读入内存，二进制文件重定向到文件时也保持不变；
+/// 只有输出是终端时，才在每个文件前面显示文件名，二进制文件才显示为一行说明。某个文件无法读取时继续显示其他文件，最后返回所有失败
+pub fn display_files(paths: &[&str], number_lines: bool, number_nonblank: bool, json: bool, io: &mut Streams) -> Result<()> {
+    if json {
+        return print_json(paths, io);
+    }
+    let paths = if paths.is_empty() { &["-"][..] } else { paths };
+    // `-b` 优先于 `-n`
+    let numbering = (number_lines || number_nonblank).then_some(number_nonblank);
+    
     let mut file_count = 0;
+    let mut errors = Vec::new();
     
     for path in paths {
-        let file_path = Path::new(path);
-        
-        // 检查文件是否存在
-        if !file_path.exists() {
-            eprintln!("❌ Error: No such file or directory: {}", path);
-            continue;
-        }
-        
-        // 检查是否是文件
-        if !file_path.is_file() {
-            eprintln!("❌ Error: Not a file: {}", path);
-            continue;
-        }
+        // 先打开文件，失败时不输出文件名
+        let file = if *path == "-" {
+            None
+        } else {
+            match open(Path::new(path)) {
+                Ok(file) => Some(file),
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            }
+        };
         
-        // 如果有多个文件，显示文件名
-        if paths.len() > 1 {
+        // 多个文件输出到终端时显示文件名，输出到文件或管道时是各个文件内容的原样连接
+        if paths.len() > 1 && io.stdout.is_terminal() {
             if file_count > 0 {
-                println!("\n");
+                outln!(io, "\n");
             }
-            println!("====> {} <====", path);
+            outln!(io, "====> {} <====", path);
             file_count += 1;
         }
         
-        // 读取文件内容
-        match fs::read_to_string(file_path) {
-            Ok(content) => {
-                print_content(&content, number_lines, number_nonblank);
-            },
-            Err(err) => {
-                // 尝试以二进制模式读取
-                match fs::read(file_path) {
-                    Ok(bytes) => {
-                        println!("📄 Binary file '{}' ({} bytes)", path, bytes.len());
+        let result = match file {
+            None => copy(&mut io.stdin, &mut io.stdout, numbering),
+            Some(mut file) if io.stdout.is_terminal() => {
+                // 终端中不显示二进制内容，先读取开头的一块来判断
+                let mut head = Vec::new();
+                match Read::by_ref(&mut file).take(CHUNK_SIZE as u64).read_to_end(&mut head) {
+                    Ok(_) if is_binary(&head) => {
+                        let size = file.metadata().map_or(head.len() as u64, |metadata| metadata.len());
+                        outln!(io, "{}{}", style::icon(&io.stdout, "📄"), tr!("cat.binary", path, size));
+                        Ok(())
                     },
-                    Err(_) => {
-                        eprintln!("❌ Error: Failed to read file '{}': {}", path, err);
-                    }
+                    Ok(_) => copy(head.as_slice().chain(file), &mut io.stdout, numbering),
+                    Err(err) => Err(err),
                 }
-            }
+            },
+            Some(file) => copy(file, &mut io.stdout, numbering),
+        };
+        if let Err(err) = result {
+            errors.push(CatError::Failed(if *path == "-" {
+                tr!("cat.stdin_failed", err)
+            } else {
+                tr!("cat.read_failed", path, err)
+            }));
         }
     }
+    CatError::collect(errors)
+}
+
+/// 开头的内容中有NUL字节或者不合法的UTF-8时认为是二进制文件。结尾被截断的多字节字符不算
+fn is_binary(head: &[u8]) -> bool {
+    head.contains(&0) || std::str::from_utf8(head).is_err_and(|err| err.error_len().is_some())
 }
 
-/// 从标准输入读取内容
-fn read_from_stdin(number_lines: bool, number_nonblank: bool) {
-    let stdin = io::stdin();
-    let mut handle = stdin.lock();
-    let mut buffer = String::new();
+/// 把 `input` 的内容原样复制到 `output`，`numbering` 为 `Some` 时逐行加上行号，值表示是否只给非空行编号。
+/// 只返回读取错误，写入失败（例如管道已关闭）时停止复制，和其他输出一样不作为错误
+fn copy(input: impl Read, output: &mut Output, numbering: Option<bool>) -> io::Result<()> {
+    let mut reader = BufReader::with_capacity(CHUNK_SIZE, input);
+    let mut line_number = 1;
+    let mut line = Vec::new();
+    loop {
+        let result = match numbering {
+            None => match reader.fill_buf() {
+                Ok([]) => return Ok(()),
+                Ok(buffer) => {
+                    let len = buffer.len();
+                    let result = output.write_all(buffer);
+                    reader.consume(len);
+                    result
+                },
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            },
+            Some(nonblank_only) => {
+                line.clear();
+                if reader.read_until(b'\n', &mut line)? == 0 {
+                    return Ok(());
+                }
+                if nonblank_only && line.trim_ascii().is_empty() {
+                    output.write_all(&line)
+                } else {
+                    let result = write!(output, "{:6}  ", line_number).and_then(|_| output.write_all(&line));
+                    line_number += 1;
+                    result
+                }
+            },
+        };
+        if result.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// `cat --json`：输出每个文件的路径和内容，行号选项不起作用。标准输入（没有文件参数或者 `-`）的路径为 `null`
+fn print_json(paths: &[&str], io: &mut Streams) -> Result<()> {
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
     
-    if let Ok(_) = handle.read_to_string(&mut buffer) {
-        print_content(&buffer, number_lines, number_nonblank);
-    } else {
-        eprintln!("❌ Error: Failed to read from stdin");
+    let paths = if paths.is_empty() { &["-"][..] } else { paths };
+    for path in paths {
+        let (path_value, content) = if *path == "-" {
+            (Value::Null, read_stdin(io))
+        } else {
+            (Value::from(*path), read(path))
+        };
+        match content {
+            Ok(content) => files.push(with_path(path_value, &content)),
+            Err(err) => errors.push(err),
+        }
     }
+    outln!(io, "{}", Value::Array(files));
+    CatError::collect(errors)
 }
 
-/// 打印内容，支持行号显示
-fn print_content(content: &str, number_lines: bool, number_nonblank: bool) {
-    if !number_lines && !number_nonblank {
-        // 不显示行号，直接打印内容
-        print!("{}", content);
-        return;
+/// 读取标准输入（终端或上一个管道阶段）的全部内容
+fn read_stdin(io: &mut Streams) -> Result<Content> {
+    let mut buffer = Vec::new();
+    if let Err(err) = io.stdin.read_to_end(&mut buffer) {
+        return Err(CatError::Failed(tr!("cat.stdin_failed", err)));
     }
-    
-    let mut line_number = 1;
-    
-    for line in content.lines() {
-        if number_nonblank {
-            // 只对非空行显示行号
-            if !line.trim().is_empty() {
-                println!("{:6}  {}", line_number, line);
-                line_number += 1;
-            } else {
-                println!();
+    Ok(String::from_utf8(buffer).map(Content::Text).unwrap_or_else(|err| Content::Binary(err.into_bytes())))
+}
+
+// 在内容对象的最前面加上路径字段
+fn with_path(path: Value, content: &Content) -> Value {
+    let mut fields = vec![("path".to_string(), path)];
+    if let Value::Object(rest) = content.to_json() {
+        fields.extend(rest);
+    }
+    Value::Object(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::{self, Lang};
+    use crate::test_util::{streams, TempDir};
+
+    fn run(paths: &[&Path], number_lines: bool, number_nonblank: bool, stdin: &[u8]) -> (Result<()>, Vec<u8>) {
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let mut io = streams(stdin);
+        let result = display_files(&paths, number_lines, number_nonblank, false, &mut io);
+        (result, io.stdout.into_bytes())
+    }
+
+    #[test]
+    fn concatenates_binary_files_byte_exact() {
+        let dir = TempDir::new();
+        let a = dir.file("a.bin", b"a\0b\xff\n");
+        let b = dir.file("b.bin", b"xyz");
+        let (result, output) = run(&[&a, &b], false, false, b"");
+        assert!(result.is_ok());
+        assert_eq!(output, b"a\0b\xff\nxyz");
+    }
+
+    #[test]
+    fn copies_large_files_unchanged() {
+        let dir = TempDir::new();
+        let content: Vec<u8> = (0..CHUNK_SIZE * 3 + 17).map(|i| (i * 31 % 251) as u8).collect();
+        let path = dir.file("large.bin", &content);
+        assert_eq!(run(&[&path], false, false, b"").1, content);
+    }
+
+    #[test]
+    fn dash_reads_stdin_between_files() {
+        let dir = TempDir::new();
+        let a = dir.file("a.txt", b"first\n");
+        let b = dir.file("b.txt", b"last\n");
+        assert_eq!(run(&[&a, Path::new("-"), &b], false, false, b"middle\n").1, b"first\nmiddle\nlast\n");
+        assert_eq!(run(&[], false, false, b"only stdin").1, b"only stdin");
+    }
+
+    #[test]
+    fn numbers_lines() {
+        let input = b"a\n\nb";
+        let stdin = Path::new("-");
+        assert_eq!(run(&[stdin], true, false, input).1, b"     1  a\n     2  \n     3  b");
+        assert_eq!(run(&[stdin], false, true, input).1, b"     1  a\n\n     2  b");
+    }
+
+    #[test]
+    fn reports_missing_files_and_continues() {
+        let dir = TempDir::new();
+        let a = dir.file("a.txt", b"a");
+        let (result, output) = run(&[&dir.path().join("missing.txt"), &a], false, false, b"");
+        assert!(result.is_err());
+        assert_eq!(output, b"a");
+        assert!(run(&[dir.path()], false, false, b"").0.is_err());
+    }
+
+    #[test]
+    fn not_a_file_message_is_localized() {
+        let dir = TempDir::new();
+        for (lang, expected) in [(Lang::ZhCn, "不是文件"), (Lang::En, "Not a file")] {
+            match i18n::with_lang(Some(lang), || open(dir.path())) {
+                Err(CatError::Failed(message)) => assert!(message.contains(expected), "{}", message),
+                other => panic!("unexpected result: {:?}", other.map(|_| ())),
             }
-        } else {
-            // 对所有行显示行号
-            println!("{:6}  {}", line_number, line);
-            line_number += 1;
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn detects_binary_content() {
+        assert!(is_binary(b"a\0b"));
+        assert!(is_binary(b"\xff\xfe"));
+        assert!(!is_binary("中文".as_bytes()));
+        // 块的末尾截断的多字节字符不算二进制
+        assert!(!is_binary(&"中".as_bytes()[..2]));
+    }
+
+    #[test]
+    fn reads_content_for_json() {
+        let dir = TempDir::new();
+        assert_eq!(read(dir.file("t.txt", b"text")).unwrap(), Content::Text("text".to_string()));
+        assert_eq!(read(dir.file("b.bin", b"\xff")).unwrap(), Content::Binary(vec![0xff]));
+        assert_eq!(number("a\n\nb\n", true), "     1  a\n\n     2  b\n");
+    }
+}
@@ -1,17 +1,23 @@
 use std::env;
+use std::io::Write;
 use std::path::PathBuf;
 
+use crate::config;
+use crate::error::{CatError, Result};
+use crate::json::Value;
+use crate::streams::Streams;
+use crate::style;
+
 /// 实现cd命令功能，更改当前工作目录
-pub fn change_directory(path: &str) {
+pub fn change_directory(path: &str, json: bool, io: &mut Streams) -> Result<()> {
     // 处理特殊路径符号
     let target_path = match path {
         "~" => {
             // 对于~，尝试获取用户主目录
-            match env::var(if cfg!(target_os = "windows") { "USERPROFILE" } else { "HOME" }) {
-                Ok(home_dir) => PathBuf::from(home_dir),
-                Err(_) => {
-                    eprintln!("❌ 错误: 无法确定用户主目录");
-                    return;
+            match config::home_dir() {
+                Some(home_dir) => home_dir,
+                None => {
+                    return Err(CatError::Failed(tr!("cd.no_home")));
                 }
             }
         },
@@ -20,8 +26,7 @@ pub fn change_directory(path: &str) {
             match env::current_dir() {
                 Ok(current) => current,
                 Err(_) => {
-                    eprintln!("❌ 错误: 无法获取当前目录");
-                    return;
+                    return Err(CatError::Failed(tr!("cd.current_dir_failed")));
                 }
             }
         },
@@ -31,12 +36,10 @@ pub fn change_directory(path: &str) {
                 if let Some(parent) = current.parent() {
                     parent.to_path_buf()
                 } else {
-                    eprintln!("❌ 错误: 已经是根目录，无法再上一级");
-                    return;
+                    return Err(CatError::Failed(tr!("cd.at_root")));
                 }
             } else {
-                eprintln!("❌ 错误: 无法获取当前目录");
-                return;
+                return Err(CatError::Failed(tr!("cd.current_dir_failed")));
             }
         },
         _ => {
@@ -49,30 +52,31 @@ pub fn change_directory(path: &str) {
     let normalized_path = match target_path.canonicalize() {
         Ok(path) => path,
         Err(err) => {
-            eprintln!("❌ 错误: 无法规范化路径: {}", err);
-            return;
+            return Err(CatError::Failed(tr!("cd.canonicalize_failed", err)));
         }
     };
 
     // 检查路径是否存在
     if !normalized_path.exists() {
-        eprintln!("❌ 错误: 找不到文件或目录: {}", path);
-        return;
+        return Err(CatError::Failed(tr!("cd.not_found", path)));
     }
 
     // 检查是否是目录
     if !normalized_path.is_dir() {
-        eprintln!("❌ 错误: 不是目录: {}", path);
-        return;
+        return Err(CatError::Failed(tr!("cd.not_dir", path)));
     }
 
     // 尝试更改当前工作目录
     if let Err(err) = env::set_current_dir(&normalized_path) {
-        eprintln!("❌ 错误: 更改目录失败: {}", err);
-    } else {
-        // 输出新的工作目录
-        if let Ok(new_dir) = env::current_dir() {
-            println!("📍 已切换到: {}", new_dir.display());
+        return Err(CatError::Failed(tr!("cd.failed", err)));
+    }
+    // 输出新的工作目录
+    if let Ok(new_dir) = env::current_dir() {
+        if json {
+            outln!(io, "{}", Value::object([("path", new_dir.display().to_string().into())]));
+        } else {
+            outln!(io, "{}{}", style::icon(&io.stdout, "📍"), tr!("cd.changed", new_dir.display()));
         }
     }
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,841 @@
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+
+use crate::config::{self, Environment};
+use crate::error::{self, CatError};
+use crate::i18n::{self, Lang};
+use crate::streams::Streams;
+use crate::style::{self, Style, When};
+use crate::{cat, cd, cmatrix, curl, glob, ls, mkdir, multicall, open, open_browser, ping, plugin, pwd, rm, server, shell, tree, tui, uname, vim, zip};
+
+// 在Commands枚举中添加TUI命令
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Cli {
+    /// 以JSON格式输出结果，错误也以JSON对象输出到标准错误
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// 界面语言（zh-CN 或 en），默认根据 LC_ALL、LC_MESSAGES、LANG 决定
+    #[arg(long, global = true, value_name = "LANG", value_parser = i18n::parse_lang)]
+    pub lang: Option<Lang>,
+
+    /// 何时显示emoji图标：auto（输出是终端时）、always、never
+    #[arg(long, global = true, value_name = "WHEN", env = "CATSHELL_EMOJI")]
+    pub emoji: Option<When>,
+
+    /// 何时使用颜色：auto（输出是终端且没有设置 NO_COLOR 时）、always、never
+    #[arg(long, global = true, value_name = "WHEN", env = "CATSHELL_COLOR")]
+    pub color: Option<When>,
+
+    /// 在指定目录中创建ls、cat、rm等命令的链接，通过链接调用时catshell就是对应的命令
+    #[arg(long, value_name = "DIR")]
+    pub install: Option<PathBuf>,
+
+    /// 不指定子命令时进入交互式REPL
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+impl Cli {
+    /// 在命令行指定的语言和输出样式下执行 `f`，没有指定的选项保持当前的设置
+    pub fn with_options<T>(&self, f: impl FnOnce() -> T) -> T {
+        i18n::with_lang(self.lang, || style::with_style(self.emoji, self.color, f))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// List directory contents
+    Ls {
+        /// Paths to list (default is current directory)
+        paths: Vec<String>,
+        
+        /// Show hidden files
+        #[arg(short, long)]
+        all: bool,
+        
+        /// Long format listing
+        #[arg(short, long)]
+        long: bool,
+        
+        /// List subdirectories recursively
+        #[arg(short = 'R', long)]
+        recursive: bool,
+
+        /// List directories themselves, not their contents
+        #[arg(short, long)]
+        directory: bool,
+
+        /// Print the inode number of each file
+        #[arg(short, long)]
+        inode: bool,
+
+        /// Print sizes like 1K 234M 2G (powers of 1024)
+        #[arg(short = 'h', long, overrides_with = "si")]
+        human_readable: bool,
+
+        /// Like -h, but use powers of 1000
+        #[arg(long, overrides_with = "human_readable")]
+        si: bool,
+
+        /// Sort by modification time, newest first
+        #[arg(short = 't', overrides_with_all = ["sort_size", "sort_extension"])]
+        sort_time: bool,
+
+        /// Sort by file size, largest first
+        #[arg(short = 'S', overrides_with_all = ["sort_time", "sort_extension"])]
+        sort_size: bool,
+
+        /// Sort alphabetically by extension
+        #[arg(short = 'X', overrides_with_all = ["sort_time", "sort_size"])]
+        sort_extension: bool,
+
+        /// Reverse the sort order (use -R to list subdirectories recursively)
+        #[arg(short, long)]
+        reverse: bool,
+
+        /// Group directories before files
+        #[arg(long)]
+        group_directories_first: bool,
+
+        /// Append an indicator (one of */=@|) to entries
+        #[arg(short = 'F', long)]
+        classify: bool,
+
+        /// List one file per line
+        #[arg(short = '1', overrides_with = "columns")]
+        one_per_line: bool,
+
+        /// List entries in columns (default when output is a terminal)
+        #[arg(short = 'C', overrides_with = "one_per_line")]
+        columns: bool,
+
+        /// Time format: locale, iso, long-iso, full-iso or +FORMAT
+        #[arg(long, value_name = "STYLE", env = "TIME_STYLE", value_parser = ls::parse_time_style)]
+        time_style: Option<ls::TimeStyle>,
+
+        /// Don't show git status markers in the long format
+        #[arg(long)]
+        no_git: bool,
+    },
+    
+    /// Show the directory structure as a tree
+    Tree {
+        /// Directory to show (default is current directory)
+        path: Option<String>,
+
+        /// Show hidden files
+        #[arg(short, long)]
+        all: bool,
+
+        /// List directories only
+        #[arg(short = 'd', long)]
+        dirs_only: bool,
+
+        /// Descend at most LEVEL directories deep
+        #[arg(short = 'L', long, value_name = "LEVEL")]
+        level: Option<usize>,
+
+        /// Follow symbolic links to directories
+        #[arg(short = 'l', long)]
+        follow: bool,
+
+        /// Hide files excluded by .gitignore
+        #[arg(long)]
+        gitignore: bool,
+
+        /// Don't show git status markers
+        #[arg(long)]
+        no_git: bool,
+    },
+
+    /// Print working directory
+    Pwd,
+    
+    /// Remove files or directories
+    Rm {
+        /// Files or directories to remove
+        paths: Vec<String>,
+        
+        /// Remove directories and their contents recursively
+        #[arg(short, long)]
+        recursive: bool,
+        
+        /// Ignore nonexistent files and arguments, never prompt
+        #[arg(short, long)]
+        force: bool,
+    },
+    
+    /// Print system information
+    Uname {
+        /// Print all system information
+        #[arg(short, long)]
+        all: bool,
+    },
+    
+    /// Change directory
+    Cd {
+        /// Directory to change to
+        path: String,
+    },
+    
+    /// Make directories
+    Mkdir {
+        /// Directories to create
+        paths: Vec<String>,
+        
+        /// Create parent directories as needed
+        #[arg(short, long)]
+        parents: bool,
+    },
+    
+    /// Concatenate and display files
+    Cat {
+        /// Files to display; - means standard input
+        paths: Vec<String>,
+        
+        /// Number all output lines
+        #[arg(short = 'n', long)]
+        number_lines: bool,
+        
+        /// Number nonempty output lines
+        #[arg(short = 'b', long)]
+        number_nonblank: bool,
+    },
+    
+    /// 发起HTTP请求 (curl-like)
+    Curl {
+        /// 请求的URL
+        url: String,
+        
+        /// HTTP方法 (GET, POST, PUT, DELETE, HEAD等)
+        #[arg(short = 'X', long, default_value = "GET")] // 添加明确的短选项
+        request: String,
+        
+        /// 显示响应头
+        #[arg(short = 'i', long)] // 添加明确的短选项
+        include: bool,
+        
+        /// 不输出任何内容
+        #[arg(short = 's', long)]
+        silent: bool,
+        
+        /// 发送的数据
+        #[arg(short = 'd', long)] // 添加明确的短选项
+        data: Option<String>,
+        
+        /// 保存响应到文件
+        #[arg(short = 'o', long)] // 添加明确的短选项
+        output: Option<String>,
+        
+        /// 只显示HTTP状态码
+        #[arg(long)]
+        head: bool,
+        
+        /// 添加自定义请求头
+        #[arg(short = 'H', long, num_args(1..), value_parser = parse_header)] // 将短选项改为'H'
+        header: Vec<(String, String)>,
+    },
+    
+    /// 显示矩阵风格的字符下落动画
+    /// 显示矩阵风格的字符下落动画
+    Cmatrix {
+        /// 设置字符颜色 (green, red, blue, yellow, cyan, magenta, white)
+        // 全局选项 --color 是颜色开关，所以长选项是 --matrix-color，原来的 `--color red` 由 `cmatrix_color_compat` 兼容
+        #[arg(short, long = "matrix-color", id = "matrix_color", value_name = "COLOR", default_value = "green", env = "CATSHELL_CMATRIX_COLOR")]
+        color: String,
+        
+        /// 设置动画速度 (1-100, 值越小越快)
+        #[arg(short, long, default_value = "10", env = "CATSHELL_CMATRIX_SPEED")]
+        speed: u64,
+        
+        /// 设置字符密度 (1-100)
+        #[arg(short = 'd', long, default_value = "30", env = "CATSHELL_CMATRIX_DENSITY")]
+        density: u8,
+        
+        /// 不使用粗体字符
+        #[arg(long)]
+        no_bold: bool,
+    },
+    
+    /// 简化版vim文本编辑器
+    Vim {
+        /// 要编辑的文件路径（可选）
+        file: Option<String>,
+    },
+    
+    /// 打开浏览器访问指定URL
+    #[command(name = "open-browser")]
+    OpenBrowser {
+        /// 要访问的URL地址
+        url: String,
+    },
+    Open,
+    /// 启动HTTP服务器运行HTML文件
+    Server {
+        /// 要提供服务的目录路径（默认是当前目录）
+        path: Option<String>,
+        
+        /// 服务器端口号（默认8000）
+        #[arg(short, long, default_value = "8000", env = "CATSHELL_SERVER_PORT")]
+        port: u16,
+    },
+    
+    /// 向指定主机发送ICMP回显请求
+    Ping {
+        /// 要ping的主机名或IP地址
+        host: String,
+        
+        /// 发送的回显请求数量
+        #[arg(short = 'c', long, default_value = "4", env = "CATSHELL_PING_COUNT")]
+        count: u32,
+        
+        /// 超时时间（秒）
+        #[arg(short = 't', long, default_value = "4", env = "CATSHELL_PING_TIMEOUT")]
+        timeout: u32,
+        
+        /// 数据包大小（字节）
+        #[arg(short = 's', long, default_value = "32")]
+        size: u32,
+    },
+    
+    /// 创建或提取zip压缩文件
+    Zip {
+        /// 创建压缩文件
+        #[arg(short, long)]
+        create: bool,
+        
+        /// 提取压缩文件
+        #[arg(short, long)]
+        extract: bool,
+        
+        /// 输出文件或目录路径
+        #[arg(short, long)]
+        output: String,
+        
+        /// 要压缩的文件或目录列表（创建模式）
+        files: Vec<String>,
+    },
+
+    /// 启动TUI界面
+    Tui,
+
+    /// 执行catshell脚本
+    Run {
+        /// 脚本文件路径
+        script: String,
+
+        /// 传给脚本的参数，脚本中通过 $1、$2 ... 访问
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// 列出PATH中的插件（名为 catshell-<命令> 的可执行文件）
+    Plugins,
+
+    /// 其他命令由PATH中的插件 catshell-<命令> 提供
+    #[command(external_subcommand)]
+    Plugin(Vec<String>),
+}
+
+// 解析HTTP头的辅助函数
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    if let Some((key, value)) = s.split_once(":") {
+        Ok((key.trim().to_string(), value.trim().to_string()))
+    } else {
+        Err(tr!("curl.bad_header"))
+    }
+}
+
+/// 使用当前语言帮助文本的命令行定义。帮助文本从消息目录中取出，
+/// 上面的文档注释只在目录中缺少对应的键时使用
+pub fn command() -> clap::Command {
+    localize(Cli::command(), "catshell")
+        .disable_help_subcommand(true)
+        .disable_version_flag(true)
+        .arg(
+            Arg::new("version")
+                .short('V')
+                .long("version")
+                .action(ArgAction::Version)
+                .help(tr!("help.version_flag"))
+                .help_heading(i18n::text("help.options_heading")),
+        )
+}
+
+/// 按 [`command`] 的定义解析参数，第一个参数是程序名。命令行和REPL共用
+pub fn parse<T: Into<OsString>>(argv: impl IntoIterator<Item = T>) -> Result<Cli, clap::Error> {
+    let argv = cmatrix_color_compat(argv.into_iter().map(Into::into).collect());
+    let command = match config::shell_environment() {
+        Some(environment) => use_environment(command(), &environment),
+        None => command(),
+    };
+    command.try_get_matches_from(argv).and_then(|matches| Cli::from_arg_matches(&matches))
+}
+
+/// clap从进程的环境变量中读取 `env = ...` 选项的值。在shell中执行时改为使用shell导出的变量，作为选项的默认值
+fn use_environment(command: clap::Command, environment: &Environment) -> clap::Command {
+    let names: Vec<String> = command.get_subcommands().map(|subcommand| subcommand.get_name().to_string()).collect();
+    let command = command.mut_args(|arg| {
+        let Some(value) = arg.get_env().map(|name| environment.get(name).cloned()) else { return arg };
+        let arg = arg.env(None);
+        match value {
+            Some(value) => arg.default_value(value),
+            None => arg,
+        }
+    });
+    names.iter().fold(command, |command, name| command.mut_subcommand(name, |subcommand| use_environment(subcommand, environment)))
+}
+
+/// 全局的 `--color` 成为颜色开关以后，`cmatrix` 的字符颜色选项改名为 `--matrix-color`。
+/// 为了让原来的 `cmatrix --color red` 继续可用，cmatrix后面取值不是 `auto`、`always`、`never` 的 `--color`
+/// 按 `--matrix-color` 处理；cmatrix的颜色名和这三个值没有重叠
+fn cmatrix_color_compat(mut args: Vec<OsString>) -> Vec<OsString> {
+    // 跳过子命令前面的全局选项，带值的选项同时跳过它的值
+    let mut index = 1;
+    while let Some(arg) = args.get(index).map(|arg| arg.to_string_lossy()) {
+        if !arg.starts_with('-') {
+            break;
+        }
+        index += if matches!(&*arg, "--lang" | "--emoji" | "--color" | "--install") { 2 } else { 1 };
+    }
+    if args.get(index).is_none_or(|arg| arg != "cmatrix") {
+        return args;
+    }
+
+    let is_switch = |value: &str| When::from_str(value, true).is_ok();
+    index += 1;
+    while index < args.len() {
+        let arg = args[index].to_string_lossy().into_owned();
+        if arg == "--" {
+            break;
+        }
+        if arg == "--color" && args.get(index + 1).is_some_and(|value| !is_switch(&value.to_string_lossy())) {
+            args[index] = "--matrix-color".into();
+            index += 1;
+        } else if let Some(value) = arg.strip_prefix("--color=").filter(|value| !is_switch(value)) {
+            args[index] = format!("--matrix-color={}", value).into();
+        }
+        index += 1;
+    }
+    args
+}
+
+/// 把命令及其子命令的说明、参数帮助和标题替换为当前语言的文本。
+/// 命令的说明使用键 `help.<命令>`，参数使用 `help.<命令>.<参数>`
+fn localize(mut cmd: clap::Command, name: &str) -> clap::Command {
+    if let Some(about) = i18n::lookup(&format!("help.{}", name)) {
+        cmd = cmd.about(about);
+    }
+
+    let ids: Vec<String> = cmd.get_arguments().map(|arg| arg.get_id().to_string()).collect();
+    for id in ids {
+        let help = i18n::lookup(&format!("help.{}.{}", name, id));
+        cmd = cmd.mut_arg(&id, |arg| {
+            // 所有参数都有标题时，clap就不会输出英文的 "Arguments" 和 "Options"
+            let heading = if arg.is_positional() { "help.arguments_heading" } else { "help.options_heading" };
+            let arg = arg.help_heading(i18n::text(heading));
+            match help {
+                Some(help) => arg.help(help),
+                None => arg,
+            }
+        });
+    }
+
+    let subcommands: Vec<String> = cmd.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for sub in subcommands {
+        cmd = cmd.mut_subcommand(&sub, |sub_cmd| localize(sub_cmd, &sub));
+    }
+
+    // 子命令自己使用 `-h` 时（例如 `ls -h`），帮助只能用 `--help`
+    let mut help = Arg::new("help")
+        .long("help")
+        .action(ArgAction::Help)
+        .help(tr!("help.help_flag"))
+        .help_heading(i18n::text("help.options_heading"));
+    if cmd.get_arguments().all(|arg| arg.get_short() != Some('h')) {
+        help = help.short('h');
+    }
+    cmd.disable_help_flag(true)
+        .arg(help)
+        .subcommand_help_heading(i18n::text("help.commands_heading"))
+        .help_template(format!("{{about-with-newline}}\n{} {{usage}}\n\n{{all-args}}{{after-help}}", tr!("help.usage_heading")))
+}
+
+/// 实现help命令，`help ls` 和 `ls --help` 一样显示子命令的帮助
+fn print_help(topic: &[String], io: &mut Streams) -> error::Result<()> {
+    let mut cmd = command();
+    cmd.build();
+    let mut target = &mut cmd;
+    for name in topic {
+        target = target
+            .find_subcommand_mut(name)
+            .ok_or_else(|| CatError::Usage(tr!("help.unknown_command", name)))?;
+    }
+    out!(io, "{}", target.render_help());
+    Ok(())
+}
+
+/// 解析命令行并执行，返回进程的退出状态码。没有子命令时进入交互式REPL。
+/// 以子命令的名字调用时（例如链接到catshell的 `ls`）直接执行该子命令
+pub fn run() -> i32 {
+    let argv = multicall::argv();
+    // 在解析之前确定语言，帮助和参数错误也使用 `--lang` 指定的语言
+    i18n::set_lang(i18n::lang_arg(&argv));
+    let mut cli = match parse(argv) {
+        Ok(cli) => cli,
+        Err(err) => err.exit(),
+    };
+    // 交互式模式中的命令也继承这里的设置
+    style::set(Style {
+        emoji: cli.emoji.unwrap_or_default(),
+        color: cli.color.unwrap_or_default(),
+    });
+    
+    if let Some(dir) = &cli.install {
+        let mut io = Streams::inherit();
+        return exit_code(multicall::install(dir, cli.json, &mut io), cli.json, &mut io);
+    }
+    
+    match &mut cli.command {
+        Some(command) => run_command(command, cli.json),
+        None => shell::run_repl(),
+    }
+}
+
+/// 执行命令行指定的子命令，返回退出状态码
+fn run_command(command: &mut Commands, json: bool) -> i32 {
+    // Unix的shell已经展开过通配符，引号中的 `*` 是文件名的一部分，不能再展开一次；cmd.exe和PowerShell不展开
+    if cfg!(windows) {
+        if let Err(err) = expand_path_args(command) {
+            failln!(Streams::inherit(), "{}", err);
+            return 1;
+        }
+    }
+    // 脚本需要把退出状态传给调用者
+    if let Commands::Run { script, args } = command {
+        return shell::run_script(script, args, &mut Streams::inherit());
+    }
+    if let Commands::Plugin(args) = command {
+        if args[0] != "help" {
+            return plugin::run(args, json, &mut Streams::inherit());
+        }
+    }
+    let mut io = Streams::inherit();
+    exit_code(execute(command, json, &mut io), json, &mut io)
+}
+
+/// 输出命令的错误并转换成退出状态码
+fn exit_code(result: error::Result<()>, json: bool, io: &mut Streams) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(err) if json => {
+            err.report_json(io);
+            err.exit_code()
+        },
+        Err(err) => {
+            err.report(io);
+            err.exit_code()
+        }
+    }
+}
+
+/// 在Windows上展开接收路径列表的命令中的通配符和大括号，REPL中的参数在解析前已经展开过
+fn expand_path_args(command: &mut Commands) -> Result<(), String> {
+    let paths = match command {
+        Commands::Ls { paths, .. }
+        | Commands::Rm { paths, .. }
+        | Commands::Cat { paths, .. }
+        | Commands::Mkdir { paths, .. } => paths,
+        Commands::Zip { files, .. } => files,
+        _ => return Ok(()),
+    };
+
+    let mut expanded = Vec::new();
+    for path in paths.iter() {
+        expanded.extend(glob::expand_path_arg(path)?);
+    }
+    *paths = expanded;
+    Ok(())
+}
+
+/// 执行单个子命令，命令行模式和REPL共用。错误由调用者输出并转换成退出状态码。
+/// `json` 为真时有结构化结果的命令输出JSON，交互式的命令（vim、tui、cmatrix等）不受影响
+pub fn execute(command: &Commands, json: bool, io: &mut Streams) -> error::Result<()> {
+    match command {
+        Commands::Ls {
+            paths, all, long, recursive, directory, inode, human_readable, si, time_style,
+            sort_time, sort_size, sort_extension, reverse, group_directories_first, classify, one_per_line, columns,
+            no_git,
+        } => {
+            let paths: Vec<&std::path::Path> = if paths.is_empty() {
+                vec![std::path::Path::new(".")]
+            } else {
+                paths.iter().map(std::path::Path::new).collect()
+            };
+            let sort = if *sort_time {
+                ls::SortBy::Time
+            } else if *sort_size {
+                ls::SortBy::Size
+            } else if *sort_extension {
+                ls::SortBy::Extension
+            } else {
+                ls::SortBy::Name
+            };
+            let list_options = ls::ListOptions {
+                all: *all,
+                sort,
+                reverse: *reverse,
+                directories_first: *group_directories_first,
+            };
+            // 以前的版本中 `-r` 表示递归
+            if *reverse && !*recursive && !json {
+                ls::reverse_hint(io);
+            }
+            let sizes = if *si {
+                ls::SizeFormat::Si
+            } else if *human_readable {
+                ls::SizeFormat::Binary
+            } else {
+                ls::SizeFormat::Bytes
+            };
+            let options = ls::DisplayOptions {
+                long: *long,
+                recursive: *recursive,
+                directory: *directory,
+                inode: *inode,
+                classify: *classify,
+                columns: !*one_per_line && (*columns || io.stdout.is_terminal()),
+                sizes,
+                time_style: time_style.clone().unwrap_or_default(),
+                git: !*no_git,
+            };
+            ls::list_paths(
+                &paths,
+                &list_options,
+                &options,
+                json,
+                io
+            )
+        },
+        
+        Commands::Tree { path, all, dirs_only, level, follow, gitignore, no_git } => {
+            let options = tree::TreeOptions {
+                all: *all,
+                dirs_only: *dirs_only,
+                level: *level,
+                follow_links: *follow,
+                gitignore: *gitignore,
+                git: !*no_git,
+            };
+            tree::print_tree(std::path::Path::new(path.as_deref().unwrap_or(".")), &options, json, io)
+        },
+
+        Commands::Pwd => {
+            pwd::print_working_directory(json, io)
+        },
+        
+        Commands::Rm { paths, recursive, force } => {
+            let path_refs: Vec<&str> = paths.iter().map(String::as_str).collect();
+            rm::remove_files(&path_refs, *recursive, *force, json, io)
+        },
+        
+        Commands::Uname { all: _ } => { // 使用_忽略未使用的变量
+            // 目前我们只实现了-a选项的功能
+            uname::print_system_info(json, io)
+        },
+        
+        Commands::Cd { path } => {
+            cd::change_directory(path, json, io)
+        },
+        
+        Commands::Mkdir { paths, parents } => {
+            let path_refs: Vec<&str> = paths.iter().map(String::as_str).collect();
+            mkdir::create_directories(&path_refs, *parents, json, io)
+        },
+        
+        Commands::Cat { paths, number_lines, number_nonblank } => {
+            let path_refs: Vec<&str> = paths.iter().map(String::as_str).collect();
+            cat::display_files(&path_refs, *number_lines, *number_nonblank, json, io)
+        },
+        Commands::Curl { url, request, include, silent, data, output, head, header } => {
+            // 转换header为&[(&str, &str)]格式
+            let header_refs: Vec<(&str, &str)> = 
+                header.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+                
+            if *head {
+                curl::get_status_code(url, json, io)
+            } else {
+                curl::execute_request(
+                    url,
+                    request,
+                    &header_refs,
+                    *include,
+                    *silent,
+                    data.as_deref(),
+                    output.as_deref(),
+                    json,
+                    io
+                )
+            }
+        },
+        Commands::Cmatrix { color, speed, density, no_bold } => {
+            // 确保参数在有效范围内
+            // 使用适当的类型转换修复clamp方法调用
+            let adjusted_speed = speed.clamp(&1, &100);
+            let adjusted_density = density.clamp(&1, &100);
+            
+            // 添加解引用操作符*来修复类型不匹配问题
+            cmatrix::run_cmatrix(color, *adjusted_speed, *adjusted_density, *no_bold)
+        },
+        Commands::Vim { file } => {
+            vim::run_vim(file.as_deref(), io)
+        },
+        
+        Commands::OpenBrowser { url } => {
+            open_browser::open_browser(url, io)
+        },
+        Commands::Open => {
+            open::open_current_directory(io)
+        },
+            Commands::Server { path, port } => {
+            server::start_server(path.clone(), *port, json, io)
+        },
+        
+        Commands::Ping { host, count, timeout, size } => {
+            ping::ping_host(host, *count, *timeout, *size, json, io)
+        },
+        
+        Commands::Zip { create, extract, output, files } => {
+            if *create && *extract {
+                Err(CatError::Usage(tr!("zip.both_modes")))
+            } else if *create {
+                zip::create_zip(files, output, json, io)
+            } else if *extract {
+                if let Some(zip_file) = files.first() {
+                    zip::extract_zip(zip_file, output, json, io)
+                } else {
+                    Err(CatError::Usage(tr!("zip.no_archive")))
+                }
+            } else {
+                Err(CatError::Usage(tr!("zip.no_mode")))
+            }
+        },
+
+        Commands::Tui => {
+            tui::start_tui()
+        },
+
+        // 调用者会直接取得脚本的退出状态，这里只区分成功和失败
+        Commands::Run { script, args } => match shell::run_script(script, args, io) {
+            0 => Ok(()),
+            status => Err(CatError::Failed(tr!("cli.script_status", script, status))),
+        },
+
+        Commands::Plugins => {
+            plugin::list(json, io)
+        },
+
+        Commands::Plugin(args) if args[0] == "help" => {
+            print_help(&args[1..], io)
+        },
+
+        Commands::Plugin(args) => match plugin::run(args, json, io) {
+            0 => Ok(()),
+            status => Err(CatError::Failed(tr!("plugin.status", args[0], status))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn matrix_color(args: &[&str]) -> (String, Option<When>) {
+        let cli = parse(std::iter::once("catshell").chain(args.iter().copied())).unwrap();
+        match cli.command {
+            Some(Commands::Cmatrix { color, .. }) => (color, cli.color),
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cmatrix_keeps_old_color_option() {
+        assert_eq!(matrix_color(&["cmatrix", "--color", "red"]).0, "red");
+        assert_eq!(matrix_color(&["cmatrix", "--color=blue"]).0, "blue");
+        assert_eq!(matrix_color(&["--json", "cmatrix", "-s", "5", "--color", "cyan"]).0, "cyan");
+        assert_eq!(matrix_color(&["cmatrix", "--matrix-color", "white"]).0, "white");
+        assert_eq!(matrix_color(&["cmatrix", "-c", "yellow"]).0, "yellow");
+    }
+
+    #[test]
+    fn color_switch_values_stay_global() {
+        assert_eq!(matrix_color(&["cmatrix", "--color", "never"]).1, Some(When::Never));
+        assert_eq!(matrix_color(&["--color", "always", "cmatrix", "--color", "red"]), ("red".to_string(), Some(When::Always)));
+        let cli = parse(["catshell", "ls", "--color", "never"]).unwrap();
+        assert_eq!(cli.color, Some(When::Never));
+        assert!(parse(["catshell", "ls", "--color", "red"]).is_err());
+    }
+
+    fn command(args: &[&str]) -> Commands {
+        parse(std::iter::once("catshell").chain(args.iter().copied())).unwrap().command.unwrap()
+    }
+
+    fn paths(command: &Commands) -> Vec<String> {
+        match command {
+            Commands::Cat { paths, .. } | Commands::Rm { paths, .. } => paths.clone(),
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn keeps_existing_file_names_with_pattern_characters() {
+        let dir = TempDir::new();
+        let literal = dir.file("a[1].txt", "").display().to_string();
+        let matched = dir.file("a1.txt", "").display().to_string();
+
+        let mut cat = command(&["cat", &literal]);
+        expand_path_args(&mut cat).unwrap();
+        assert_eq!(paths(&cat), [literal.as_str()]);
+        // 没有同名文件时仍然作为通配符展开
+        let mut cat = command(&["cat", &literal.replace("[1]", "[12]")]);
+        expand_path_args(&mut cat).unwrap();
+        assert_eq!(paths(&cat), [matched]);
+        assert_eq!(run_command(&mut command(&["cat", &literal]), false), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rm_removes_only_the_quoted_file() {
+        let dir = TempDir::new();
+        let literal = dir.file("b*.txt", "");
+        let other = dir.file("bxyz.txt", "");
+
+        let mut rm = command(&["rm", &literal.display().to_string()]);
+        expand_path_args(&mut rm).unwrap();
+        assert_eq!(paths(&rm), [literal.display().to_string()]);
+        assert_eq!(run_command(&mut rm, false), 0);
+        assert!(!literal.exists());
+        assert!(other.exists());
+    }
+
+    #[test]
+    fn reads_option_defaults_from_the_shell_environment() {
+        let environment: Environment = std::sync::Arc::new(
+            [("CATSHELL_CMATRIX_COLOR", "red"), ("CATSHELL_COLOR", "never")].map(|(name, value)| (name.into(), value.into())).into(),
+        );
+        let (color, switch) = config::with_environment(environment.clone(), || matrix_color(&["cmatrix"]));
+        assert_eq!((color.as_str(), switch), ("red", Some(When::Never)));
+        // 命令行上的选项优先
+        let (color, switch) = config::with_environment(environment, || matrix_color(&["--color", "always", "cmatrix", "-c", "cyan"]));
+        assert_eq!((color.as_str(), switch), ("cyan", Some(When::Always)));
+        // shell中没有导出的变量不会从进程的环境变量中读取
+        let empty: Environment = Default::default();
+        assert_eq!(config::with_environment(empty, || matrix_color(&["cmatrix"])).0, "green");
+    }
+}
@@ -3,12 +3,14 @@ use std::thread::sleep;
 use std::time::{Duration, Instant};
 use rand::Rng;
 
+use crate::error::{CatError, Result};
+use crate::signal;
+
 // cmatrix命令实现
-pub fn run_cmatrix(color: &str, speed: u64, density: u8, no_bold: bool) {
+pub fn run_cmatrix(color: &str, speed: u64, density: u8, no_bold: bool) -> Result<()> {
     // 检查终端是否支持ANSI转义序列
     if !is_ansi_supported() {
-        println!("错误: 当前终端不支持ANSI转义序列，无法运行cmatrix");
-        return;
+        return Err(CatError::Failed(tr!("cmatrix.no_ansi")));
     }
 
     // 尝试获取终端大小
@@ -45,7 +47,7 @@ pub fn run_cmatrix(color: &str, speed: u64, density: u8, no_bold: bool) {
     let show_cursor = "\x1b[?25h";
 
     // 显示欢迎信息并提示退出方法
-    println!("{}CatShell Matrix 动画\n按 Ctrl+C 退出...{}", bold_code, reset_code);
+    println!("{}{}{}", bold_code, tr!("cmatrix.banner"), reset_code);
     sleep(Duration::from_secs(1));
 
     // 进入动画循环
@@ -54,8 +56,8 @@ pub fn run_cmatrix(color: &str, speed: u64, density: u8, no_bold: bool) {
     let frame_duration = Duration::from_millis(speed);
 
     // 尝试隐藏光标
-    write!(stdout_lock, "{}", hide_cursor).unwrap();
-    stdout_lock.flush().unwrap();
+    write!(stdout_lock, "{}", hide_cursor)?;
+    stdout_lock.flush()?;
 
     let mut last_frame = Instant::now();
 
@@ -69,7 +71,7 @@ pub fn run_cmatrix(color: &str, speed: u64, density: u8, no_bold: bool) {
         last_frame = Instant::now();
 
         // 清屏并开始新帧
-        write!(stdout_lock, "{}", clear_screen).unwrap();
+        write!(stdout_lock, "{}", clear_screen)?;
 
         // 更新所有列
         for column in &mut columns {
@@ -89,13 +91,12 @@ pub fn run_cmatrix(color: &str, speed: u64, density: u8, no_bold: bool) {
                         color_code,
                         bold_code,
                         c
-                    )
-                    .unwrap();
+                    )?;
                 }
             }
         }
 
-        stdout_lock.flush().unwrap();
+        stdout_lock.flush()?;
 
         // 检查是否有退出信号（简化处理，实际Ctrl+C会通过信号处理）
         if check_for_exit() {
@@ -104,8 +105,9 @@ pub fn run_cmatrix(color: &str, speed: u64, density: u8, no_bold: bool) {
     }
 
     // 恢复终端状态
-    write!(stdout_lock, "{}{}{}", reset_code, clear_screen, show_cursor).unwrap();
-    stdout_lock.flush().unwrap();
+    write!(stdout_lock, "{}{}{}", reset_code, clear_screen, show_cursor)?;
+    stdout_lock.flush()?;
+    Ok(())
 }
 
 // 定义矩阵列结构
@@ -195,8 +197,7 @@ fn get_terminal_size() -> Option<(u16, u16)> {
     Some((80, 25))
 }
 
-// 检查是否需要退出（简化实现）
+// 检查是否需要退出：交互模式下 Ctrl+C 会设置中断标志
 fn check_for_exit() -> bool {
-    // 注意：在实际应用中，应该使用信号处理来捕获 Ctrl+C
-    false
+    signal::interrupted()
 }
\ No newline at end of file
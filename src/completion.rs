@@ -0,0 +1,360 @@
+use std::fs;
+use std::path::Path;
+
+use clap::CommandFactory;
+
+use crate::lexer;
+use crate::shell::Shell;
+use crate::cli::{self, Cli};
+
+/// 一个补全候选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// 替换当前单词的文本，已经按需转义
+    pub replacement: String,
+    /// 在候选菜单中显示的文本
+    pub display: String,
+}
+
+/// 补全的结果：从 `start` 到光标的内容会被替换成候选项
+#[derive(Debug, Default)]
+pub struct Completion {
+    pub start: usize,
+    pub candidates: Vec<Candidate>,
+}
+
+/// 光标所在的位置需要补全什么
+enum Context {
+    Command,
+    Flag(String),
+    Path { dirs_only: bool },
+    Subcommand,
+    Host { url: bool },
+}
+
+/// 根据光标前的内容补全当前单词：命令位置补全子命令和shell内置命令，
+/// `-` 开头时补全 `Commands` 中定义的选项，`ping` / `curl` 补全历史记录中的主机，其他参数补全路径
+pub fn complete(line: &[char], cursor: usize, shell: &Shell) -> Completion {
+    let scan = scan(&line[..cursor]);
+    let word: String = unescape(&line[scan.start..cursor]);
+
+    let context = if scan.redirect {
+        Context::Path { dirs_only: false }
+    } else if scan.words.is_empty() {
+        if word.contains('/') {
+            Context::Path { dirs_only: false }
+        } else {
+            Context::Command
+        }
+    } else {
+        let command = scan.words[0].as_str();
+        let previous = scan.words.last().map(String::as_str).unwrap_or("");
+        if word.starts_with('-') {
+            Context::Flag(command.to_string())
+        } else if takes_value(command, previous) {
+            Context::Path { dirs_only: false }
+        } else {
+            match command {
+                "help" => Context::Subcommand,
+                "cd" => Context::Path { dirs_only: true },
+                "ping" => Context::Host { url: false },
+                "curl" => Context::Host { url: true },
+                _ => Context::Path { dirs_only: false },
+            }
+        }
+    };
+
+    let mut candidates = match context {
+        Context::Command => words(shell.command_names(), &word),
+        Context::Subcommand => words(subcommand_names(), &word),
+        Context::Flag(command) => words(flags(&command), &word),
+        Context::Host { url } => words(history_hosts(shell, url), &word),
+        Context::Path { dirs_only } => paths(&word, dirs_only),
+    };
+    candidates.sort_by(|a, b| a.display.cmp(&b.display));
+    candidates.dedup();
+
+    Completion { start: scan.start, candidates }
+}
+
+/// 光标前的内容扫描结果
+struct Scan {
+    /// 当前单词的起始位置
+    start: usize,
+    /// 当前管道阶段中已经输入完的单词
+    words: Vec<String>,
+    /// 当前单词是重定向的目标
+    redirect: bool,
+}
+
+/// 找到当前单词的起始位置，以及同一个命令中前面的单词。引号和转义内的空白和操作符不会切分单词
+fn scan(chars: &[char]) -> Scan {
+    let mut scan = Scan { start: 0, words: Vec::new(), redirect: false };
+    let mut quote = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {},
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '\\') => i += 1,
+            // `2>&1` 和 `>&2` 中的 `&` 不分隔命令
+            (None, '&') if i > 0 && chars[i - 1] == '>' => {
+                scan.redirect = false;
+                i += 1;
+                scan.start = i + 1;
+            },
+            (None, c) if c.is_whitespace() || "|;&()<>".contains(c) => {
+                // `2>` 中的数字是重定向的一部分，不是命令的参数
+                let fd = c == '>' && matches!(&chars[scan.start..i], ['1'] | ['2']);
+                if i > scan.start && !fd {
+                    if scan.redirect {
+                        scan.redirect = false;
+                    } else {
+                        scan.words.push(unescape(&chars[scan.start..i]));
+                    }
+                }
+                match c {
+                    '<' | '>' => scan.redirect = true,
+                    '|' | ';' | '&' | '(' | ')' => {
+                        scan.words.clear();
+                        scan.redirect = false;
+                    },
+                    _ => {},
+                }
+                scan.start = i + 1;
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    scan.start = scan.start.min(chars.len());
+    scan
+}
+
+/// 去掉单词中的引号和反斜杠，得到用来匹配的文本
+fn unescape(chars: &[char]) -> String {
+    let mut text = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\'' | '"' => {},
+            '\\' => match chars.get(i + 1) {
+                Some(&next) if lexer::is_special(next) => {
+                    text.push(next);
+                    i += 1;
+                },
+                _ => text.push('\\'),
+            },
+            c => text.push(c),
+        }
+        i += 1;
+    }
+    text
+}
+
+/// 转义补全结果中对shell有特殊含义的字符
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if lexer::is_special(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 以 `prefix` 开头的单词
+fn words(words: impl IntoIterator<Item = String>, prefix: &str) -> Vec<Candidate> {
+    words
+        .into_iter()
+        .filter(|word| word.starts_with(prefix))
+        .map(|word| Candidate { replacement: escape(&word), display: word })
+        .collect()
+}
+
+/// `Commands` 中定义的所有子命令
+pub fn subcommand_names() -> Vec<String> {
+    Cli::command().get_subcommands().map(|sub| sub.get_name().to_string()).collect()
+}
+
+/// 子命令的所有选项，直接从clap的定义中读取
+fn flags(command: &str) -> Vec<String> {
+    // 使用和帮助相同的定义，子命令自己使用 `-h` 时帮助只有 `--help`
+    let mut cli = cli::command();
+    cli.build();
+    let Some(sub) = cli.find_subcommand(command) else { return Vec::new() };
+
+    let mut flags = Vec::new();
+    for arg in sub.get_arguments().filter(|arg| !arg.is_positional()) {
+        if let Some(long) = arg.get_long() {
+            flags.push(format!("--{}", long));
+        }
+        if let Some(short) = arg.get_short() {
+            flags.push(format!("-{}", short));
+        }
+    }
+    flags
+}
+
+/// `previous` 是否是 `command` 的一个需要值的选项，例如 `curl -o`
+fn takes_value(command: &str, previous: &str) -> bool {
+    if !previous.starts_with('-') {
+        return false;
+    }
+    let cli = Cli::command();
+    let Some(sub) = cli.find_subcommand(command) else { return false };
+
+    let takes_value = sub.get_arguments().any(|arg| {
+        let matches = match previous.strip_prefix("--") {
+            Some(long) => arg.get_long() == Some(long),
+            None => previous.len() == 2 && arg.get_short() == previous.chars().nth(1),
+        };
+        matches && arg.get_action().takes_values()
+    });
+    takes_value
+}
+
+/// 从历史记录中收集 `ping` 和 `curl` 用过的主机。`url` 为 `true` 时返回完整的URL，否则只返回主机名
+fn history_hosts(shell: &Shell, url: bool) -> Vec<String> {
+    let mut hosts = Vec::new();
+    for entry in shell.history.entries() {
+        let mut words = entry.split_whitespace();
+        if !matches!(words.next(), Some("ping") | Some("curl")) {
+            continue;
+        }
+
+        for word in words.filter(|word| !word.starts_with('-')) {
+            let word = word.trim_matches(['\'', '"']);
+            let host = host_of(word);
+            if host != "localhost" && !host.contains('.') && !host.contains(':') {
+                continue;
+            }
+            hosts.push(if url { word.to_string() } else { host.to_string() });
+        }
+    }
+    hosts
+}
+
+/// 去掉URL中的协议、路径和端口，只保留主机名
+fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    // IPv6地址中的冒号不是端口
+    if rest.matches(':').count() == 1 {
+        rest.split(':').next().unwrap_or(rest)
+    } else {
+        rest
+    }
+}
+
+/// 补全文件路径，目录的候选项以 `/` 结尾。只有前缀以 `.` 开头时才补全隐藏文件
+fn paths(word: &str, dirs_only: bool) -> Vec<Candidate> {
+    let (dir, prefix) = match word.rfind('/') {
+        Some(index) => (&word[..=index], &word[index + 1..]),
+        None => ("", word),
+    };
+    let entries = match fs::read_dir(if dir.is_empty() { Path::new(".") } else { Path::new(dir) }) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut candidates = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+            continue;
+        }
+        // 跟随符号链接判断是否是目录
+        let is_dir = entry.path().is_dir();
+        if dirs_only && !is_dir {
+            continue;
+        }
+
+        let suffix = if is_dir { "/" } else { "" };
+        candidates.push(Candidate {
+            replacement: format!("{}{}{}", escape(dir), escape(&name), suffix),
+            display: format!("{}{}", name, suffix),
+        });
+    }
+    candidates
+}
+
+/// 所有候选项共同的前缀
+pub fn common_prefix(candidates: &[Candidate]) -> String {
+    let mut prefix: Vec<char> = match candidates.first() {
+        Some(first) => first.replacement.chars().collect(),
+        None => return String::new(),
+    };
+    for candidate in &candidates[1..] {
+        let len = prefix.iter().zip(candidate.replacement.chars()).take_while(|(a, b)| **a == *b).count();
+        prefix.truncate(len);
+    }
+    prefix.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn complete_line(line: &str, shell: &Shell) -> (usize, Vec<String>) {
+        let chars: Vec<char> = line.chars().collect();
+        let completion = complete(&chars, chars.len(), shell);
+        (completion.start, completion.candidates.into_iter().map(|candidate| candidate.replacement).collect())
+    }
+
+    #[test]
+    fn scans_current_word() {
+        let line: Vec<char> = r#"echo "a b" c\ d | ls -l 2>&1 pa"#.chars().collect();
+        let scan = scan(&line);
+        assert_eq!(scan.words, ["ls", "-l"]);
+        assert_eq!(scan.start, line.len() - 2);
+        assert!(!scan.redirect);
+
+        let scan = super::scan(&"cat a > out".chars().collect::<Vec<_>>());
+        assert!(scan.redirect);
+        assert_eq!(scan.words, ["cat", "a"]);
+    }
+
+    #[test]
+    fn completes_commands_flags_and_hosts() {
+        let mut shell = Shell::new("test", &[]);
+        let (start, candidates) = complete_line("echo a; al", &shell);
+        assert_eq!(start, 8);
+        assert!(candidates.contains(&"alias".to_string()), "{:?}", candidates);
+
+        assert_eq!(complete_line("ls --rec", &shell).1, ["--recursive"]);
+        // `ls -h` 是 --human-readable，帮助只有 --help
+        assert_eq!(complete_line("ls -h", &shell).1, ["-h"]);
+        assert_eq!(complete_line("ls --he", &shell).1, ["--help"]);
+        assert_eq!(complete_line("cat -", &shell).1.iter().filter(|flag| *flag == "-h").count(), 1);
+
+        shell.history.add("ping example.com");
+        shell.history.add("curl -s https://api.example.org/v1?x=1");
+        shell.history.add("ping notahost");
+        assert_eq!(complete_line("ping ", &shell).1, ["api.example.org", "example.com"]);
+        assert_eq!(complete_line("curl h", &shell).1, ["https://api.example.org/v1\\?x=1"]);
+    }
+
+    #[test]
+    fn completes_and_escapes_paths() {
+        let dir = TempDir::new();
+        dir.file("my file.txt", "");
+        dir.file("mine/x", "");
+        dir.file(".hidden", "");
+        let base = dir.path().display().to_string();
+        let shell = Shell::new("test", &[]);
+
+        let (_, candidates) = complete_line(&format!("cat {}/m", base), &shell);
+        assert_eq!(candidates, [format!("{}/mine/", escape(&base)), format!("{}/my\\ file.txt", escape(&base))]);
+        assert_eq!(complete_line(&format!("cd {}/m", base), &shell).1, [format!("{}/mine/", escape(&base))]);
+        assert_eq!(complete_line(&format!("cat {}/my\\ f", base), &shell).1.len(), 1);
+        assert_eq!(complete_line(&format!("cat {}/.h", base), &shell).1, [format!("{}/.hidden", escape(&base))]);
+        let line: Vec<char> = format!("cat {}/m", base).chars().collect();
+        assert_eq!(common_prefix(&complete(&line, line.len(), &shell).candidates), format!("{}/m", escape(&base)));
+    }
+}
@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::Path;
+
+/// 计算 `test` / `[` 的条件表达式，支持文件判断、字符串比较和整数比较。
+/// 表达式格式不正确时返回错误信息
+pub fn evaluate(args: &[String]) -> Result<bool, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        [] => Ok(false),
+        ["!", rest @ ..] => evaluate_strs(rest).map(|result| !result),
+        _ => evaluate_strs(&args),
+    }
+}
+
+fn evaluate_strs(args: &[&str]) -> Result<bool, String> {
+    match *args {
+        [] => Ok(false),
+        [value] => Ok(!value.is_empty()),
+        [op, value] => unary(op, value),
+        [left, op, right] => binary(left, op, right),
+        _ => Err(tr!("test.too_many_args", args.join(" "))),
+    }
+}
+
+fn unary(op: &str, value: &str) -> Result<bool, String> {
+    let path = Path::new(value);
+    Ok(match op {
+        "-e" => path.exists(),
+        "-f" => path.is_file(),
+        "-d" => path.is_dir(),
+        "-s" => fs::metadata(path).map(|meta| meta.len() > 0).unwrap_or(false),
+        "-z" => value.is_empty(),
+        "-n" => !value.is_empty(),
+        _ => return Err(tr!("test.unknown_unary", op)),
+    })
+}
+
+fn binary(left: &str, op: &str, right: &str) -> Result<bool, String> {
+    match op {
+        "=" | "==" => Ok(left == right),
+        "!=" => Ok(left != right),
+        "-eq" | "-ne" | "-lt" | "-le" | "-gt" | "-ge" => {
+            let (left, right) = (parse_integer(left)?, parse_integer(right)?);
+            Ok(match op {
+                "-eq" => left == right,
+                "-ne" => left != right,
+                "-lt" => left < right,
+                "-le" => left <= right,
+                "-gt" => left > right,
+                _ => left >= right,
+            })
+        },
+        _ => Err(tr!("test.unknown_binary", op)),
+    }
+}
+
+fn parse_integer(value: &str) -> Result<i64, String> {
+    value.trim().parse().map_err(|_| tr!("test.integer_expected", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn test(args: &[&str]) -> Result<bool, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        evaluate(&args)
+    }
+
+    #[test]
+    fn compares_strings_and_integers() {
+        assert_eq!(test(&[]), Ok(false));
+        assert_eq!(test(&[""]), Ok(false));
+        assert_eq!(test(&["x"]), Ok(true));
+        assert_eq!(test(&["-z", ""]), Ok(true));
+        assert_eq!(test(&["-n", ""]), Ok(false));
+        assert_eq!(test(&["a", "=", "a"]), Ok(true));
+        assert_eq!(test(&["a", "!=", "a"]), Ok(false));
+        assert_eq!(test(&["!", "a", "==", "b"]), Ok(true));
+        assert_eq!(test(&["10", "-gt", " 9"]), Ok(true));
+        assert_eq!(test(&["-3", "-ge", "-2"]), Ok(false));
+        assert!(test(&["a", "-lt", "1"]).is_err());
+        assert!(test(&["a", "-x", "b"]).is_err());
+        assert!(test(&["-q", "a"]).is_err());
+        assert!(test(&["a", "=", "b", "c"]).is_err());
+    }
+
+    #[test]
+    fn checks_files() {
+        let dir = TempDir::new();
+        let empty = dir.file("empty", "").display().to_string();
+        let full = dir.file("full", "x").display().to_string();
+        let base = dir.path().display().to_string();
+
+        assert_eq!(test(&["-e", &empty]), Ok(true));
+        assert_eq!(test(&["-f", &empty]), Ok(true));
+        assert_eq!(test(&["-s", &empty]), Ok(false));
+        assert_eq!(test(&["-s", &full]), Ok(true));
+        assert_eq!(test(&["-d", &base]), Ok(true));
+        assert_eq!(test(&["-f", &base]), Ok(false));
+        assert_eq!(test(&["!", "-e", &format!("{}/missing", base)]), Ok(true));
+    }
+}
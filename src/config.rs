@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+
+/// 环境变量表
+pub type Environment = Arc<BTreeMap<OsString, OsString>>;
+
+thread_local! {
+    /// shell中 `export` 和 `unset` 之后的环境变量，为 `None` 时读取进程的环境变量。
+    /// shell不修改进程的环境变量，因为管道和后台任务中的命令可能正在其他线程中读取
+    static ENVIRONMENT: RefCell<Option<Environment>> = const { RefCell::new(None) };
+}
+
+/// 当前线程的环境变量
+pub fn environment() -> Environment {
+    shell_environment().unwrap_or_else(|| Arc::new(env::vars_os().collect()))
+}
+
+/// 在shell中执行时shell导出的环境变量，否则为 `None`
+pub fn shell_environment() -> Option<Environment> {
+    ENVIRONMENT.with_borrow(Clone::clone)
+}
+
+/// 在环境变量 `environment` 下执行 `f`，结束后恢复原来的设置
+pub fn with_environment<T>(environment: Environment, f: impl FnOnce() -> T) -> T {
+    let previous = ENVIRONMENT.replace(Some(environment));
+    let result = f();
+    ENVIRONMENT.set(previous);
+    result
+}
+
+/// 当前线程的环境变量 `name`
+pub fn var_os(name: &str) -> Option<OsString> {
+    ENVIRONMENT.with_borrow(|environment| match environment {
+        Some(environment) => environment.get(OsStr::new(name)).cloned(),
+        None => env::var_os(name),
+    })
+}
+
+/// 当前线程的环境变量 `name`，不是有效的UTF-8时返回 `None`
+pub fn var(name: &str) -> Option<String> {
+    var_os(name)?.into_string().ok()
+}
+
+/// 让外部程序使用当前线程的环境变量
+pub fn apply_environment(command: &mut Command) {
+    ENVIRONMENT.with_borrow(|environment| {
+        if let Some(environment) = environment {
+            command.env_clear().envs(environment.iter());
+        }
+    });
+}
+
+/// 用户主目录，Windows上是 `%USERPROFILE%`，其他系统是 `$HOME`
+pub fn home_dir() -> Option<PathBuf> {
+    let name = if cfg!(target_os = "windows") { "USERPROFILE" } else { "HOME" };
+    var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+/// catshell的配置目录，用来保存历史记录等文件：
+/// Windows上是 `%APPDATA%\catshell`，macOS上是 `~/Library/Application Support/catshell`，
+/// 其他系统是 `$XDG_CONFIG_HOME/catshell` 或 `~/.config/catshell`
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        var_os("XDG_CONFIG_HOME")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|home| home.join(".config")))
+    };
+    base.map(|base| base.join("catshell"))
+}
@@ -1,139 +1,265 @@
-use std::process::{Command, Stdio};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
 
-/// 执行HTTP请求并显示响应（使用Windows PowerShell）
-pub fn execute_request(
-    url: &str,
-    method: &str,
-    headers: &[(&str, &str)],
-    show_headers: bool,
-    silent: bool,
-    data: Option<&str>,
-    output: Option<&str>
-) {
-    // 构建PowerShell命令
-    let mut ps_command = String::new();
+use crate::error::{CatError, Result};
+use crate::json::{ToJson, Value};
+use crate::streams::Streams;
+use crate::style;
+
+/// 一个HTTP请求，默认是没有请求体的GET请求
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub url: String,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    pub data: Option<String>,
+}
+
+impl Request {
+    pub fn new(url: impl Into<String>) -> Self {
+        Request {
+            url: url.into(),
+            method: "GET".to_string(),
+            headers: Vec::new(),
+            data: None,
+        }
+    }
+}
+
+/// HTTP响应，`body` 是未经解码的原始字节
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub reason: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// 按名称查找响应头，不区分大小写
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl ToJson for Response {
+    /// 正文不是UTF-8文本时 `body` 为 `null`，只输出大小
+    fn to_json(&self) -> Value {
+        let headers = self.headers.iter().map(|(key, value)| (key.clone(), value.as_str().into())).collect();
+        Value::object([
+            ("status", self.status.into()),
+            ("reason", self.reason.as_str().into()),
+            ("headers", Value::Object(headers)),
+            ("content_type", self.header("Content-Type").into()),
+            ("body_size", self.body.len().into()),
+            ("body", std::str::from_utf8(&self.body).ok().into()),
+        ])
+    }
+}
+
+/// 发送HTTP请求并返回完整的响应（使用Windows PowerShell）
+pub fn fetch(request: &Request) -> Result<Response> {
+    // 构建PowerShell命令，关闭进度条以免它写到错误输出
+    let mut ps_command = String::from("$ProgressPreference = 'SilentlyContinue'\n");
     
     // 设置方法和URL
-    ps_command.push_str(&format!("$response = Invoke-WebRequest -Uri '{}' -Method {}", url, method));
+    ps_command.push_str(&format!(
+        "$response = Invoke-WebRequest -Uri {} -Method {} -UseBasicParsing",
+        quote(&request.url),
+        request.method
+    ));
     
     // 添加请求头
-    if !headers.is_empty() {
+    if !request.headers.is_empty() {
         ps_command.push_str(" -Headers @{");
-        for (key, value) in headers {
-            ps_command.push_str(&format!("'{}'='{}';", key, value));
+        for (key, value) in &request.headers {
+            ps_command.push_str(&format!("{}={};", quote(key), quote(value)));
         }
-        ps_command.push_str("}");
+        ps_command.push('}');
     }
     
     // 添加请求体
-    if let Some(body) = data {
-        // 对数据进行转义，以避免PowerShell解析错误
-        let escaped_body = body.replace("'", "''");
-        ps_command.push_str(&format!(" -Body '{}'", escaped_body));
+    if let Some(body) = &request.data {
+        ps_command.push_str(&format!(" -Body {}", quote(body)));
     }
     
-    // 添加其他选项
-    if !show_headers {
-        ps_command.push_str(" -UseBasicParsing");
+    // 依次输出状态码、状态描述、响应头、空行，最后是base64编码的正文
+    ps_command.push_str("\nWrite-Output $response.StatusCode");
+    ps_command.push_str("\nWrite-Output $response.StatusDescription");
+    ps_command.push_str("\nforeach ($key in $response.Headers.Keys) {\n");
+    ps_command.push_str("    Write-Output ('{0}: {1}' -f $key, $response.Headers[$key])\n");
+    ps_command.push('}');
+    ps_command.push_str("\nWrite-Output ''");
+    ps_command.push_str("\nWrite-Output ([Convert]::ToBase64String($response.RawContentStream.ToArray()))");
+    
+    // 执行PowerShell命令
+    let output = Command::new("powershell.exe")
+        .arg("-Command")
+        .arg(&ps_command)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output();
+    
+    let result = output.map_err(|err| CatError::spawn("powershell.exe", err))?;
+    check_result(&result)?;
+    parse_response(&String::from_utf8_lossy(&result.stdout))
+}
+
+/// 执行HTTP请求并显示响应。`json` 时输出包含状态码、响应头和正文的对象，保存到文件时不包含正文
+#[allow(clippy::too_many_arguments)]
+pub fn execute_request(
+    url: &str,
+    method: &str,
+    headers: &[(&str, &str)],
+    show_headers: bool,
+    silent: bool,
+    data: Option<&str>,
+    output: Option<&str>,
+    json: bool,
+    io: &mut Streams
+) -> Result<()> {
+    let request = Request {
+        url: url.to_string(),
+        method: method.to_string(),
+        headers: headers.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+        data: data.map(str::to_string),
+    };
+    let response = fetch(&request)?;
+    
+    if json {
+        let mut fields = vec![
+            ("url".to_string(), Value::from(url)),
+            ("method".to_string(), Value::from(method)),
+        ];
+        if let Value::Object(rest) = response.to_json() {
+            fields.extend(rest);
+        }
+        if let Some(file_path) = output {
+            if let Err(err) = fs::write(file_path, &response.body) {
+                return Err(CatError::Failed(tr!("curl.write_failed", file_path, err)));
+            }
+            // 正文已经保存到文件中
+            for (key, value) in fields.iter_mut() {
+                if key == "body" {
+                    *value = Value::Null;
+                }
+            }
+        }
+        fields.push(("saved_to".to_string(), output.into()));
+        outln!(io, "{}", Value::Object(fields));
+        return Ok(());
     }
     
     // 如果需要显示状态码和头部
     if show_headers || !silent {
-        ps_command.push_str("\nWrite-Output ('HTTP/1.1 {0} {1}' -f $response.StatusCode, $response.StatusDescription)");
+        outln!(io, "HTTP/1.1 {} {}", response.status, response.reason);
         
         if show_headers && !silent {
-            ps_command.push_str("\n$response.Headers | ForEach-Object {\n");
-            ps_command.push_str("    foreach ($key in $_.Keys) {\n");
-            ps_command.push_str("        Write-Output ('{0}: {1}' -f $key, $_.Item($key))\n");
-            ps_command.push_str("    }\n");
-            ps_command.push_str("}\n");
-            ps_command.push_str("Write-Output ''"); // 空行分隔头部和正文
+            for (key, value) in &response.headers {
+                outln!(io, "{}: {}", key, value);
+            }
+            outln!(io); // 空行分隔头部和正文
         }
     }
     
     // 处理响应内容
-    if output.is_some() {
+    if let Some(file_path) = output {
         // 保存到文件
-        let file_path = output.unwrap();
-        ps_command.push_str(&format!("\n[System.IO.File]::WriteAllBytes('{}', $response.Content)", file_path));
+        if let Err(err) = fs::write(file_path, &response.body) {
+            return Err(CatError::Failed(tr!("curl.write_failed", file_path, err)));
+        }
         if !silent {
-            ps_command.push_str(&format!("\nWrite-Output '✅ 响应已保存到 {}'", file_path));
+            outln!(io, "{}{}", style::icon(&io.stdout, "✅"), tr!("curl.saved", file_path));
         }
     } else if !silent {
-        // 尝试将内容作为字符串输出（处理文本内容）
-        ps_command.push_str("\ntry {\n");
-        ps_command.push_str("    $content = [System.Text.Encoding]::UTF8.GetString($response.Content)\n");
-        ps_command.push_str("    Write-Output $content\n");
-        ps_command.push_str("} catch {\n");
-        ps_command.push_str("    Write-Output '[二进制内容或无法解码]'\n");
-        ps_command.push_str("}");
+        // 按原始字节写出，以便重定向到文件
+        let _ = io.stdout.write_all(&response.body);
     }
+    Ok(())
+}
+
+/// 获取URL的HTTP状态码
+pub fn get_status_code(url: &str, json: bool, io: &mut Streams) -> Result<()> {
+    let request = Request {
+        method: "Head".to_string(),
+        ..Request::new(url)
+    };
+    let response = fetch(&request).map_err(|err| CatError::Failed(tr!("curl.request_failed", err)))?;
+    if json {
+        outln!(io, "{}", Value::object([
+            ("url", url.into()),
+            ("status", response.status.into()),
+            ("reason", response.reason.as_str().into()),
+        ]));
+    } else {
+        outln!(io, "{} - {}", response.status, response.reason);
+    }
+    Ok(())
+}
+
+/// PowerShell失败时把它的错误输出作为错误返回
+fn check_result(result: &Output) -> Result<()> {
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr).trim().to_string();
+        return Err(CatError::Failed(if stderr.is_empty() { tr!("curl.failed") } else { stderr }));
+    }
+    Ok(())
+}
+
+/// 解析 [`fetch`] 中PowerShell脚本的输出
+fn parse_response(output: &str) -> Result<Response> {
+    let invalid = || CatError::Failed(tr!("curl.bad_output"));
+    let mut lines = output.lines();
     
-    // 执行PowerShell命令
-    let output = Command::new("powershell.exe")
-        .arg("-Command")
-        .arg(&ps_command)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output();
+    let status = lines.next().and_then(|line| line.trim().parse().ok()).ok_or_else(invalid)?;
+    let reason = lines.next().unwrap_or("").trim().to_string();
     
-    match output {
-        Ok(result) => {
-            // 打印标准输出
-            if !result.stdout.is_empty() {
-                if let Ok(stdout_str) = String::from_utf8(result.stdout) {
-                    print!("{}", stdout_str);
-                }
-            }
-            
-            // 打印错误输出
-            if !result.stderr.is_empty() {
-                if let Ok(stderr_str) = String::from_utf8(result.stderr) {
-                    eprintln!("❌ {}", stderr_str.trim());
-                }
-            }
-        },
-        Err(err) => {
-            eprintln!("❌ 无法执行PowerShell命令: {}", err);
+    let mut headers = Vec::new();
+    for line in lines.by_ref() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(": ") {
+            headers.push((key.to_string(), value.to_string()));
         }
     }
+    
+    let encoded: String = lines.collect();
+    let body = decode_base64(encoded.trim()).ok_or_else(invalid)?;
+    Ok(Response { status, reason, headers, body })
 }
 
-/// 获取URL的HTTP状态码
-pub fn get_status_code(url: &str) {
-    // 使用PowerShell发送HEAD请求
-    let ps_command = format!(
-        "try {{
-            $response = Invoke-WebRequest -Uri '{}' -Method Head -UseBasicParsing
-            Write-Output ('{{0}} - {{1}}' -f $response.StatusCode, $response.StatusDescription)
-        }} catch {{
-            Write-Error ('请求失败: {{0}}' -f $_.Exception.Message)
-        }}",
-        url
-    );
+/// 解码标准base64，遇到非法字符时返回 `None`
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
     
-    let output = Command::new("powershell.exe")
-        .arg("-Command")
-        .arg(&ps_command)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output();
-    
-    match output {
-        Ok(result) => {
-            if !result.stdout.is_empty() {
-                if let Ok(stdout_str) = String::from_utf8(result.stdout) {
-                    println!("{}", stdout_str.trim());
-                }
-            }
-            if !result.stderr.is_empty() {
-                if let Ok(stderr_str) = String::from_utf8(result.stderr) {
-                    eprintln!("❌ {}", stderr_str.trim());
-                }
-            }
-        },
-        Err(err) => {
-            eprintln!("❌ 无法执行PowerShell命令: {}", err);
+    for c in input.bytes().filter(|&c| c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
         }
     }
-}
\ No newline at end of file
+    Some(bytes)
+}
+
+/// 把字符串写成PowerShell的单引号字面量
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
@@ -0,0 +1,734 @@
+//! 本地时间的换算和格式化，只使用标准库。
+//! 时区来自 `TZ` 环境变量（时区名、TZif文件路径或POSIX规则，例如 `CST-8`），
+//! 没有设置时Unix上读取 `/etc/localtime`，Windows上使用系统的时区设置。都读取失败时使用UTC
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+const WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+/// 一个时刻在某个时区的日期和时间
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTime {
+    /// Unix时间戳（秒）
+    pub timestamp: i64,
+    /// 秒以下的纳秒数
+    pub nanos: u32,
+    pub year: i64,
+    /// 1-12
+    pub month: u32,
+    /// 1-31
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// 0-6，0是星期日
+    pub weekday: u32,
+    /// 一年中的第几天，从0开始
+    pub yday: u32,
+    /// 相对UTC的秒数，东八区为28800
+    pub offset: i32,
+    /// 时区缩写，例如 `CST`
+    pub zone: String,
+}
+
+impl DateTime {
+    /// `time` 在当前时区的本地时间
+    pub fn local(time: SystemTime) -> DateTime {
+        let (timestamp, nanos) = split(time);
+        let zone = local_zone();
+        let zone_type = zone.zone_at(timestamp);
+        DateTime::new(timestamp, nanos, zone_type.offset, &zone_type.abbr)
+    }
+
+    fn new(timestamp: i64, nanos: u32, offset: i32, zone: &str) -> DateTime {
+        let local = timestamp + i64::from(offset);
+        let days = local.div_euclid(SECS_PER_DAY);
+        let secs = local.rem_euclid(SECS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        DateTime {
+            timestamp,
+            nanos,
+            year,
+            month,
+            day,
+            hour: secs / 3600,
+            minute: secs / 60 % 60,
+            second: secs % 60,
+            weekday: (days + 4).rem_euclid(7) as u32,
+            yday: (days - days_from_civil(year, 1, 1)) as u32,
+            offset,
+            zone: zone.to_string(),
+        }
+    }
+
+    /// 按 `strftime` 风格的格式输出，支持 `%Y %m %d %H %M %S %b %e %F %T %z %Z %N` 等常用转换，
+    /// 不认识的转换原样输出
+    pub fn format(&self, pattern: &str) -> String {
+        let mut result = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            let Some(spec) = chars.next() else {
+                result.push('%');
+                break;
+            };
+            let hour12 = (self.hour + 11) % 12 + 1;
+            let text = match spec {
+                'a' => WEEKDAYS[self.weekday as usize][..3].to_string(),
+                'A' => WEEKDAYS[self.weekday as usize].to_string(),
+                'b' | 'h' => MONTHS[self.month as usize - 1][..3].to_string(),
+                'B' => MONTHS[self.month as usize - 1].to_string(),
+                'c' => self.format("%a %b %e %H:%M:%S %Y"),
+                'C' => format!("{:02}", self.year.div_euclid(100)),
+                'd' => format!("{:02}", self.day),
+                'D' => self.format("%m/%d/%y"),
+                'e' => format!("{:>2}", self.day),
+                'F' => self.format("%Y-%m-%d"),
+                'H' => format!("{:02}", self.hour),
+                'I' => format!("{:02}", hour12),
+                'j' => format!("{:03}", self.yday + 1),
+                'k' => format!("{:>2}", self.hour),
+                'l' => format!("{:>2}", hour12),
+                'm' => format!("{:02}", self.month),
+                'M' => format!("{:02}", self.minute),
+                'n' => "\n".to_string(),
+                'N' => format!("{:09}", self.nanos),
+                'p' => if self.hour < 12 { "AM" } else { "PM" }.to_string(),
+                'r' => self.format("%I:%M:%S %p"),
+                'R' => self.format("%H:%M"),
+                's' => self.timestamp.to_string(),
+                'S' => format!("{:02}", self.second),
+                't' => "\t".to_string(),
+                'T' => self.format("%H:%M:%S"),
+                'u' => ((self.weekday + 6) % 7 + 1).to_string(),
+                'w' => self.weekday.to_string(),
+                'y' => format!("{:02}", self.year.rem_euclid(100)),
+                'Y' => self.year.to_string(),
+                'z' => {
+                    let sign = if self.offset < 0 { '-' } else { '+' };
+                    let offset = self.offset.unsigned_abs();
+                    format!("{}{:02}{:02}", sign, offset / 3600, offset / 60 % 60)
+                },
+                'Z' => self.zone.clone(),
+                '%' => "%".to_string(),
+                other => format!("%{}", other),
+            };
+            result.push_str(&text);
+        }
+        result
+    }
+}
+
+/// 把 `SystemTime` 拆成Unix时间戳和纳秒，1970年以前的时间戳为负数
+fn split(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => (duration.as_secs() as i64, duration.subsec_nanos()),
+        Err(err) => {
+            let duration = err.duration();
+            match duration.subsec_nanos() {
+                0 => (-(duration.as_secs() as i64), 0),
+                nanos => (-(duration.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+            }
+        },
+    }
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// 1970-01-01以来的天数对应的年月日
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// 年月日对应的1970-01-01以来的天数
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// 时区中的一种本地时间类型，例如标准时间或夏令时
+#[derive(Debug, Clone)]
+struct ZoneType {
+    offset: i32,
+    abbr: String,
+}
+
+/// 一个时区：TZif文件中的历史切换时刻，加上最后一次切换以后使用的POSIX规则
+#[derive(Debug)]
+struct TimeZone {
+    transitions: Vec<i64>,
+    indices: Vec<u8>,
+    types: Vec<ZoneType>,
+    rule: Option<Rule>,
+}
+
+impl TimeZone {
+    fn utc() -> TimeZone {
+        TimeZone::from_rule(Rule { std: ZoneType { offset: 0, abbr: "UTC".to_string() }, dst: None })
+    }
+
+    fn from_rule(rule: Rule) -> TimeZone {
+        TimeZone { transitions: Vec::new(), indices: Vec::new(), types: vec![rule.std.clone()], rule: Some(rule) }
+    }
+
+    /// Unix时间戳 `timestamp` 时使用的本地时间类型
+    fn zone_at(&self, timestamp: i64) -> &ZoneType {
+        let index = self.transitions.partition_point(|&at| at <= timestamp);
+        if index == self.transitions.len() {
+            if let Some(rule) = &self.rule {
+                return rule.zone_at(timestamp);
+            }
+        }
+        match index {
+            0 => &self.types[0],
+            index => &self.types[usize::from(self.indices[index - 1])],
+        }
+    }
+}
+
+/// POSIX `TZ` 规则，例如 `CST-8` 或 `EST5EDT,M3.2.0,M11.1.0`
+#[derive(Debug)]
+struct Rule {
+    std: ZoneType,
+    dst: Option<Dst>,
+}
+
+/// 夏令时及其开始和结束的时间，开始时间按标准时间计算，结束时间按夏令时计算
+#[derive(Debug)]
+struct Dst {
+    zone: ZoneType,
+    start: RuleTime,
+    end: RuleTime,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RuleTime {
+    date: RuleDate,
+    /// 当天0点以后的秒数，可以是负数或超过24小时
+    time: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RuleDate {
+    /// `Jn`：1-365，不计算2月29日
+    Julian(u32),
+    /// `n`：0-365，计算2月29日
+    Day(u32),
+    /// `Mm.w.d`：m月的第w个星期d，w为5表示最后一个
+    Month { month: u32, week: u32, weekday: u32 },
+}
+
+impl Rule {
+    fn zone_at(&self, timestamp: i64) -> &ZoneType {
+        let Some(dst) = &self.dst else {
+            return &self.std;
+        };
+        let (year, _, _) = civil_from_days((timestamp + i64::from(self.std.offset)).div_euclid(SECS_PER_DAY));
+        let start = dst.start.local_seconds(year) - i64::from(self.std.offset);
+        let end = dst.end.local_seconds(year) - i64::from(dst.zone.offset);
+        // 南半球的夏令时跨年，开始时间晚于结束时间
+        let in_dst = if start <= end {
+            start <= timestamp && timestamp < end
+        } else {
+            !(end <= timestamp && timestamp < start)
+        };
+        if in_dst { &dst.zone } else { &self.std }
+    }
+}
+
+impl RuleTime {
+    /// `year` 年中这个时刻的本地时间，以1970-01-01 00:00以来的秒数表示
+    fn local_seconds(&self, year: i64) -> i64 {
+        let first_day = days_from_civil(year, 1, 1);
+        let day = match self.date {
+            RuleDate::Julian(n) => {
+                let n = i64::from(n) - 1;
+                first_day + n + i64::from(is_leap(year) && n >= 59)
+            },
+            RuleDate::Day(n) => first_day + i64::from(n),
+            RuleDate::Month { month, week, weekday } => {
+                let first = days_from_civil(year, month, 1);
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first + (i64::from(weekday) - first_weekday).rem_euclid(7) + 7 * (i64::from(week) - 1);
+                let last = first + i64::from(days_in_month(year, month)) - 1;
+                while day > last {
+                    day -= 7;
+                }
+                day
+            },
+        };
+        day * SECS_PER_DAY + i64::from(self.time)
+    }
+}
+
+/// 解析POSIX `TZ` 规则，格式不正确时返回 `None`
+fn parse_rule(text: &str) -> Option<Rule> {
+    let mut parser = RuleParser { text: text.as_bytes(), pos: 0 };
+    let std = ZoneType { abbr: parser.name()?, offset: -parser.offset()? };
+    if parser.done() {
+        return Some(Rule { std, dst: None });
+    }
+    let abbr = parser.name()?;
+    let offset = match parser.peek() {
+        None | Some(b',') => std.offset + 3600,
+        Some(_) => -parser.offset()?,
+    };
+    // 没有给出切换规则时使用美国的规则，和glibc一致
+    let (start, end) = if parser.eat(b',') {
+        let start = parser.rule_time()?;
+        parser.eat(b',').then_some(())?;
+        (start, parser.rule_time()?)
+    } else {
+        (
+            RuleTime { date: RuleDate::Month { month: 3, week: 2, weekday: 0 }, time: 7200 },
+            RuleTime { date: RuleDate::Month { month: 11, week: 1, weekday: 0 }, time: 7200 },
+        )
+    };
+    parser.done().then_some(Rule { std, dst: Some(Dst { zone: ZoneType { offset, abbr }, start, end }) })
+}
+
+struct RuleParser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl RuleParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    fn done(&self) -> bool {
+        self.pos == self.text.len()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matched = self.peek() == Some(byte);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn take_while(&mut self, accept: impl Fn(u8) -> bool) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(&accept) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.pos]).unwrap_or_default()
+    }
+
+    /// 时区缩写：字母，或者 `<+08>` 这样用尖括号括起来的任意字符
+    fn name(&mut self) -> Option<String> {
+        let name = if self.eat(b'<') {
+            let name = self.take_while(|byte| byte != b'>').to_string();
+            self.eat(b'>').then_some(name)?
+        } else {
+            self.take_while(|byte| byte.is_ascii_alphabetic()).to_string()
+        };
+        (!name.is_empty()).then_some(name)
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        self.take_while(|byte| byte.is_ascii_digit()).parse().ok()
+    }
+
+    /// `[+-]hh[:mm[:ss]]`，返回秒数
+    fn offset(&mut self) -> Option<i32> {
+        let sign = if self.eat(b'-') {
+            -1
+        } else {
+            self.eat(b'+');
+            1
+        };
+        let mut seconds = self.number()? * 3600;
+        if self.eat(b':') {
+            seconds += self.number()? * 60;
+            if self.eat(b':') {
+                seconds += self.number()?;
+            }
+        }
+        Some(sign * i32::try_from(seconds).ok()?)
+    }
+
+    /// `Jn`、`n` 或 `Mm.w.d`，后面可以带有 `/时间`，默认是凌晨2点
+    fn rule_time(&mut self) -> Option<RuleTime> {
+        let date = if self.eat(b'J') {
+            let n = self.number()?;
+            (1..=365).contains(&n).then_some(RuleDate::Julian(n))?
+        } else if self.eat(b'M') {
+            let month = self.number()?;
+            self.eat(b'.').then_some(())?;
+            let week = self.number()?;
+            self.eat(b'.').then_some(())?;
+            let weekday = self.number()?;
+            ((1..=12).contains(&month) && (1..=5).contains(&week) && weekday <= 6)
+                .then_some(RuleDate::Month { month, week, weekday })?
+        } else {
+            let n = self.number()?;
+            (n <= 365).then_some(RuleDate::Day(n))?
+        };
+        let time = if self.eat(b'/') { self.offset()? } else { 7200 };
+        Some(RuleTime { date, time })
+    }
+}
+
+/// 顺序读取TZif文件中的大端整数
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.data.len() {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        Some(i32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        Some(i64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    /// 文件头：版本号和六个计数 isutcnt、isstdcnt、leapcnt、timecnt、typecnt、charcnt
+    fn header(&mut self) -> Option<(u8, [usize; 6])> {
+        if self.take(4)? != b"TZif" {
+            return None;
+        }
+        let version = self.take(1)?[0];
+        self.take(15)?;
+        let mut counts = [0; 6];
+        for count in &mut counts {
+            *count = self.u32()? as usize;
+        }
+        Some((version, counts))
+    }
+}
+
+/// 解析TZif文件（RFC 8536）。版本2以上的文件使用64位的数据块和末尾的POSIX规则
+fn parse_tzif(data: &[u8]) -> Option<TimeZone> {
+    let mut reader = Reader { data };
+    let (version, mut counts) = reader.header()?;
+    let mut time_size = 4;
+    if version >= b'2' {
+        let [isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt] = counts;
+        reader.take(timecnt * 5 + typecnt * 6 + charcnt + leapcnt * 8 + isstdcnt + isutcnt)?;
+        (_, counts) = reader.header()?;
+        time_size = 8;
+    }
+    let [isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt] = counts;
+
+    let transitions = (0..timecnt)
+        .map(|_| if time_size == 8 { reader.i64() } else { reader.i32().map(i64::from) })
+        .collect::<Option<Vec<_>>>()?;
+    let indices = reader.take(timecnt)?.to_vec();
+    let raw_types = (0..typecnt)
+        .map(|_| Some((reader.i32()?, reader.take(2)?[1])))
+        .collect::<Option<Vec<_>>>()?;
+    let chars = reader.take(charcnt)?;
+    reader.take(leapcnt * (time_size + 4) + isstdcnt + isutcnt)?;
+
+    let types: Vec<ZoneType> = raw_types
+        .into_iter()
+        .map(|(offset, abbr_index)| {
+            let abbr = chars.get(usize::from(abbr_index)..).unwrap_or_default();
+            let end = abbr.iter().position(|&byte| byte == 0).unwrap_or(abbr.len());
+            ZoneType { offset, abbr: String::from_utf8_lossy(&abbr[..end]).into_owned() }
+        })
+        .collect();
+    if types.is_empty() || indices.iter().any(|&index| usize::from(index) >= types.len()) {
+        return None;
+    }
+
+    // 末尾的 "\n规则\n" 描述最后一次切换以后的时间
+    let rule = if version >= b'2' {
+        let footer = String::from_utf8_lossy(reader.data);
+        footer.strip_prefix('\n').and_then(|rest| rest.split('\n').next()).and_then(parse_rule)
+    } else {
+        None
+    };
+    Some(TimeZone { transitions, indices, types, rule })
+}
+
+/// 按 `TZ` 的值加载时区：绝对路径或zoneinfo目录中的时区名，否则作为POSIX规则解析
+fn load_tz(tz: &str) -> Option<TimeZone> {
+    let name = tz.strip_prefix(':').unwrap_or(tz);
+    if name.is_empty() {
+        return Some(TimeZone::utc());
+    }
+    let path = if name.starts_with('/') {
+        Some(PathBuf::from(name))
+    } else if !name.split('/').any(|part| part == "..") {
+        let dir = config::var_os("TZDIR").unwrap_or_else(|| "/usr/share/zoneinfo".into());
+        Some(PathBuf::from(dir).join(name))
+    } else {
+        None
+    };
+    path.and_then(|path| fs::read(path).ok())
+        .and_then(|data| parse_tzif(&data))
+        .or_else(|| parse_rule(name).map(TimeZone::from_rule))
+}
+
+fn load_zone(tz: Option<&OsString>) -> TimeZone {
+    let zone = match tz {
+        Some(tz) => load_tz(&tz.to_string_lossy()),
+        None => system_zone(),
+    };
+    zone.unwrap_or_else(TimeZone::utc)
+}
+
+#[cfg(not(windows))]
+fn system_zone() -> Option<TimeZone> {
+    parse_tzif(&fs::read("/etc/localtime").ok()?)
+}
+
+#[cfg(windows)]
+fn system_zone() -> Option<TimeZone> {
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemTime16 {
+        year: u16,
+        month: u16,
+        day_of_week: u16,
+        day: u16,
+        hour: u16,
+        minute: u16,
+        second: u16,
+        milliseconds: u16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct TimeZoneInformation {
+        bias: i32,
+        standard_name: [u16; 32],
+        standard_date: SystemTime16,
+        standard_bias: i32,
+        daylight_name: [u16; 32],
+        daylight_date: SystemTime16,
+        daylight_bias: i32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTimeZoneInformation(info: *mut TimeZoneInformation) -> u32;
+    }
+
+    const TIME_ZONE_ID_INVALID: u32 = u32::MAX;
+
+    let mut info = TimeZoneInformation::default();
+    // SAFETY: info指向有效的局部变量
+    if unsafe { GetTimeZoneInformation(&mut info) } == TIME_ZONE_ID_INVALID {
+        return None;
+    }
+    let name = |wide: &[u16]| {
+        let end = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+        String::from_utf16_lossy(&wide[..end])
+    };
+    // Windows的切换日期是“某月第几个星期几”，和POSIX规则的 `Mm.w.d` 相同
+    let rule_time = |date: &SystemTime16| RuleTime {
+        date: RuleDate::Month { month: u32::from(date.month), week: u32::from(date.day), weekday: u32::from(date.day_of_week) },
+        time: i32::from(date.hour) * 3600 + i32::from(date.minute) * 60 + i32::from(date.second),
+    };
+    let std = ZoneType { offset: -(info.bias + info.standard_bias) * 60, abbr: name(&info.standard_name) };
+    let dst = (info.daylight_date.month != 0).then(|| Dst {
+        zone: ZoneType { offset: -(info.bias + info.daylight_bias) * 60, abbr: name(&info.daylight_name) },
+        start: rule_time(&info.daylight_date),
+        end: rule_time(&info.standard_date),
+    });
+    Some(TimeZone::from_rule(Rule { std, dst }))
+}
+
+/// 当前的时区。REPL中可以用 `export TZ=...` 修改，所以按 `TZ` 的值缓存
+fn local_zone() -> Arc<TimeZone> {
+    static CACHE: Mutex<Option<(Option<OsString>, Arc<TimeZone>)>> = Mutex::new(None);
+    let tz = config::var_os("TZ");
+    let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    match &*cache {
+        Some((key, zone)) if *key == tz => zone.clone(),
+        _ => {
+            let zone = Arc::new(load_zone(tz.as_ref()));
+            *cache = Some((tz, zone.clone()));
+            zone
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// UTC的 `year-month-day hour:00:00` 的时间戳
+    fn utc(year: i64, month: u32, day: u32, hour: i64) -> i64 {
+        days_from_civil(year, month, day) * SECS_PER_DAY + hour * 3600
+    }
+
+    /// `timestamp` 前一秒和当时的时区缩写
+    fn around(zone: &TimeZone, timestamp: i64) -> (&str, &str) {
+        (&zone.zone_at(timestamp - 1).abbr, &zone.zone_at(timestamp).abbr)
+    }
+
+    fn rule(text: &str) -> TimeZone {
+        TimeZone::from_rule(parse_rule(text).unwrap())
+    }
+
+    #[test]
+    fn converts_civil_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(days_in_month(2100, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+
+        let date = DateTime::new(1_700_000_000, 5, 8 * 3600, "CST");
+        assert_eq!(date.format("%F %T %a %j %z %Z %s.%N"), "2023-11-15 06:13:20 Wed 319 +0800 CST 1700000000.000000005");
+        assert_eq!(date.format("%e %b %l%p %% %q"), "15 Nov  6AM % %q");
+        assert_eq!(DateTime::new(-1, 0, -5 * 3600 - 1800, "X").format("%F %T %z"), "1969-12-31 18:29:59 -0530");
+    }
+
+    #[test]
+    fn switches_at_northern_dst_transitions() {
+        let zone = rule("EST5EDT,M3.2.0,M11.1.0");
+        // 2024年3月10日凌晨2点（EST）开始夏令时，11月3日凌晨2点（EDT）结束
+        assert_eq!(around(&zone, utc(2024, 3, 10, 7)), ("EST", "EDT"));
+        assert_eq!(around(&zone, utc(2024, 11, 3, 6)), ("EDT", "EST"));
+        assert_eq!(zone.zone_at(utc(2024, 7, 1, 0)).offset, -4 * 3600);
+
+        // 最后一个星期日，结束时间按夏令时计算
+        let zone = rule("CET-1CEST,M3.5.0,M10.5.0/3");
+        assert_eq!(around(&zone, utc(2024, 3, 31, 1)), ("CET", "CEST"));
+        assert_eq!(around(&zone, utc(2024, 10, 27, 1)), ("CEST", "CET"));
+        assert_eq!(around(&zone, utc(2021, 3, 28, 1)), ("CET", "CEST"));
+    }
+
+    #[test]
+    fn switches_at_southern_dst_transitions() {
+        let zone = rule("AEST-10AEDT,M10.1.0,M4.1.0/3");
+        // 夏令时跨年：4月7日凌晨3点（AEDT）结束，10月6日凌晨2点（AEST）开始
+        assert_eq!(around(&zone, utc(2024, 4, 6, 16)), ("AEDT", "AEST"));
+        assert_eq!(around(&zone, utc(2024, 10, 5, 16)), ("AEST", "AEDT"));
+        assert_eq!(zone.zone_at(utc(2024, 1, 1, 0)).offset, 11 * 3600);
+    }
+
+    #[test]
+    fn parses_posix_rules() {
+        let zone = rule("<+0545>-5:45");
+        assert_eq!((zone.zone_at(0).abbr.as_str(), zone.zone_at(0).offset), ("+0545", 5 * 3600 + 45 * 60));
+        // 没有切换规则时使用美国的规则，夏令时默认比标准时间快一小时
+        let zone = rule("PST8PDT");
+        assert_eq!(zone.zone_at(utc(2024, 7, 1, 0)).offset, -7 * 3600);
+
+        // `Jn` 不计算2月29日，`n` 从0开始并且计算2月29日
+        let julian = RuleTime { date: RuleDate::Julian(60), time: 0 };
+        let day = RuleTime { date: RuleDate::Day(59), time: 0 };
+        assert_eq!(julian.local_seconds(2024), utc(2024, 3, 1, 0));
+        assert_eq!(day.local_seconds(2024), utc(2024, 2, 29, 0));
+        assert_eq!(julian.local_seconds(2023), day.local_seconds(2023));
+
+        for text in ["", "EST", "5", "EST5EDT,M3.2.0", "EST5EDT,M13.1.0,M11.1.0", "EST5EDT,J0,J300", "<+08-8", "EST5EDT4,M3.2.0/2,"] {
+            assert!(parse_rule(text).is_none(), "{}", text);
+        }
+    }
+
+    /// 生成版本2的TZif数据，版本1的数据块为空
+    fn tzif(transitions: &[(i64, u8)], types: &[(i32, &str)], footer: &str) -> Vec<u8> {
+        let header = |counts: [usize; 6]| {
+            let mut data = b"TZif2".to_vec();
+            data.extend([0; 15]);
+            for count in counts {
+                data.extend((count as u32).to_be_bytes());
+            }
+            data
+        };
+        let mut chars = Vec::new();
+        let mut raw_types = Vec::new();
+        for (offset, abbr) in types {
+            raw_types.extend(offset.to_be_bytes());
+            raw_types.extend([0, chars.len() as u8]);
+            chars.extend(abbr.bytes().chain([0]));
+        }
+
+        let mut data = header([0; 6]);
+        data.extend(header([0, 0, 0, transitions.len(), types.len(), chars.len()]));
+        for (at, _) in transitions {
+            data.extend(at.to_be_bytes());
+        }
+        data.extend(transitions.iter().map(|(_, index)| index));
+        data.extend(raw_types);
+        data.extend(chars);
+        data.extend(format!("\n{}\n", footer).bytes());
+        data
+    }
+
+    #[test]
+    fn reads_tzif_transitions_and_footer() {
+        // 1991年以前使用 LMT，之后在 XST 和 XDT 之间切换，最后一次切换以后使用末尾的规则
+        let data = tzif(
+            &[(utc(1990, 1, 1, 0), 1), (utc(1991, 3, 31, 1), 2), (utc(1991, 10, 27, 1), 1)],
+            &[(1800, "LMT"), (3600, "XST"), (7200, "XDT")],
+            "XST-1XDT,M3.5.0,M10.5.0/3",
+        );
+        let zone = parse_tzif(&data).unwrap();
+        assert_eq!(zone.zone_at(utc(1980, 1, 1, 0)).abbr, "LMT");
+        assert_eq!(around(&zone, utc(1990, 1, 1, 0)), ("LMT", "XST"));
+        assert_eq!(around(&zone, utc(1991, 3, 31, 1)), ("XST", "XDT"));
+        assert_eq!(around(&zone, utc(1991, 10, 27, 1)), ("XDT", "XST"));
+        assert_eq!(around(&zone, utc(2024, 3, 31, 1)), ("XST", "XDT"));
+        assert_eq!(around(&zone, utc(2024, 10, 27, 1)), ("XDT", "XST"));
+
+        let mut broken = data.clone();
+        broken[0] = b'X';
+        assert!(parse_tzif(&broken).is_none());
+        assert!(parse_tzif(&data[..60]).is_none());
+        // 类型索引越界
+        assert!(parse_tzif(&tzif(&[(0, 5)], &[(0, "UTC")], "UTC0")).is_none());
+    }
+}
@@ -0,0 +1,264 @@
+use std::io::{self, IsTerminal, Stdout, Write};
+
+use crate::completion;
+use crate::i18n;
+use crate::shell::Shell;
+use crate::terminal::{self, Key, KeyInput, RawMode, TerminalInput};
+
+/// 读入一行的结果
+pub enum ReadLine {
+    /// 输入的一行，不包含末尾的换行
+    Line(String),
+    /// 按下了 Ctrl+C，当前输入被丢弃
+    Interrupted,
+    /// 输入结束（Ctrl+D）
+    Eof,
+}
+
+/// 显示提示符并读入一行。标准输入是终端时支持行编辑、用方向键浏览历史记录、Ctrl+R 搜索和 Tab 补全，
+/// 否则（例如从管道读入命令）按普通的行读入
+pub fn read_line(prompt: &str, shell: &Shell) -> io::Result<ReadLine> {
+    let raw = if io::stdin().is_terminal() { RawMode::enable() } else { None };
+    match raw {
+        Some(_raw) => Editor::new(prompt, shell).run(),
+        None => read_plain_line(prompt),
+    }
+}
+
+fn read_plain_line(prompt: &str) -> io::Result<ReadLine> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Ok(ReadLine::Eof);
+    }
+    let len = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(len);
+    Ok(ReadLine::Line(line))
+}
+
+/// 单行编辑器的状态
+struct Editor<'a> {
+    prompt: &'a str,
+    /// 用来读取历史记录和补全命令名
+    shell: &'a Shell,
+    buffer: Vec<char>,
+    /// 光标在 `buffer` 中的位置
+    cursor: usize,
+    /// 正在浏览的历史记录位置，等于记录条数时表示正在编辑的新行
+    history_index: usize,
+    /// 开始浏览历史记录之前输入的内容
+    draft: Vec<char>,
+    out: Stdout,
+}
+
+impl<'a> Editor<'a> {
+    fn new(prompt: &'a str, shell: &'a Shell) -> Self {
+        Editor {
+            prompt,
+            shell,
+            buffer: Vec::new(),
+            cursor: 0,
+            history_index: shell.history.entries().len(),
+            draft: Vec::new(),
+            out: io::stdout(),
+        }
+    }
+
+    fn run(&mut self) -> io::Result<ReadLine> {
+        let mut stdin = TerminalInput::default();
+        self.render()?;
+
+        loop {
+            let key = match terminal::read_key(&mut stdin)? {
+                Some(key) => key,
+                None => return Ok(ReadLine::Eof),
+            };
+
+            match key {
+                Key::Enter => return self.finish(),
+                Key::Ctrl('c') => {
+                    write!(self.out, "^C\r\n")?;
+                    return Ok(ReadLine::Interrupted);
+                },
+                Key::Ctrl('d') if self.buffer.is_empty() => return Ok(ReadLine::Eof),
+                Key::Ctrl('d') | Key::Delete if self.cursor < self.buffer.len() => {
+                    self.buffer.remove(self.cursor);
+                },
+                Key::Char(c) => {
+                    self.buffer.insert(self.cursor, c);
+                    self.cursor += 1;
+                },
+                Key::Backspace if self.cursor > 0 => {
+                    self.cursor -= 1;
+                    self.buffer.remove(self.cursor);
+                },
+                Key::Left | Key::Ctrl('b') => self.cursor = self.cursor.saturating_sub(1),
+                Key::Right | Key::Ctrl('f') => self.cursor = (self.cursor + 1).min(self.buffer.len()),
+                Key::Home | Key::Ctrl('a') => self.cursor = 0,
+                Key::End | Key::Ctrl('e') => self.cursor = self.buffer.len(),
+                Key::Ctrl('u') => {
+                    self.buffer.drain(..self.cursor);
+                    self.cursor = 0;
+                },
+                Key::Ctrl('k') => self.buffer.truncate(self.cursor),
+                Key::Ctrl('w') => {
+                    // 删除光标前的一个单词以及它后面的空白
+                    let mut start = self.cursor;
+                    while start > 0 && self.buffer[start - 1].is_whitespace() {
+                        start -= 1;
+                    }
+                    while start > 0 && !self.buffer[start - 1].is_whitespace() {
+                        start -= 1;
+                    }
+                    self.buffer.drain(start..self.cursor);
+                    self.cursor = start;
+                },
+                Key::Ctrl('l') => write!(self.out, "\x1b[2J\x1b[H")?,
+                Key::Tab => self.complete()?,
+                Key::Up | Key::Ctrl('p') => self.browse_history(-1),
+                Key::Down | Key::Ctrl('n') => self.browse_history(1),
+                Key::Ctrl('r') => {
+                    let accepted = self.reverse_search(&mut stdin)?;
+                    if accepted {
+                        return self.finish();
+                    }
+                },
+                _ => {},
+            }
+            self.render()?;
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<ReadLine> {
+        self.cursor = self.buffer.len();
+        self.render()?;
+        write!(self.out, "\r\n")?;
+        self.out.flush()?;
+        Ok(ReadLine::Line(self.buffer.iter().collect()))
+    }
+
+    /// 重新绘制提示符和当前输入，并把光标移到正确的位置
+    fn render(&mut self) -> io::Result<()> {
+        let line: String = self.buffer.iter().collect();
+        let tail: String = self.buffer[self.cursor..].iter().collect();
+        write!(self.out, "\r{}{}\x1b[K", self.prompt, line)?;
+        let width = terminal::display_width(&tail);
+        if width > 0 {
+            write!(self.out, "\x1b[{}D", width)?;
+        }
+        self.out.flush()
+    }
+
+    /// 补全光标处的单词。只有一个候选项时直接补全，有多个时先补全共同的前缀，
+    /// 前缀无法再延长时在输入行下方列出所有候选项
+    fn complete(&mut self) -> io::Result<()> {
+        let completion = completion::complete(&self.buffer, self.cursor, self.shell);
+        let current = self.cursor - completion.start;
+
+        match completion.candidates.as_slice() {
+            [] => write!(self.out, "\x07")?,
+            [candidate] => {
+                let mut text = candidate.replacement.clone();
+                // 目录补全后可以继续输入下一级
+                if !text.ends_with('/') {
+                    text.push(' ');
+                }
+                self.replace(completion.start, &text);
+            },
+            candidates => {
+                let prefix = completion::common_prefix(candidates);
+                if prefix.chars().count() > current {
+                    self.replace(completion.start, &prefix);
+                } else {
+                    let items: Vec<String> = candidates.iter().map(|candidate| candidate.display.clone()).collect();
+                    write!(self.out, "\r\n")?;
+                    for line in terminal::columns(&items, terminal::width()) {
+                        write!(self.out, "{}\r\n", line)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 把 `start` 到光标之间的内容替换成 `text`
+    fn replace(&mut self, start: usize, text: &str) {
+        let chars: Vec<char> = text.chars().collect();
+        let len = chars.len();
+        self.buffer.splice(start..self.cursor, chars);
+        self.cursor = start + len;
+    }
+
+    /// 向前（`-1`）或向后（`1`）切换历史记录
+    fn browse_history(&mut self, step: isize) {
+        let entries = self.shell.history.entries();
+        let index = match self.history_index.checked_add_signed(step) {
+            Some(index) if index <= entries.len() => index,
+            _ => return,
+        };
+
+        if self.history_index == entries.len() {
+            self.draft = self.buffer.clone();
+        }
+        self.history_index = index;
+        self.buffer = match entries.get(index) {
+            Some(entry) => entry.chars().collect(),
+            None => self.draft.clone(),
+        };
+        self.cursor = self.buffer.len();
+    }
+
+    /// Ctrl+R 反向增量搜索：输入的内容会实时在历史记录中查找，再按 Ctrl+R 查找更早的记录。
+    /// 回车执行找到的命令（返回 `true`），其他编辑键把它放到输入行中继续编辑，
+    /// Ctrl+C 或 Ctrl+G 取消搜索
+    fn reverse_search(&mut self, stdin: &mut impl KeyInput) -> io::Result<bool> {
+        let entries = self.shell.history.entries();
+        let mut query = String::new();
+        let mut found = None;
+        let mut failed = false;
+
+        loop {
+            let matched = found.map(|index: usize| entries[index].as_str()).unwrap_or("");
+            let label = i18n::text(if failed { "editor.search_failed" } else { "editor.search" });
+            write!(self.out, "\r({})'{}': {}\x1b[K", label, query, matched)?;
+            self.out.flush()?;
+
+            let key = match terminal::read_key(stdin)? {
+                Some(key) => key,
+                None => return Ok(false),
+            };
+
+            // 从 `before` 之前开始查找，找不到时保留上一次的结果
+            let before = match key {
+                Key::Char(c) => {
+                    query.push(c);
+                    found.map_or(entries.len(), |index| index + 1)
+                },
+                Key::Backspace => {
+                    query.pop();
+                    entries.len()
+                },
+                Key::Ctrl('r') => found.unwrap_or(entries.len()),
+                Key::Ctrl('c') | Key::Ctrl('g') => return Ok(false),
+                _ => {
+                    if let Some(index) = found {
+                        self.buffer = entries[index].chars().collect();
+                        self.cursor = self.buffer.len();
+                        self.history_index = index;
+                    }
+                    return Ok(key == Key::Enter);
+                }
+            };
+
+            match self.shell.history.search(&query, before) {
+                Some(index) => {
+                    found = Some(index);
+                    failed = false;
+                },
+                None => failed = !query.is_empty(),
+            }
+        }
+    }
+}
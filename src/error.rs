@@ -0,0 +1,134 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::json::{ToJson, Value};
+use crate::streams::Streams;
+
+/// 子命令执行失败的原因，决定catshell的退出状态码
+#[derive(Debug)]
+pub enum CatError {
+    /// 一般的失败，例如文件不存在或者没有权限，退出状态码1
+    Failed(String),
+    /// 用法错误，例如选项冲突或者缺少参数，退出状态码2
+    Usage(String),
+    /// 找不到需要启动的外部程序，退出状态码127
+    NotFound(String),
+    /// 多个参数分别失败，例如 `rm a b` 中两个文件都不存在
+    Multiple(Vec<CatError>),
+}
+
+pub type Result<T> = std::result::Result<T, CatError>;
+
+impl CatError {
+    /// 和POSIX shell一致的退出状态码，多个错误时取最大的一个
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CatError::Failed(_) => 1,
+            CatError::Usage(_) => 2,
+            CatError::NotFound(_) => 127,
+            CatError::Multiple(errors) => errors.iter().map(CatError::exit_code).max().unwrap_or(1),
+        }
+    }
+
+    /// 启动外部程序失败，程序不存在时返回 [`CatError::NotFound`]
+    pub fn spawn(program: &str, err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::NotFound {
+            CatError::NotFound(tr!("error.command_not_found", program))
+        } else {
+            CatError::Failed(tr!("error.spawn_failed", program, err))
+        }
+    }
+
+    /// 把逐个参数处理时收集到的错误合并成一个，没有错误时返回 `Ok`
+    pub fn collect(mut errors: Vec<CatError>) -> Result<()> {
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(CatError::Multiple(errors)),
+        }
+    }
+
+    /// 把错误输出到标准错误，每个错误一行
+    pub fn report(&self, io: &mut Streams) {
+        match self {
+            CatError::Multiple(errors) => errors.iter().for_each(|err| err.report(io)),
+            err => failln!(io, "{}", err),
+        }
+    }
+
+    /// `--json` 模式下的 [`CatError::report`]，每个错误输出一个JSON对象
+    pub fn report_json(&self, io: &mut Streams) {
+        match self {
+            CatError::Multiple(errors) => errors.iter().for_each(|err| err.report_json(io)),
+            err => errln!(io, "{}", err.to_json()),
+        }
+    }
+}
+
+impl ToJson for CatError {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("error", self.to_string().into()),
+            ("exit_code", i64::from(self.exit_code()).into()),
+        ])
+    }
+}
+
+impl fmt::Display for CatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatError::Failed(message) | CatError::Usage(message) | CatError::NotFound(message) => {
+                write!(f, "{}", message)
+            },
+            CatError::Multiple(errors) => {
+                let messages: Vec<String> = errors.iter().map(CatError::to_string).collect();
+                write!(f, "{}", messages.join("; "))
+            },
+        }
+    }
+}
+
+impl std::error::Error for CatError {}
+
+impl From<io::Error> for CatError {
+    fn from(err: io::Error) -> Self {
+        CatError::Failed(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::{with_style, When};
+    use crate::test_util::{output, streams};
+
+    #[test]
+    fn maps_errors_to_exit_codes() {
+        assert_eq!(CatError::Failed("a".into()).exit_code(), 1);
+        assert_eq!(CatError::Usage("a".into()).exit_code(), 2);
+        assert_eq!(CatError::spawn("nope", io::ErrorKind::NotFound.into()).exit_code(), 127);
+        assert_eq!(CatError::spawn("nope", io::ErrorKind::PermissionDenied.into()).exit_code(), 1);
+        assert_eq!(CatError::from(io::Error::other("x")).exit_code(), 1);
+
+        assert!(CatError::collect(Vec::new()).is_ok());
+        let single = CatError::collect(vec![CatError::Usage("u".into())]).unwrap_err();
+        assert!(matches!(single, CatError::Usage(_)));
+        let multiple = CatError::collect(vec![CatError::Failed("a".into()), CatError::Usage("b".into())]).unwrap_err();
+        assert_eq!(multiple.exit_code(), 2);
+        assert_eq!(multiple.to_string(), "a; b");
+    }
+
+    #[test]
+    fn reports_each_error_on_its_own_line() {
+        let error = CatError::Multiple(vec![CatError::Failed("a: 不存在".into()), CatError::NotFound("b".into())]);
+        let mut io = streams(b"");
+        with_style(Some(When::Never), Some(When::Never), || {
+            error.report(&mut io);
+            error.report_json(&mut io);
+        });
+        assert_eq!(
+            output(io).1,
+            "a: 不存在\nb\n{\"error\":\"a: 不存在\",\"exit_code\":1}\n{\"error\":\"b\",\"exit_code\":127}\n"
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use crate::glob::{self, PatternChar};
+use crate::lexer::{self, find_dollar_end, find_quote_end};
+use crate::shell::Shell;
+
+/// 展开一个原始单词：展开大括号，去掉引号和转义，替换变量和命令替换，最后匹配通配符。
+/// 未加引号的展开结果会按空白拆分成多个参数，因此返回值可能有零个或多个元素。
+/// 通配符没有匹配到任何文件时返回错误
+pub fn expand_word(raw: &str, shell: &mut Shell) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+
+    for alternative in glob::expand_braces(raw) {
+        for field in expand_fields(&alternative, shell, true) {
+            if glob::is_pattern(&field) {
+                words.extend(glob::glob(&field)?);
+            } else {
+                words.push(glob::pattern_to_string(&field));
+            }
+        }
+    }
+    Ok(words)
+}
+
+/// 展开变量赋值的值，不拆分、不展开大括号也不匹配通配符。`"$@"` 展开成的多个参数用空格连接
+pub fn expand_assignment(raw: &str, shell: &mut Shell) -> String {
+    expand_fields(raw, shell, false)
+        .iter()
+        .map(|field| glob::pattern_to_string(field))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 去掉引号和转义并替换 `$` 表达式，每个字符都记录它是否被引用过
+fn expand_fields(raw: &str, shell: &mut Shell, split: bool) -> Vec<Vec<PatternChar>> {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut fields = Vec::new();
+    let mut current = Vec::new();
+    // 引号中的空字符串也算一个参数，例如 `cat ""`
+    let mut has_field = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                // 反斜杠后面不是特殊字符时保留反斜杠，例如Windows路径
+                match chars.get(i + 1) {
+                    Some(&next) if lexer::is_special(next) => {
+                        current.push(PatternChar { c: next, literal: true });
+                        i += 1;
+                    },
+                    _ => current.push(PatternChar { c: '\\', literal: true }),
+                }
+                has_field = true;
+            },
+            '\'' => {
+                let end = find_quote_end(&chars, i).unwrap_or(chars.len());
+                current.extend(chars[i + 1..end].iter().map(|&c| PatternChar { c, literal: true }));
+                i = end;
+                has_field = true;
+            },
+            '"' => {
+                let end = find_quote_end(&chars, i).unwrap_or(chars.len());
+                // `"$@"` 可能展开成多个参数，第一个接在前面的内容后面，其余的各自开始一个新参数
+                for (index, text) in expand_double_quoted(&chars[i + 1..end], shell).into_iter().enumerate() {
+                    if index > 0 {
+                        fields.push(std::mem::take(&mut current));
+                    }
+                    current.extend(text.chars().map(|c| PatternChar { c, literal: true }));
+                    has_field = true;
+                }
+                i = end;
+            },
+            '$' => {
+                let (value, end) = expand_dollar(&chars, i, shell);
+                // 未加引号的展开结果按空白拆分，其中的通配符仍然有效
+                for c in value.chars() {
+                    if split && c.is_whitespace() {
+                        if has_field {
+                            fields.push(std::mem::take(&mut current));
+                            has_field = false;
+                        }
+                    } else {
+                        current.push(PatternChar { c, literal: false });
+                        has_field = true;
+                    }
+                }
+                i = end;
+            },
+            c => {
+                current.push(PatternChar { c, literal: false });
+                has_field = true;
+            }
+        }
+        i += 1;
+    }
+
+    if has_field {
+        fields.push(current);
+    }
+    fields
+}
+
+/// 展开双引号内的内容，只有 `$`、`` ` ``、`"`、`\` 可以被反斜杠转义。
+/// 通常返回一个参数，`"$@"` 中每个位置参数展开成一个参数
+fn expand_double_quoted(chars: &[char], shell: &mut Shell) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    // 空的引号 `""` 也算一个参数，只有没有位置参数时的 `"$@"` 不产生参数
+    let mut keep = chars.is_empty();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                match chars.get(i + 1) {
+                    Some(&next) if matches!(next, '$' | '`' | '"' | '\\') => {
+                        current.push(next);
+                        i += 1;
+                    },
+                    _ => current.push('\\'),
+                }
+                keep = true;
+            },
+            '$' if chars.get(i + 1) == Some(&'@') => {
+                for (index, arg) in shell.args().iter().enumerate() {
+                    if index > 0 {
+                        fields.push(std::mem::take(&mut current));
+                    }
+                    current.push_str(arg);
+                    keep = true;
+                }
+                i += 1;
+            },
+            '$' => {
+                let (value, end) = expand_dollar(chars, i, shell);
+                current.push_str(&value);
+                i = end;
+                keep = true;
+            },
+            c => {
+                current.push(c);
+                keep = true;
+            },
+        }
+        i += 1;
+    }
+    if keep {
+        fields.push(current);
+    }
+    fields
+}
+
+/// 展开 `start` 处的 `$` 表达式，返回展开后的值和表达式最后一个字符的位置
+fn expand_dollar(chars: &[char], start: usize, shell: &mut Shell) -> (String, usize) {
+    match chars.get(start + 1) {
+        Some('?') => (shell.last_status.to_string(), start + 1),
+        Some('#') => (shell.args().len().to_string(), start + 1),
+        Some('!') => (shell.last_background.map(|pid| pid.to_string()).unwrap_or_default(), start + 1),
+        // 位置参数用空格连接，双引号中的 "$@" 由 `expand_double_quoted` 展开成多个参数
+        Some('@') | Some('*') => (shell.args().join(" "), start + 1),
+        Some(c) if c.is_ascii_digit() => (shell.get_var(&c.to_string()).unwrap_or_default(), start + 1),
+        Some('(') => match find_dollar_end(chars, start) {
+            Some(end) => {
+                let source: String = chars[start + 2..end].iter().collect();
+                (shell.capture(&source), end)
+            },
+            None => ("$".to_string(), start),
+        },
+        Some('{') => match find_dollar_end(chars, start) {
+            Some(end) => {
+                let name: String = chars[start + 2..end].iter().collect();
+                (shell.get_var(&name).unwrap_or_default(), end)
+            },
+            None => ("$".to_string(), start),
+        },
+        Some(&c) if c.is_ascii_alphabetic() || c == '_' => {
+            let mut end = start + 1;
+            while matches!(chars.get(end + 1), Some(&c) if c.is_ascii_alphanumeric() || c == '_') {
+                end += 1;
+            }
+            let name: String = chars[start + 1..=end].iter().collect();
+            (shell.get_var(&name).unwrap_or_default(), end)
+        },
+        // 其他情况下 `$` 就是普通字符
+        _ => ("$".to_string(), start),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn shell(args: &[&str]) -> Shell {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Shell::new("test", &args)
+    }
+
+    fn expand(raw: &str, shell: &mut Shell) -> Vec<String> {
+        expand_word(raw, shell).unwrap()
+    }
+
+    #[test]
+    fn quoted_at_expands_to_one_field_per_parameter() {
+        let mut shell = shell(&["a b", "c"]);
+        assert_eq!(expand("\"$@\"", &mut shell), ["a b", "c"]);
+        assert_eq!(expand("x\"$@\"y", &mut shell), ["xa b", "cy"]);
+        assert_eq!(expand("\"$*\"", &mut shell), ["a b c"]);
+        assert_eq!(expand("$@", &mut shell), ["a", "b", "c"]);
+        assert_eq!(expand_assignment("\"$@\"", &mut shell), "a b c");
+    }
+
+    #[test]
+    fn quoted_at_without_parameters_is_no_field() {
+        let mut shell = shell(&[]);
+        assert!(expand("\"$@\"", &mut shell).is_empty());
+        assert_eq!(expand("\"\"", &mut shell), [""]);
+        assert_eq!(expand("\"x$@\"", &mut shell), ["x"]);
+        assert_eq!(expand("\"\"\"$@\"", &mut shell), [""]);
+    }
+
+    #[test]
+    fn quotes_and_escapes() {
+        let mut shell = shell(&[]);
+        shell.run_source("x='1  2'").unwrap();
+        assert_eq!(expand("'$x'", &mut shell), ["$x"]);
+        assert_eq!(expand("\"$x\"", &mut shell), ["1  2"]);
+        assert_eq!(expand("$x", &mut shell), ["1", "2"]);
+        assert_eq!(expand("a\\ b", &mut shell), ["a b"]);
+        assert_eq!(expand(r"C:\Users\a.txt", &mut shell), [r"C:\Users\a.txt"]);
+        assert_eq!(expand(r"a\\b\*", &mut shell), [r"a\b*"]);
+        assert_eq!(expand("\"a\\$x\\n\"", &mut shell), ["a$x\\n"]);
+        assert_eq!(expand("${x}3", &mut shell), ["1", "23"]);
+        assert_eq!(expand("$undefined", &mut shell), Vec::<String>::new());
+        assert_eq!(expand("{a,b}c", &mut shell), ["ac", "bc"]);
+        assert_eq!(expand("\"$(echo hi  there)\"", &mut shell), ["hi there"]);
+    }
+
+    #[test]
+    fn globs_only_unquoted_patterns() {
+        let dir = TempDir::new();
+        dir.file("a.txt", "");
+        dir.file("b.txt", "");
+        dir.file("c.rs", "");
+        let base = dir.path().display().to_string();
+        let mut shell = shell(&[]);
+
+        assert_eq!(expand(&format!("{}/*.txt", base), &mut shell), [format!("{}/a.txt", base), format!("{}/b.txt", base)]);
+        assert_eq!(expand(&format!("\"{}/*.txt\"", base), &mut shell), [format!("{}/*.txt", base)]);
+        assert_eq!(expand(&format!("{}/\\*.txt", base), &mut shell), [format!("{}/*.txt", base)]);
+        assert!(expand_word(&format!("{}/*.md", base), &mut shell).is_err());
+
+        // 变量展开的结果中的通配符仍然有效
+        shell.run_source(&format!("pattern='{}/?.rs'", base)).unwrap();
+        assert_eq!(expand("$pattern", &mut shell), [format!("{}/c.rs", base)]);
+    }
+}
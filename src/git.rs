@@ -0,0 +1,838 @@
+//! 工作树中文件的git状态，`ls -l` 和 `tree` 显示。直接读取 `.git` 目录中的索引、引用和对象（松散对象和打包文件），
+//! 不需要安装git。状态标记和 `git status --short` 的两列一致
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::gitignore::IgnoreStack;
+use crate::inflate;
+use crate::prompt;
+use crate::sha1;
+
+/// 对象ID，即SHA-1摘要
+type ObjectId = [u8; 20];
+
+const MODE_TYPE_MASK: u32 = 0o170000;
+const MODE_TREE: u32 = 0o040000;
+const MODE_EXECUTABLE: u32 = 0o100755;
+const MODE_SYMLINK: u32 = 0o120000;
+/// 子模块在索引和树中的类型
+const MODE_GITLINK: u32 = 0o160000;
+
+const KIND_COMMIT: u8 = 1;
+const KIND_TREE: u8 = 2;
+const KIND_BLOB: u8 = 3;
+const KIND_TAG: u8 = 4;
+const KIND_OFS_DELTA: u8 = 6;
+const KIND_REF_DELTA: u8 = 7;
+
+/// 一个文件或目录的状态：`index` 是暂存区相对HEAD的变化，`worktree` 是工作树相对暂存区的变化。
+/// 取值和 `git status --short` 相同：`M` 修改、`A` 新增、`D` 删除、`T` 类型改变、`U` 冲突，
+/// 两列都是 `?` 表示未跟踪，都是 `!` 表示被忽略，都是空格表示没有变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub index: char,
+    pub worktree: char,
+}
+
+impl Status {
+    pub const CLEAN: Status = Status { index: ' ', worktree: ' ' };
+    pub const UNTRACKED: Status = Status { index: '?', worktree: '?' };
+    pub const IGNORED: Status = Status { index: '!', worktree: '!' };
+    const CONFLICT: Status = Status { index: 'U', worktree: 'U' };
+
+    /// 合并目录中两个条目的状态，每一列相同时保留，不同时显示 `M`
+    fn merge(self, other: Status) -> Status {
+        let column = |a: char, b: char| match (a, b) {
+            (' ', c) | (c, ' ') => c,
+            (a, b) if a == b => a,
+            _ => 'M',
+        };
+        Status { index: column(self.index, other.index), worktree: column(self.worktree, other.worktree) }
+    }
+}
+
+/// 一次 `ls` 或 `tree` 中用到的仓库。按目录找到所在的仓库，每个仓库只读取一次
+#[derive(Default)]
+pub struct Statuses {
+    repositories: Vec<Repository>,
+    /// 目录的绝对路径和它所在的仓库在 `repositories` 中的位置，不在仓库中时为 `None`
+    dirs: HashMap<PathBuf, Option<usize>>,
+}
+
+impl Statuses {
+    /// `path` 的状态，不在git工作树中（或者在 `.git` 目录中）时返回 `None`。
+    /// 目录的状态由其中所有文件的状态合并而成
+    pub fn status(&mut self, path: &Path, is_dir: bool) -> Option<Status> {
+        let path = absolute(path)?;
+        // 按所在的目录查找仓库，这样嵌套的仓库在外层仓库中显示为未跟踪的目录。工作树的根目录本身使用它自己的仓库
+        let index = self.repository(path.parent()?).or_else(|| is_dir.then(|| self.repository(&path)).flatten())?;
+        self.repositories[index].status(&path, is_dir)
+    }
+
+    /// 包含目录 `dir` 的仓库在 `repositories` 中的位置
+    fn repository(&mut self, dir: &Path) -> Option<usize> {
+        if let Some(index) = self.dirs.get(dir) {
+            return *index;
+        }
+        let index = self.find_repository(dir);
+        self.dirs.insert(dir.to_path_buf(), index);
+        index
+    }
+
+    fn find_repository(&mut self, dir: &Path) -> Option<usize> {
+        let work_tree = dir.ancestors().find(|ancestor| ancestor.join(".git").exists())?;
+        if let Some(index) = self.repositories.iter().position(|repository| repository.work_tree == work_tree) {
+            return Some(index);
+        }
+        self.repositories.push(Repository::open(work_tree)?);
+        Some(self.repositories.len() - 1)
+    }
+}
+
+/// 规范化的绝对路径。不跟随最后一个组件，这样符号链接本身的状态不会变成它指向的文件的状态
+fn absolute(path: &Path) -> Option<PathBuf> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            Some(fs::canonicalize(parent).ok()?.join(name))
+        },
+        _ => fs::canonicalize(path).ok(),
+    }
+}
+
+/// 索引中的一项
+struct IndexEntry {
+    mode: u32,
+    id: ObjectId,
+    /// 加入索引时文件的大小（只保留低32位）和修改时间（秒、纳秒）
+    size: u32,
+    mtime: (u32, u32),
+    /// 合并冲突：索引中有这个路径的多个版本
+    conflict: bool,
+    /// `git add -N` 加入的文件，内容还没有暂存
+    intent_to_add: bool,
+    /// 稀疏检出时不在工作树中的文件
+    skip_worktree: bool,
+}
+
+/// HEAD提交的树中的一个文件
+struct TreeEntry {
+    mode: u32,
+    id: ObjectId,
+}
+
+/// 一个打包文件和它的索引（`.idx`，第2版）
+struct Pack {
+    idx: Vec<u8>,
+    data: PathBuf,
+}
+
+struct Repository {
+    work_tree: PathBuf,
+    objects: PathBuf,
+    packs: Vec<Pack>,
+    index: BTreeMap<String, IndexEntry>,
+    /// 索引文件的修改时间。不早于它修改的文件可能在写入索引的同一时刻又被修改过，需要比较内容
+    index_mtime: Option<(u32, u32)>,
+    head: BTreeMap<String, TreeEntry>,
+    /// 已经计算过的跟踪文件的状态
+    tracked: HashMap<String, Option<Status>>,
+    /// 最近查询的目录：目录本身是否被忽略，以及其中的条目适用的 `.gitignore` 规则
+    ignore: Option<(PathBuf, bool, IgnoreStack)>,
+}
+
+impl Repository {
+    /// 打开工作树 `work_tree`（规范化的绝对路径）所属的仓库，读取索引和HEAD提交的树
+    fn open(work_tree: &Path) -> Option<Repository> {
+        let git_dir = prompt::find_git_dir(work_tree)?;
+        // `git worktree` 创建的工作树共用主仓库的对象和引用
+        let common_dir = match fs::read_to_string(git_dir.join("commondir")) {
+            Ok(path) => git_dir.join(path.trim()),
+            Err(_) => git_dir.clone(),
+        };
+        let objects = common_dir.join("objects");
+        let packs = fs::read_dir(objects.join("pack"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "idx"))
+            .filter_map(|path| Pack::load(&path))
+            .collect();
+
+        let index_path = git_dir.join("index");
+        let (index, index_mtime) = match fs::metadata(&index_path) {
+            Ok(metadata) => (read_index(&fs::read(&index_path).ok()?)?, Some(mtime(&metadata))),
+            // 还没有暂存过任何文件
+            Err(_) => (BTreeMap::new(), None),
+        };
+
+        let mut repository = Repository {
+            work_tree: work_tree.to_path_buf(),
+            objects,
+            packs,
+            index,
+            index_mtime,
+            head: BTreeMap::new(),
+            tracked: HashMap::new(),
+            ignore: None,
+        };
+        // 还没有提交时HEAD指向不存在的分支，树为空；提交存在但读不出来时无法判断状态
+        if let Some(commit) = resolve_head(&git_dir, &common_dir) {
+            let tree = repository.commit_tree(&commit)?;
+            let mut head = BTreeMap::new();
+            repository.read_tree(&tree, "", &mut head)?;
+            repository.head = head;
+        }
+        Some(repository)
+    }
+
+    fn status(&mut self, path: &Path, is_dir: bool) -> Option<Status> {
+        let relative = path.strip_prefix(&self.work_tree).ok()?;
+        let mut components = Vec::new();
+        for component in relative.components() {
+            let name = component.as_os_str().to_string_lossy();
+            if name == ".git" {
+                return None;
+            }
+            components.push(name);
+        }
+        let relative = components.join("/");
+
+        if relative.is_empty() {
+            // 工作树的根目录
+            let mut ignore = IgnoreStack::new(&self.work_tree);
+            return Some(self.dir_status(path, "", false, &mut ignore));
+        }
+
+        let (dir_ignored, mut ignore) = self.enter(path.parent()?);
+        let status = if !is_dir {
+            match self.tracked_status(&relative) {
+                Some(status) => status,
+                None if dir_ignored || ignore.is_ignored(path, false) => Status::IGNORED,
+                None => Status::UNTRACKED,
+            }
+        } else if self.index.get(&relative).is_some_and(|entry| entry.mode == MODE_GITLINK) {
+            // 子模块只比较记录的提交
+            self.tracked_status(&relative).unwrap_or(Status::CLEAN)
+        } else {
+            let ignored = dir_ignored || ignore.is_ignored(path, true);
+            self.dir_status(path, &format!("{}/", relative), ignored, &mut ignore)
+        };
+        self.ignore = Some((path.parent()?.to_path_buf(), dir_ignored, ignore));
+        Some(status)
+    }
+
+    /// 准备查询目录 `dir` 中的条目：返回 `dir` 或者它的上级目录是否被忽略，以及 `dir` 中的条目适用的规则
+    fn enter(&mut self, dir: &Path) -> (bool, IgnoreStack) {
+        if let Some((cached, ignored, ignore)) = self.ignore.take() {
+            if cached == dir {
+                return (ignored, ignore);
+            }
+        }
+        let mut ignore = IgnoreStack::new(&self.work_tree);
+        ignore.push(&self.work_tree);
+        let mut ignored = false;
+        let mut current = self.work_tree.clone();
+        for component in dir.strip_prefix(&self.work_tree).into_iter().flat_map(Path::components) {
+            current.push(component);
+            ignored = ignored || ignore.is_ignored(&current, true);
+            ignore.push(&current);
+        }
+        (ignored, ignore)
+    }
+
+    /// 目录的状态：其中跟踪的文件的状态合并在一起，还有未跟踪的文件时工作树一列为 `?`。
+    /// 只包含被忽略的文件的目录和git一样显示为被忽略。`prefix` 是目录的相对路径加上 `/`，
+    /// `ignore` 是目录本身适用的规则
+    fn dir_status(&mut self, path: &Path, prefix: &str, ignored: bool, ignore: &mut IgnoreStack) -> Status {
+        let mut paths: BTreeSet<String> = BTreeSet::new();
+        paths.extend(self.index.range(prefix.to_string()..).take_while(|(key, _)| key.starts_with(prefix)).map(|(key, _)| key.clone()));
+        paths.extend(self.head.range(prefix.to_string()..).take_while(|(key, _)| key.starts_with(prefix)).map(|(key, _)| key.clone()));
+        if paths.is_empty() && ignored {
+            return Status::IGNORED;
+        }
+
+        let changed = paths
+            .iter()
+            .filter_map(|path| self.tracked_status(path))
+            .filter(|status| *status != Status::CLEAN)
+            .reduce(Status::merge);
+        let untracked = !ignored && self.has_untracked(path, prefix, ignore);
+        match changed {
+            Some(status) if untracked && status.worktree == ' ' => Status { worktree: '?', ..status },
+            Some(status) => status,
+            None if untracked => Status::UNTRACKED,
+            None => Status::CLEAN,
+        }
+    }
+
+    /// 目录 `dir` 中是否有没有跟踪也没有被忽略的文件
+    fn has_untracked(&self, dir: &Path, prefix: &str, ignore: &mut IgnoreStack) -> bool {
+        ignore.push(dir);
+        let mut found = false;
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name == ".git" {
+                continue;
+            }
+            let path = entry.path();
+            let relative = format!("{}{}", prefix, name);
+            let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+            if ignore.is_ignored(&path, is_dir) {
+                continue;
+            }
+            found = if is_dir {
+                !self.index.contains_key(&relative) && self.has_untracked(&path, &format!("{}/", relative), ignore)
+            } else {
+                !self.index.contains_key(&relative)
+            };
+            if found {
+                break;
+            }
+        }
+        ignore.pop();
+        found
+    }
+
+    /// 在索引或者HEAD中的文件的状态，都不在时返回 `None`
+    fn tracked_status(&mut self, path: &str) -> Option<Status> {
+        if let Some(status) = self.tracked.get(path) {
+            return *status;
+        }
+        let status = match (self.head.get(path), self.index.get(path)) {
+            (None, None) => None,
+            (_, Some(entry)) if entry.conflict => Some(Status::CONFLICT),
+            // 从索引中删除了，工作树中的文件还在时也是未跟踪的文件
+            (Some(_), None) => {
+                let exists = fs::symlink_metadata(self.work_tree.join(path)).is_ok();
+                Some(Status { index: 'D', worktree: if exists { '?' } else { ' ' } })
+            },
+            (head, Some(entry)) => {
+                let index = match head {
+                    _ if entry.intent_to_add => ' ',
+                    None => 'A',
+                    Some(head) if head.mode & MODE_TYPE_MASK != entry.mode & MODE_TYPE_MASK => 'T',
+                    Some(head) if head.id != entry.id || head.mode != entry.mode => 'M',
+                    Some(_) => ' ',
+                };
+                Some(Status { index, worktree: self.worktree_status(path, entry) })
+            },
+        };
+        self.tracked.insert(path.to_string(), status);
+        status
+    }
+
+    /// 工作树中的文件相对索引的变化。大小和修改时间都和索引中记录的一样时认为没有修改，否则比较内容的哈希值
+    fn worktree_status(&self, path: &str, entry: &IndexEntry) -> char {
+        if entry.mode == MODE_GITLINK || entry.skip_worktree {
+            return ' ';
+        }
+        let full_path = self.work_tree.join(path);
+        let Ok(metadata) = fs::symlink_metadata(&full_path) else {
+            return 'D';
+        };
+        if entry.intent_to_add {
+            return 'A';
+        }
+        if metadata.is_dir() {
+            return 'D';
+        }
+        let is_symlink = metadata.file_type().is_symlink();
+        // Windows上没有启用符号链接时，链接以普通文件的形式检出，只比较内容
+        if cfg!(unix) && is_symlink != (entry.mode & MODE_TYPE_MASK == MODE_SYMLINK) {
+            return 'T';
+        }
+        if executable_changed(&metadata, entry) {
+            return 'M';
+        }
+
+        let mtime = mtime(&metadata);
+        let stat_matches = metadata.len() as u32 == entry.size
+            && mtime.0 == entry.mtime.0
+            && (entry.mtime.1 == 0 || mtime.1 == entry.mtime.1);
+        let racy = self.index_mtime.is_none_or(|index_mtime| entry.mtime >= index_mtime);
+        if stat_matches && !racy {
+            return ' ';
+        }
+
+        let content = if is_symlink {
+            match fs::read_link(&full_path) {
+                Ok(target) => target.to_string_lossy().replace('\\', "/").into_bytes(),
+                Err(_) => return 'M',
+            }
+        } else {
+            match fs::read(&full_path) {
+                Ok(content) => content,
+                Err(_) => return 'M',
+            }
+        };
+        if blob_id(&content) == entry.id {
+            return ' ';
+        }
+        // 设置了 `core.autocrlf` 时，检出的文件的换行符是CRLF，暂存的内容是LF
+        if content.windows(2).any(|pair| pair == b"\r\n") {
+            let normalized: Vec<u8> = content
+                .iter()
+                .enumerate()
+                .filter(|&(i, &byte)| !(byte == b'\r' && content.get(i + 1) == Some(&b'\n')))
+                .map(|(_, &byte)| byte)
+                .collect();
+            if blob_id(&normalized) == entry.id {
+                return ' ';
+            }
+        }
+        'M'
+    }
+
+    /// 提交对象中的树
+    fn commit_tree(&self, id: &ObjectId) -> Option<ObjectId> {
+        let (kind, data) = self.read_object(id)?;
+        if kind != KIND_COMMIT {
+            return None;
+        }
+        let line = data.split(|&byte| byte == b'\n').next()?;
+        parse_hex(std::str::from_utf8(line.strip_prefix(b"tree ")?).ok()?)
+    }
+
+    /// 把树对象中的所有文件展开到 `entries`，键是以 `/` 分隔的相对路径
+    fn read_tree(&self, id: &ObjectId, prefix: &str, entries: &mut BTreeMap<String, TreeEntry>) -> Option<()> {
+        let (kind, data) = self.read_object(id)?;
+        if kind != KIND_TREE {
+            return None;
+        }
+        // 每一项是 "<八进制模式> <名称>\0<20字节的对象ID>"
+        let mut rest = &data[..];
+        while !rest.is_empty() {
+            let space = rest.iter().position(|&byte| byte == b' ')?;
+            let nul = rest.iter().position(|&byte| byte == 0)?;
+            let mode = u32::from_str_radix(std::str::from_utf8(&rest[..space]).ok()?, 8).ok()?;
+            let path = format!("{}{}", prefix, String::from_utf8_lossy(rest.get(space + 1..nul)?));
+            let id: ObjectId = rest.get(nul + 1..nul + 21)?.try_into().ok()?;
+            rest = &rest[nul + 21..];
+            if mode == MODE_TREE {
+                self.read_tree(&id, &format!("{}/", path), entries)?;
+            } else {
+                entries.insert(path, TreeEntry { mode, id });
+            }
+        }
+        Some(())
+    }
+
+    /// 读取对象，返回类型和内容
+    fn read_object(&self, id: &ObjectId) -> Option<(u8, Vec<u8>)> {
+        let hex = to_hex(id);
+        if let Ok(compressed) = fs::read(self.objects.join(&hex[..2]).join(&hex[2..])) {
+            // 松散对象的内容前面是 "<类型> <大小>\0"
+            let data = inflate::inflate_zlib(&compressed)?;
+            let nul = data.iter().position(|&byte| byte == 0)?;
+            let kind = match data.split(|&byte| byte == b' ').next()? {
+                b"commit" => KIND_COMMIT,
+                b"tree" => KIND_TREE,
+                b"blob" => KIND_BLOB,
+                b"tag" => KIND_TAG,
+                _ => return None,
+            };
+            return Some((kind, data[nul + 1..].to_vec()));
+        }
+        self.packs.iter().find_map(|pack| pack.find(id).and_then(|offset| self.read_packed(pack, offset)))
+    }
+
+    /// 读取打包文件中位于 `offset` 的对象，差异对象会应用到它的基础对象上
+    fn read_packed(&self, pack: &Pack, offset: u64) -> Option<(u8, Vec<u8>)> {
+        let mut file = File::open(&pack.data).ok()?;
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        let mut header = Vec::new();
+        file.by_ref().take(32).read_to_end(&mut header).ok()?;
+
+        // 第一个字节的4-6位是类型，大小按每字节7位、低位在前的方式编码
+        let mut pos = 0;
+        let mut byte = *header.first()?;
+        let kind = (byte >> 4) & 0x07;
+        let mut size = u64::from(byte & 0x0f);
+        let mut shift = 4;
+        while byte & 0x80 != 0 {
+            pos += 1;
+            byte = *header.get(pos)?;
+            size |= u64::from(byte & 0x7f).checked_shl(shift)?;
+            shift += 7;
+        }
+        pos += 1;
+
+        let base = match kind {
+            KIND_OFS_DELTA => {
+                // 基础对象在同一个打包文件中，位置是相对当前对象的偏移
+                let mut byte = *header.get(pos)?;
+                let mut distance = u64::from(byte & 0x7f);
+                while byte & 0x80 != 0 {
+                    pos += 1;
+                    byte = *header.get(pos)?;
+                    distance = ((distance + 1) << 7) | u64::from(byte & 0x7f);
+                }
+                pos += 1;
+                Some(self.read_packed(pack, offset.checked_sub(distance)?)?)
+            },
+            KIND_REF_DELTA => {
+                let id: ObjectId = header.get(pos..pos + 20)?.try_into().ok()?;
+                pos += 20;
+                Some(self.read_object(&id)?)
+            },
+            _ => None,
+        };
+
+        // 压缩后的长度没有记录，deflate最坏情况下每个不压缩的块增加5个字节
+        file.seek(SeekFrom::Start(offset + pos as u64)).ok()?;
+        let mut compressed = Vec::new();
+        file.take(size + size / 1000 + 64).read_to_end(&mut compressed).ok()?;
+        let data = inflate::inflate_zlib(&compressed)?;
+        if data.len() as u64 != size {
+            return None;
+        }
+        match base {
+            Some((base_kind, base_data)) => Some((base_kind, apply_delta(&base_data, &data)?)),
+            None => Some((kind, data)),
+        }
+    }
+}
+
+impl Pack {
+    fn load(idx_path: &Path) -> Option<Pack> {
+        let idx = fs::read(idx_path).ok()?;
+        if idx.get(..8)? != b"\xfftOc\0\0\0\x02" || idx.len() < 8 + 256 * 4 {
+            return None;
+        }
+        Some(Pack { idx, data: idx_path.with_extension("pack") })
+    }
+
+    /// 对象在打包文件中的位置。索引中的对象ID按顺序排列，`fanout[b]` 是第一个字节不大于 `b` 的对象数
+    fn find(&self, id: &ObjectId) -> Option<u64> {
+        let fanout = |byte: usize| read_u32(&self.idx, 8 + byte * 4).map(|count| count as usize);
+        let count = fanout(255)?;
+        let first = usize::from(id[0]);
+        let (mut low, mut high) = (if first == 0 { 0 } else { fanout(first - 1)? }, fanout(first)?);
+        let ids = 8 + 256 * 4;
+        while low < high {
+            let middle = (low + high) / 2;
+            match self.idx.get(ids + middle * 20..ids + middle * 20 + 20)?.cmp(&id[..]) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => {
+                    // 对象ID之后是CRC表和4字节的位置表，最高位为1时表示在8字节的大位置表中
+                    let offset = read_u32(&self.idx, ids + count * 24 + middle * 4)?;
+                    if offset & 0x8000_0000 == 0 {
+                        return Some(u64::from(offset));
+                    }
+                    let position = ids + count * 28 + (offset & 0x7fff_ffff) as usize * 8;
+                    return Some(u64::from_be_bytes(self.idx.get(position..position + 8)?.try_into().ok()?));
+                },
+            }
+        }
+        None
+    }
+}
+
+/// 解析索引文件（第2到4版）。冲突的路径有多个阶段不为0的项，合并成一个有冲突标记的项
+fn read_index(data: &[u8]) -> Option<BTreeMap<String, IndexEntry>> {
+    if data.get(..4)? != b"DIRC" {
+        return None;
+    }
+    let version = read_u32(data, 4)?;
+    if !(2..=4).contains(&version) {
+        return None;
+    }
+    let count = read_u32(data, 8)?;
+
+    let mut entries = BTreeMap::new();
+    let mut pos = 12;
+    let mut name: Vec<u8> = Vec::new();
+    for _ in 0..count {
+        // 依次是ctime、mtime、dev、ino、mode、uid、gid、size，每项4字节，然后是对象ID和2字节的标志
+        let field = |index: usize| read_u32(data, pos + index * 4);
+        let mtime = (field(2)?, field(3)?);
+        let mode = field(6)?;
+        let size = field(9)?;
+        let id: ObjectId = data.get(pos + 40..pos + 60)?.try_into().ok()?;
+        let flags = u16::from_be_bytes(data.get(pos + 60..pos + 62)?.try_into().ok()?);
+        let mut cursor = pos + 62;
+        let extended = if version >= 3 && flags & 0x4000 != 0 {
+            cursor += 2;
+            u16::from_be_bytes(data.get(cursor - 2..cursor)?.try_into().ok()?)
+        } else {
+            0
+        };
+
+        let end = cursor + data.get(cursor..)?.iter().position(|&byte| byte == 0)?;
+        if version == 4 {
+            // 第4版的路径去掉和上一项相同的前缀：先是要从上一个路径末尾删掉的字节数，然后是剩下的部分
+            let mut strip = 0usize;
+            let mut byte = *data.get(cursor)?;
+            strip |= usize::from(byte & 0x7f);
+            while byte & 0x80 != 0 {
+                cursor += 1;
+                byte = *data.get(cursor)?;
+                strip = ((strip + 1) << 7) | usize::from(byte & 0x7f);
+            }
+            cursor += 1;
+            let end = cursor + data.get(cursor..)?.iter().position(|&byte| byte == 0)?;
+            name.truncate(name.len().checked_sub(strip)?);
+            name.extend_from_slice(&data[cursor..end]);
+            pos = end + 1;
+        } else {
+            // 第2、3版的路径以NUL结尾，整项补齐到8字节的倍数
+            name = data[cursor..end].to_vec();
+            pos += (cursor - pos + name.len() + 8) & !7;
+        }
+
+        // 稀疏索引中代表整个目录的项
+        if mode & MODE_TYPE_MASK == MODE_TREE {
+            continue;
+        }
+        let stage = (flags >> 12) & 0x03;
+        entries.insert(String::from_utf8_lossy(&name).into_owned(), IndexEntry {
+            mode,
+            id,
+            size,
+            mtime,
+            conflict: stage != 0,
+            intent_to_add: extended & 0x2000 != 0,
+            skip_worktree: extended & 0x4000 != 0,
+        });
+    }
+    Some(entries)
+}
+
+/// HEAD指向的提交。HEAD可以是分支的符号引用，分支在 `refs` 目录下或者 `packed-refs` 中
+fn resolve_head(git_dir: &Path, common_dir: &Path) -> Option<ObjectId> {
+    let mut content = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    for _ in 0..5 {
+        let Some(name) = content.trim().strip_prefix("ref:").map(|name| name.trim().to_string()) else {
+            return parse_hex(content.trim());
+        };
+        content = match fs::read_to_string(git_dir.join(&name)).or_else(|_| fs::read_to_string(common_dir.join(&name))) {
+            Ok(content) => content,
+            Err(_) => {
+                let packed = fs::read_to_string(common_dir.join("packed-refs")).ok()?;
+                let line = packed.lines().find(|line| line.split_once(' ').is_some_and(|(_, ref_name)| ref_name == name))?;
+                line.split(' ').next()?.to_string()
+            },
+        };
+    }
+    None
+}
+
+/// 应用打包文件中的差异：先是基础对象和结果的大小，然后是从基础对象复制和插入新数据的指令
+fn apply_delta(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    let mut pos = 0;
+    let mut size = || {
+        let mut value = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = *delta.get(pos)?;
+            pos += 1;
+            value |= usize::from(byte & 0x7f).checked_shl(shift)?;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+    };
+    let (base_size, result_size) = (size()?, size()?);
+    if base_size != base.len() {
+        return None;
+    }
+
+    let mut result = Vec::with_capacity(result_size);
+    while let Some(&op) = delta.get(pos) {
+        pos += 1;
+        if op & 0x80 != 0 {
+            // 低4位表示有哪些位置字节，接下来3位表示有哪些长度字节
+            let (mut offset, mut len) = (0usize, 0usize);
+            for i in 0..7 {
+                if op & (1 << i) != 0 {
+                    let byte = usize::from(*delta.get(pos)?);
+                    pos += 1;
+                    if i < 4 {
+                        offset |= byte << (8 * i);
+                    } else {
+                        len |= byte << (8 * (i - 4));
+                    }
+                }
+            }
+            if len == 0 {
+                len = 0x10000;
+            }
+            result.extend_from_slice(base.get(offset..offset.checked_add(len)?)?);
+        } else if op != 0 {
+            let len = usize::from(op);
+            result.extend_from_slice(delta.get(pos..pos + len)?);
+            pos += len;
+        } else {
+            return None;
+        }
+    }
+    (result.len() == result_size).then_some(result)
+}
+
+/// 文件内容作为blob对象的ID
+fn blob_id(content: &[u8]) -> ObjectId {
+    let mut object = format!("blob {}\0", content.len()).into_bytes();
+    object.extend_from_slice(content);
+    sha1::sha1(&object)
+}
+
+/// 修改时间的秒数和纳秒数，和索引中记录的格式一样
+fn mtime(metadata: &Metadata) -> (u32, u32) {
+    match metadata.modified().ok().and_then(|time| time.duration_since(UNIX_EPOCH).ok()) {
+        Some(duration) => (duration.as_secs() as u32, duration.subsec_nanos()),
+        None => (0, 0),
+    }
+}
+
+/// 可执行权限和索引中记录的模式不一致。Windows上没有可执行权限，git也不比较
+#[cfg(unix)]
+fn executable_changed(metadata: &Metadata, entry: &IndexEntry) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.is_file()
+        && entry.mode & MODE_TYPE_MASK != MODE_SYMLINK
+        && (metadata.permissions().mode() & 0o100 != 0) != (entry.mode == MODE_EXECUTABLE)
+}
+
+#[cfg(not(unix))]
+fn executable_changed(_metadata: &Metadata, _entry: &IndexEntry) -> bool {
+    false
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+fn to_hex(id: &ObjectId) -> String {
+    id.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_hex(text: &str) -> Option<ObjectId> {
+    if text.len() != 40 || !text.is_ascii() {
+        return None;
+    }
+    let mut id = [0u8; 20];
+    for (i, byte) in id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    use crate::test_util::TempDir;
+
+    /// 在 `dir` 中执行git命令，没有安装git时返回 `false`
+    fn git(dir: &Path, args: &[&str]) -> bool {
+        let output = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com", "-c", "init.defaultBranch=main"])
+            .args(["-c", "core.autocrlf=false", "-c", "gc.auto=0"])
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("HOME", dir)
+            .output();
+        match output {
+            Ok(output) => {
+                assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+                true
+            },
+            Err(_) => false,
+        }
+    }
+
+    fn status(statuses: &mut Statuses, repo: &Path, path: &str) -> String {
+        let path = repo.join(path);
+        let status = statuses.status(&path, path.is_dir()).unwrap();
+        format!("{}{}", status.index, status.worktree)
+    }
+
+    #[test]
+    fn hashes_blobs() {
+        assert_eq!(to_hex(&blob_id(b"")), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+        assert_eq!(to_hex(&blob_id(b"hello\n")), "ce013625030ba8dba906f756967f9e9ca394464a");
+        assert_eq!(parse_hex("ce013625030ba8dba906f756967f9e9ca394464a"), Some(blob_id(b"hello\n")));
+        assert_eq!(parse_hex("ce01"), None);
+    }
+
+    #[test]
+    fn applies_deltas() {
+        let base = b"hello, world";
+        // 基础对象12字节，结果13字节：复制 `hello`，插入 ` there`，再复制 `, w`（跳过 `orld`）
+        let delta = [12, 13, 0x90, 5, 6, b' ', b't', b'h', b'e', b'r', b'e', 0x91, 5, 2];
+        assert_eq!(apply_delta(base, &delta).unwrap(), b"hello there, ");
+        assert_eq!(apply_delta(b"short", &delta), None);
+        assert_eq!(apply_delta(base, &[12, 5, 0x91, 10, 5]), None);
+    }
+
+    #[test]
+    fn merges_directory_statuses() {
+        let status = |text: &str| Status { index: text.chars().next().unwrap(), worktree: text.chars().nth(1).unwrap() };
+        assert_eq!(status("M ").merge(status(" M")), status("MM"));
+        assert_eq!(status("A ").merge(status("D ")), status("M "));
+        assert_eq!(status(" D").merge(status(" D")), status(" D"));
+    }
+
+    #[test]
+    fn reports_worktree_statuses() {
+        let dir = TempDir::new();
+        dir.file("repo/.gitignore", "*.log\nbuild/\n");
+        dir.file("repo/a.txt", "a\n");
+        dir.file("repo/b.txt", "b\n");
+        dir.file("repo/src/lib.rs", "fn main() {}\n");
+        dir.file("repo/docs/guide.md", "# guide\n");
+        let repo = dir.path().join("repo");
+        if !git(&repo, &["init", "-q"]) {
+            return;
+        }
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-qm", "init"]);
+
+        fs::write(repo.join("a.txt"), "changed\n").unwrap();
+        fs::remove_file(repo.join("b.txt")).unwrap();
+        dir.file("repo/src/new.rs", "");
+        git(&repo, &["add", "src/new.rs"]);
+        dir.file("repo/docs/draft.md", "");
+        dir.file("repo/debug.log", "");
+        dir.file("repo/build/out.o", "");
+
+        let expected = [
+            ("a.txt", " M"),
+            ("b.txt", " D"),
+            ("src/lib.rs", "  "),
+            ("src/new.rs", "A "),
+            ("src", "A "),
+            ("docs/draft.md", "??"),
+            ("docs", "??"),
+            ("debug.log", "!!"),
+            ("build", "!!"),
+            ("build/out.o", "!!"),
+            ("", "AM"),
+        ];
+        let mut statuses = Statuses::default();
+        for (path, status) in expected {
+            assert_eq!(self::status(&mut statuses, &repo, path), status, "{}", path);
+        }
+        assert_eq!(statuses.status(&repo.join(".git/HEAD"), false), None);
+        assert_eq!(statuses.status(dir.path(), true), None);
+
+        // 打包之后从打包文件中读取HEAD的树，结果不变
+        git(&repo, &["gc", "-q"]);
+        assert!(fs::read_dir(repo.join(".git/objects/pack")).unwrap().count() > 0);
+        let mut statuses = Statuses::default();
+        for (path, status) in expected {
+            assert_eq!(self::status(&mut statuses, &repo, path), status, "{}", path);
+        }
+    }
+}
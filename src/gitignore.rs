@@ -0,0 +1,215 @@
+//! `.gitignore` 规则的解析和匹配，`tree --gitignore` 使用。
+//! 支持注释、`!` 取反、`/` 开头或中间的锚定模式、`/` 结尾的目录模式和 `**`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::glob::{self, PatternChar};
+use crate::prompt;
+
+/// 一个 `.gitignore` 文件中的规则，模式相对于 `base` 目录
+struct Gitignore {
+    base: PathBuf,
+    rules: Vec<Rule>,
+}
+
+struct Rule {
+    /// 按 `/` 分开的模式组件
+    components: Vec<Vec<PatternChar>>,
+    /// 模式开头或中间有 `/` 时相对于 `base` 匹配整个路径，否则匹配任意一层的名称
+    anchored: bool,
+    /// 以 `/` 结尾的模式只匹配目录
+    dir_only: bool,
+    /// 以 `!` 开头的模式重新包含之前排除的文件
+    negated: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Rule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        let anchored = pattern.contains('/');
+        let components = pattern
+            .trim_start_matches('/')
+            .split('/')
+            .map(|component| {
+                // `\` 转义的字符不作为通配符，例如 `\#`、`\!` 和 `\*`
+                let mut chars = Vec::new();
+                let mut iter = component.chars();
+                while let Some(c) = iter.next() {
+                    match c {
+                        '\\' => chars.extend(iter.next().map(|c| PatternChar { c, literal: true })),
+                        c => chars.push(PatternChar { c, literal: false }),
+                    }
+                }
+                chars
+            })
+            .collect();
+        Some(Rule { components, anchored, dir_only, negated })
+    }
+
+    fn matches(&self, path: &[Vec<char>], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        match (self.anchored, path.last()) {
+            (true, _) => match_path(&self.components, path),
+            (false, Some(name)) => glob::match_component(&self.components[0], name),
+            (false, None) => false,
+        }
+    }
+}
+
+/// 按组件匹配相对路径，`**` 组件匹配零个或多个目录
+fn match_path(pattern: &[Vec<PatternChar>], path: &[Vec<char>]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if glob::is_globstar(first) => (0..=path.len()).any(|skip| match_path(rest, &path[skip..])),
+        Some((first, rest)) => path
+            .split_first()
+            .is_some_and(|(name, path_rest)| glob::match_component(first, name) && match_path(rest, path_rest)),
+    }
+}
+
+impl Gitignore {
+    fn load(file: &Path, base: &Path) -> Option<Gitignore> {
+        let text = fs::read_to_string(file).ok()?;
+        Some(Gitignore { base: base.to_path_buf(), rules: text.lines().filter_map(Rule::parse).collect() })
+    }
+
+    /// `Some(true)` 表示被排除，`Some(false)` 表示被 `!` 规则重新包含，`None` 表示没有匹配的规则。
+    /// 同一个文件中后面的规则优先
+    fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.base).ok()?;
+        let components: Vec<Vec<char>> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().chars().collect())
+            .collect();
+        self.rules.iter().rev().find(|rule| rule.matches(&components, is_dir)).map(|rule| !rule.negated)
+    }
+}
+
+/// 遍历目录时生效的 `.gitignore` 文件，从仓库根目录到当前目录，越深的文件优先。
+/// 使用绝对路径，遍历进入和离开目录时调用 [`push`](IgnoreStack::push) 和 [`pop`](IgnoreStack::pop)
+pub struct IgnoreStack {
+    files: Vec<Option<Gitignore>>,
+}
+
+impl IgnoreStack {
+    /// 开始遍历 `dir` 前的规则：`dir` 所在git仓库的 `.git/info/exclude`，以及仓库根目录到 `dir` 上一级的各个 `.gitignore`
+    pub fn new(dir: &Path) -> IgnoreStack {
+        let mut files = Vec::new();
+        let work_tree = dir.ancestors().find(|ancestor| ancestor.join(".git").exists());
+        if let Some(work_tree) = work_tree {
+            if let Some(git_dir) = prompt::find_git_dir(work_tree) {
+                files.push(Gitignore::load(&git_dir.join("info").join("exclude"), work_tree));
+            }
+            let mut ancestors: Vec<&Path> = dir.ancestors().skip(1).take_while(|ancestor| ancestor.starts_with(work_tree)).collect();
+            ancestors.reverse();
+            for ancestor in ancestors {
+                files.push(Gitignore::load(&ancestor.join(".gitignore"), ancestor));
+            }
+        }
+        IgnoreStack { files }
+    }
+
+    /// 进入目录 `dir`，读取其中的 `.gitignore`
+    pub fn push(&mut self, dir: &Path) {
+        self.files.push(Gitignore::load(&dir.join(".gitignore"), dir));
+    }
+
+    /// 离开最后进入的目录
+    pub fn pop(&mut self) {
+        self.files.pop();
+    }
+
+    /// `path` 是否被排除
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.files.iter().rev().flatten().find_map(|file| file.matched(path, is_dir)).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// 用一个 `.gitignore` 文件的内容判断相对路径是否被排除，`/` 结尾的路径是目录
+    fn ignored(rules: &str, path: &str) -> bool {
+        let gitignore = Gitignore { base: PathBuf::from("/repo"), rules: rules.lines().filter_map(Rule::parse).collect() };
+        let is_dir = path.ends_with('/');
+        gitignore.matched(&Path::new("/repo").join(path.trim_end_matches('/')), is_dir).unwrap_or(false)
+    }
+
+    #[test]
+    fn negation_reincludes_files() {
+        let rules = "*.log\n!keep.log\n";
+        assert!(ignored(rules, "debug.log"));
+        assert!(ignored(rules, "src/debug.log"));
+        assert!(!ignored(rules, "keep.log"));
+        assert!(!ignored(rules, "src/keep.log"));
+        // 后面的规则优先
+        assert!(ignored("!keep.log\n*.log", "keep.log"));
+        // `\!` 是普通的感叹号
+        assert!(ignored("\\!important", "!important"));
+        assert!(!ignored("\\!important", "important"));
+    }
+
+    #[test]
+    fn slashes_anchor_patterns() {
+        assert!(ignored("/build", "build/"));
+        assert!(!ignored("/build", "src/build/"));
+        assert!(ignored("build", "src/build/"));
+        assert!(ignored("doc/*.txt", "doc/a.txt"));
+        assert!(!ignored("doc/*.txt", "src/doc/a.txt"));
+        assert!(!ignored("doc/*.txt", "doc/sub/a.txt"));
+        assert!(ignored("/*.c", "main.c"));
+        assert!(!ignored("/*.c", "src/main.c"));
+    }
+
+    #[test]
+    fn matches_directories_and_globstars() {
+        assert!(ignored("out/", "out/"));
+        assert!(!ignored("out/", "out"));
+        assert!(ignored("out/", "src/out/"));
+        assert!(ignored("**/tmp", "tmp"));
+        assert!(ignored("**/tmp", "a/b/tmp/"));
+        assert!(ignored("a/**/b", "a/b"));
+        assert!(ignored("a/**/b", "a/x/y/b"));
+        assert!(!ignored("a/**/b", "x/a/b"));
+        assert!(ignored("logs/**", "logs/x/y.txt"));
+        assert!(ignored("\\#notes  ", "#notes"));
+        assert!(!ignored("# comment\n\n", "# comment"));
+    }
+
+    #[test]
+    fn deeper_files_take_precedence() {
+        let dir = TempDir::new();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        dir.file(".git/info/exclude", "*.bak\n");
+        dir.file(".gitignore", "*.txt\n");
+        dir.file("sub/.gitignore", "!keep.txt\n");
+        dir.file("sub/deep/file.txt", "");
+
+        // 从子目录开始遍历时也会读取上级目录和 `.git/info/exclude` 中的规则
+        let mut stack = IgnoreStack::new(&root.join("sub"));
+        stack.push(&root.join("sub"));
+        assert!(stack.is_ignored(&root.join("sub/a.txt"), false));
+        assert!(!stack.is_ignored(&root.join("sub/keep.txt"), false));
+        assert!(stack.is_ignored(&root.join("sub/x.bak"), false));
+        stack.pop();
+        assert!(stack.is_ignored(&root.join("keep.txt"), false));
+        assert!(!stack.is_ignored(&root.join("a.rs"), false));
+    }
+}
@@ -0,0 +1,350 @@
+use std::fs;
+use std::path::Path;
+
+use crate::lexer::{find_dollar_end, find_quote_end};
+
+/// 通配模式中的一个字符，`literal` 为真时表示它来自引号或转义，不作为通配符
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternChar {
+    pub c: char,
+    pub literal: bool,
+}
+
+/// 展开命令行模式下的路径参数：先展开大括号，再匹配通配符。
+/// 没有通配符的参数和正好是已有文件名的参数（例如 `a[1].txt`）原样返回，通配符没有匹配到任何文件时返回错误
+pub fn expand_path_arg(arg: &str) -> Result<Vec<String>, String> {
+    if fs::symlink_metadata(arg).is_ok() {
+        return Ok(vec![arg.to_string()]);
+    }
+    let mut paths = Vec::new();
+    for word in expand_braces(arg) {
+        let pattern: Vec<PatternChar> = word.chars().map(|c| PatternChar { c, literal: false }).collect();
+        if is_pattern(&pattern) {
+            paths.extend(glob(&pattern)?);
+        } else {
+            paths.push(word);
+        }
+    }
+    Ok(paths)
+}
+
+/// 展开原始单词中的大括号，例如 `file.{rs,txt}` 展开为 `file.rs file.txt`。
+/// 引号、转义和 `$(...)` 中的大括号不会被展开
+pub fn expand_braces(raw: &str) -> Vec<String> {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '\'' | '"' => match find_quote_end(&chars, i) {
+                Some(end) => i = end,
+                None => break,
+            },
+            '$' if matches!(chars.get(i + 1), Some('(') | Some('{')) => match find_dollar_end(&chars, i) {
+                Some(end) => i = end,
+                None => break,
+            },
+            '{' => {
+                if let Some((close, commas)) = find_brace_group(&chars, i) {
+                    let prefix: String = chars[..i].iter().collect();
+                    let suffix: String = chars[close + 1..].iter().collect();
+                    let mut words = Vec::new();
+                    let mut start = i + 1;
+
+                    for end in commas.into_iter().chain(std::iter::once(close)) {
+                        let alternative: String = chars[start..end].iter().collect();
+                        words.extend(expand_braces(&format!("{}{}{}", prefix, alternative, suffix)));
+                        start = end + 1;
+                    }
+                    return words;
+                }
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+
+    vec![raw.to_string()]
+}
+
+/// 从 `open` 处的 `{` 开始查找匹配的 `}`，同时记录顶层逗号的位置。
+/// 没有逗号的大括号（例如 `{}`）不做展开，返回 `None`
+fn find_brace_group(chars: &[char], open: usize) -> Option<(usize, Vec<usize>)> {
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut i = open;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '\'' | '"' => i = find_quote_end(chars, i)?,
+            '$' if matches!(chars.get(i + 1), Some('(') | Some('{')) => i = find_dollar_end(chars, i)?,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return if commas.is_empty() { None } else { Some((i, commas)) };
+                }
+            },
+            ',' if depth == 1 => commas.push(i),
+            _ => {},
+        }
+        i += 1;
+    }
+    None
+}
+
+/// 模式中是否包含需要匹配文件系统的通配符
+pub fn is_pattern(pattern: &[PatternChar]) -> bool {
+    pattern.iter().enumerate().any(|(i, pc)| {
+        !pc.literal
+            && match pc.c {
+                '*' | '?' => true,
+                '[' => match_class(&pattern[i..], '\0').is_some(),
+                _ => false,
+            }
+    })
+}
+
+/// 把模式还原成普通字符串
+pub fn pattern_to_string(pattern: &[PatternChar]) -> String {
+    pattern.iter().map(|pc| pc.c).collect()
+}
+
+/// 在文件系统中匹配通配模式，支持 `*`、`?`、`[a-z]` 和递归的 `**`。
+/// 结果按名称排序，没有任何匹配时返回错误
+pub fn glob(pattern: &[PatternChar]) -> Result<Vec<String>, String> {
+    let absolute = pattern.first().is_some_and(|pc| is_separator(pc.c));
+    let components: Vec<&[PatternChar]> = pattern
+        .split(|pc| is_separator(pc.c))
+        .filter(|component| !component.is_empty())
+        .collect();
+
+    let mut matches = vec![if absolute { "/".to_string() } else { String::new() }];
+
+    for (index, component) in components.iter().enumerate() {
+        let last = index + 1 == components.len();
+        let mut next = Vec::new();
+
+        for base in &matches {
+            if is_globstar(component) {
+                // `**` 匹配零个或多个目录，位于末尾时匹配所有文件和目录
+                if !last {
+                    next.push(base.clone());
+                }
+                walk(base, last, &mut next);
+            } else if !is_pattern(component) {
+                next.push(join(base, &pattern_to_string(component)));
+            } else if let Ok(entries) = fs::read_dir(dir_of(base)) {
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    // 隐藏文件只有在模式以 '.' 开头时才匹配
+                    if name.starts_with('.') && component[0].c != '.' {
+                        continue;
+                    }
+                    let name_chars: Vec<char> = name.chars().collect();
+                    if match_component(component, &name_chars) {
+                        next.push(join(base, &name));
+                    }
+                }
+            }
+        }
+        matches = next;
+    }
+
+    // 字面组件只是拼接路径，这里确认最终的路径确实存在
+    matches.retain(|path| !path.is_empty() && Path::new(path).symlink_metadata().is_ok());
+    matches.sort();
+    matches.dedup();
+
+    if matches.is_empty() {
+        Err(tr!("glob.no_match", pattern_to_string(pattern)))
+    } else {
+        Ok(matches)
+    }
+}
+
+/// 路径分隔符，Windows上同时支持 '/' 和 '\'
+fn is_separator(c: char) -> bool {
+    c == '/' || (cfg!(windows) && c == '\\')
+}
+
+/// 组件是否是匹配任意层目录的 `**`
+pub fn is_globstar(component: &[PatternChar]) -> bool {
+    matches!(component, [a, b] if is_special(a, '*') && is_special(b, '*'))
+}
+
+/// 未被引用的特殊字符
+fn is_special(pc: &PatternChar, c: char) -> bool {
+    !pc.literal && pc.c == c
+}
+
+fn join(base: &str, name: &str) -> String {
+    if base.is_empty() {
+        name.to_string()
+    } else if base.ends_with('/') {
+        format!("{}{}", base, name)
+    } else {
+        format!("{}/{}", base, name)
+    }
+}
+
+fn dir_of(base: &str) -> &str {
+    if base.is_empty() {
+        "."
+    } else {
+        base
+    }
+}
+
+/// 递归收集 `base` 下的所有子目录（`include_files` 为真时也包括文件），跳过隐藏项，不跟随符号链接
+fn walk(base: &str, include_files: bool, result: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir_of(base)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = join(base, &name);
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        if is_dir || include_files {
+            result.push(path.clone());
+        }
+        if is_dir {
+            walk(&path, include_files, result);
+        }
+    }
+}
+
+/// 用一个路径组件的模式匹配文件名
+pub fn match_component(pattern: &[PatternChar], name: &[char]) -> bool {
+    let Some(first) = pattern.first() else {
+        return name.is_empty();
+    };
+
+    if !first.literal {
+        match first.c {
+            '*' => return (0..=name.len()).any(|skip| match_component(&pattern[1..], &name[skip..])),
+            '?' => return !name.is_empty() && match_component(&pattern[1..], &name[1..]),
+            '[' => {
+                if let Some(&c) = name.first() {
+                    if let Some((length, matched)) = match_class(pattern, c) {
+                        return matched && match_component(&pattern[length..], &name[1..]);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    name.first() == Some(&first.c) && match_component(&pattern[1..], &name[1..])
+}
+
+/// 解析模式开头的字符类 `[...]`，返回字符类的长度以及 `c` 是否属于它。
+/// 支持 `[!...]`、`[^...]` 取反和 `a-z` 范围，不完整的字符类返回 `None`
+fn match_class(pattern: &[PatternChar], c: char) -> Option<(usize, bool)> {
+    let mut i = 1;
+    let negate = matches!(pattern.get(i), Some(pc) if is_special(pc, '!') || is_special(pc, '^'));
+    if negate {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    while let Some(pc) = pattern.get(i) {
+        // 紧跟在 '[' 后的 ']' 是普通字符
+        if is_special(pc, ']') && !first {
+            return Some((i + 1, matched != negate));
+        }
+        first = false;
+
+        match (pattern.get(i + 1), pattern.get(i + 2)) {
+            (Some(dash), Some(end)) if is_special(dash, '-') && !is_special(end, ']') => {
+                if pc.c <= c && c <= end.c {
+                    matched = true;
+                }
+                i += 3;
+            },
+            _ => {
+                if pc.c == c {
+                    matched = true;
+                }
+                i += 1;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn pattern(text: &str) -> Vec<PatternChar> {
+        text.chars().map(|c| PatternChar { c, literal: false }).collect()
+    }
+
+    fn matches(pattern_text: &str, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        match_component(&pattern(pattern_text), &name)
+    }
+
+    #[test]
+    fn expands_braces() {
+        assert_eq!(expand_braces("file.{rs,txt}"), ["file.rs", "file.txt"]);
+        assert_eq!(expand_braces("{a,b}{1,2}"), ["a1", "a2", "b1", "b2"]);
+        assert_eq!(expand_braces("x{a,{b,c}d}"), ["xa", "xbd", "xcd"]);
+        assert_eq!(expand_braces("{,s}"), ["", "s"]);
+        for raw in ["{}", "{a}", "'{a,b}'", "\"{a,b}\"", "\\{a,b}", "$(echo {a,b})", "{a,b"] {
+            assert_eq!(expand_braces(raw), [raw], "{}", raw);
+        }
+    }
+
+    #[test]
+    fn matches_components() {
+        assert!(matches("*.rs", "main.rs"));
+        assert!(matches("*", ""));
+        assert!(!matches("*.rs", "main.rsx"));
+        assert!(matches("a?c", "abc"));
+        assert!(!matches("a?c", "ac"));
+        assert!(matches("[a-c]x", "bx"));
+        assert!(!matches("[!a-c]x", "bx"));
+        assert!(matches("[^a-c]x", "dx"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[a-]", "-"));
+        // 不完整的字符类按普通字符匹配
+        assert!(matches("[ab", "[ab"));
+
+        let quoted = [PatternChar { c: '*', literal: true }];
+        assert!(match_component(&quoted, &['*']));
+        assert!(!match_component(&quoted, &['a']));
+        assert!(!is_pattern(&quoted));
+        assert!(!is_pattern(&pattern("[ab")));
+        assert!(is_pattern(&pattern("[ab]")));
+    }
+
+    #[test]
+    fn globs_the_file_system() {
+        let dir = TempDir::new();
+        for name in ["a.rs", "b.txt", ".hidden.rs", "src/c.rs", "src/deep/d.rs", "src/.git/e.rs"] {
+            dir.file(name, "");
+        }
+        let base = dir.path().display().to_string();
+        let glob_at = |text: &str| -> Result<Vec<String>, String> {
+            let found = glob(&pattern(&format!("{}/{}", base, text)))?;
+            Ok(found.iter().map(|path| path[base.len() + 1..].to_string()).collect())
+        };
+
+        assert_eq!(glob_at("*.rs").unwrap(), ["a.rs"]);
+        assert_eq!(glob_at(".*.rs").unwrap(), [".hidden.rs"]);
+        assert_eq!(glob_at("*/c.rs").unwrap(), ["src/c.rs"]);
+        assert_eq!(glob_at("**/*.rs").unwrap(), ["a.rs", "src/c.rs", "src/deep/d.rs"]);
+        assert_eq!(glob_at("src/**").unwrap(), ["src/c.rs", "src/deep", "src/deep/d.rs"]);
+        assert!(glob_at("*.md").is_err());
+        assert!(glob_at("missing/*").is_err());
+    }
+}
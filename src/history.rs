@@ -0,0 +1,218 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+
+/// 默认最多保留的历史记录条数，可以用环境变量 `HISTSIZE` 修改
+const DEFAULT_LIMIT: usize = 1000;
+
+/// 交互模式的命令历史，每一行输入是一条记录。
+/// 重复的命令只保留最近的一条，超过条数上限时丢弃最早的记录
+pub struct History {
+    entries: Vec<String>,
+    /// 历史记录文件，为 `None` 时只保存在内存中（例如执行脚本时）
+    path: Option<PathBuf>,
+    limit: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History { entries: Vec::new(), path: None, limit: DEFAULT_LIMIT }
+    }
+}
+
+impl History {
+    /// 从历史记录文件加载，文件位置可以用环境变量 `HISTFILE` 指定，默认在配置目录下
+    pub fn load() -> Self {
+        let path = config::var_os("HISTFILE")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| config::config_dir().map(|dir| dir.join("history")));
+        let limit = config::var("HISTSIZE").and_then(|value| value.parse().ok()).unwrap_or(DEFAULT_LIMIT);
+
+        let mut history = History { entries: Vec::new(), path, limit };
+        if let Some(content) = history.path.as_ref().and_then(|path| fs::read_to_string(path).ok()) {
+            for line in content.lines() {
+                history.push(line);
+            }
+        }
+        history
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// 记录一行输入并写入历史记录文件。空行和以空格开头的行不会记录
+    pub fn add(&mut self, line: &str) {
+        if line.trim().is_empty() || line.starts_with(' ') {
+            return;
+        }
+        self.push(line);
+        self.save();
+    }
+
+    /// 清空历史记录，包括历史记录文件
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.save();
+    }
+
+    fn push(&mut self, line: &str) {
+        self.entries.retain(|entry| entry != line);
+        self.entries.push(line.to_string());
+        if self.entries.len() > self.limit {
+            let excess = self.entries.len() - self.limit;
+            self.entries.drain(..excess);
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else { return };
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+
+        let mut content = self.entries.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        let _ = fs::write(path, content);
+    }
+
+    /// 在 `before` 之前的记录中从新到旧查找包含 `query` 的记录，返回它的位置
+    pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+        let before = before.min(self.entries.len());
+        self.entries[..before].iter().rposition(|entry| entry.contains(query))
+    }
+
+    /// 展开 `!!`（上一条命令）、`!n`（第n条命令）和 `!-n`（倒数第n条命令）。
+    /// 单引号内和反斜杠转义的 `!` 不展开。没有需要展开的内容时返回 `None`
+    pub fn expand(&self, line: &str) -> Result<Option<String>, String> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut result = String::new();
+        let mut expanded = false;
+        let mut in_single_quote = false;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            match c {
+                '\'' => in_single_quote = !in_single_quote,
+                '\\' if !in_single_quote => {
+                    result.push(c);
+                    if let Some(&next) = chars.get(i + 1) {
+                        result.push(next);
+                        i += 1;
+                    }
+                    i += 1;
+                    continue;
+                },
+                '!' if !in_single_quote => {
+                    if let Some((entry, end)) = self.event(&chars, i)? {
+                        result.push_str(entry);
+                        expanded = true;
+                        i = end;
+                        continue;
+                    }
+                },
+                _ => {},
+            }
+            result.push(c);
+            i += 1;
+        }
+
+        Ok(expanded.then_some(result))
+    }
+
+    /// 解析 `start` 处 `!` 开始的事件引用，返回对应的记录和引用结束的位置。
+    /// `!` 后面不是事件引用时（例如 `! -f` 或 `!=`）返回 `None`
+    fn event(&self, chars: &[char], start: usize) -> Result<Option<(&str, usize)>, String> {
+        let mut end = start + 1;
+        if chars.get(end) == Some(&'!') {
+            return match self.entries.last() {
+                Some(entry) => Ok(Some((entry, end + 1))),
+                None => Err(tr!("history.not_found", "!!")),
+            };
+        }
+
+        let negative = chars.get(end) == Some(&'-');
+        if negative {
+            end += 1;
+        }
+        let digits_start = end;
+        while chars.get(end).is_some_and(|c| c.is_ascii_digit()) {
+            end += 1;
+        }
+        if end == digits_start {
+            return Ok(None);
+        }
+
+        let text: String = chars[start..end].iter().collect();
+        let number: usize = chars[digits_start..end].iter().collect::<String>().parse().unwrap_or(0);
+        let index = if negative {
+            self.entries.len().checked_sub(number)
+        } else {
+            number.checked_sub(1)
+        };
+
+        match index.and_then(|index| self.entries.get(index)) {
+            Some(entry) => Ok(Some((entry, end))),
+            None => Err(tr!("history.not_found", text)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn history(lines: &[&str]) -> History {
+        let mut history = History::default();
+        for line in lines {
+            history.add(line);
+        }
+        history
+    }
+
+    #[test]
+    fn keeps_latest_unique_entries() {
+        let mut history = history(&["ls", "cd src", "", " secret", "ls"]);
+        assert_eq!(history.entries(), ["cd src", "ls"]);
+
+        history.limit = 2;
+        history.add("pwd");
+        assert_eq!(history.entries(), ["ls", "pwd"]);
+        assert_eq!(history.search("l", 2), Some(0));
+        assert_eq!(history.search("l", 0), None);
+        assert_eq!(history.search("x", 2), None);
+    }
+
+    #[test]
+    fn saves_to_file() {
+        let dir = TempDir::new();
+        let path = dir.path().join("nested/history");
+        let mut history = History { path: Some(path.clone()), ..History::default() };
+        history.add("echo a");
+        history.add("echo b");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "echo a\necho b\n");
+        history.clear();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+    }
+
+    #[test]
+    fn expands_events() {
+        let history = history(&["ls -l", "echo hi", "cat a"]);
+        assert_eq!(history.expand("sudo !!").unwrap().as_deref(), Some("sudo cat a"));
+        assert_eq!(history.expand("!1 | !-2").unwrap().as_deref(), Some("ls -l | echo hi"));
+        assert_eq!(history.expand("!2x").unwrap().as_deref(), Some("echo hix"));
+        for line in ["echo '!!'", "echo \\!!", "[ ! -f a ]", "[ a != b ]", "echo hi!"] {
+            assert_eq!(history.expand(line).unwrap(), None, "{}", line);
+        }
+        assert!(history.expand("!4").is_err());
+        assert!(history.expand("!0").is_err());
+        assert!(history.expand("!-4").is_err());
+        assert!(History::default().expand("!!").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use std::cell::Cell;
+use std::ffi::OsStr;
+use std::fmt::{self, Write};
+
+use crate::config;
+use crate::messages::MESSAGES;
+
+/// 取出消息目录中当前语言的文本，`{}` 依次替换为参数，`{0}`、`{1}` 按位置替换
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::text($key).to_string()
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format($crate::i18n::text($key), &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    ZhCn,
+    En,
+}
+
+thread_local! {
+    /// `--lang` 指定的语言，没有指定时根据环境变量决定
+    static OVERRIDE: Cell<Option<Lang>> = const { Cell::new(None) };
+}
+
+impl Lang {
+    /// 解析 `--lang` 的值，例如 `zh-CN`、`zh_CN.UTF-8`、`en`、`en_US`
+    pub fn parse(value: &str) -> Option<Lang> {
+        let value = value.to_lowercase();
+        if value.starts_with("zh") {
+            Some(Lang::ZhCn)
+        } else if value.starts_with("en") || value == "c" || value == "posix" {
+            Some(Lang::En)
+        } else {
+            None
+        }
+    }
+
+    /// 语言代码，也会通过环境变量 `CATSHELL_LANG` 传给插件
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lang::ZhCn => "zh-CN",
+            Lang::En => "en",
+        }
+    }
+
+    /// 按 `LC_ALL`、`LC_MESSAGES`、`LANG` 的顺序取第一个非空的变量，中文以外的语言都使用英文。
+    /// 都没有设置时（Windows上通常如此）使用中文
+    fn from_env() -> Lang {
+        for name in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            match config::var(name) {
+                Some(value) if !value.is_empty() => {
+                    return if value.to_lowercase().starts_with("zh") { Lang::ZhCn } else { Lang::En };
+                },
+                _ => {},
+            }
+        }
+        Lang::ZhCn
+    }
+}
+
+/// 当前线程使用的语言。每次都重新读取环境变量，REPL中 `export LANG=...` 后立即生效
+pub fn lang() -> Lang {
+    OVERRIDE.with(Cell::get).unwrap_or_else(Lang::from_env)
+}
+
+/// 设置当前线程的语言，`None` 表示恢复为根据环境变量决定
+pub fn set_lang(lang: Option<Lang>) {
+    OVERRIDE.with(|cell| cell.set(lang));
+}
+
+/// 在指定的语言下执行 `f`，结束后恢复原来的设置。`lang` 为 `None` 时不改变语言
+pub fn with_lang<T>(lang: Option<Lang>, f: impl FnOnce() -> T) -> T {
+    let Some(lang) = lang else { return f() };
+    let previous = OVERRIDE.with(|cell| cell.replace(Some(lang)));
+    let result = f();
+    set_lang(previous);
+    result
+}
+
+/// 在clap解析之前从参数中找出 `--lang`，这样 `--help` 和参数错误也能使用指定的语言
+pub fn lang_arg<S: AsRef<OsStr>>(args: &[S]) -> Option<Lang> {
+    let mut args = args.iter().map(|arg| arg.as_ref().to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--lang" {
+            return args.next().and_then(|value| Lang::parse(&value));
+        }
+        if let Some(value) = arg.strip_prefix("--lang=") {
+            return Lang::parse(value);
+        }
+    }
+    None
+}
+
+/// `--lang` 的clap解析函数
+pub fn parse_lang(value: &str) -> Result<Lang, String> {
+    Lang::parse(value).ok_or_else(|| tr!("i18n.unknown_lang", value))
+}
+
+/// 按键查找当前语言的文本
+pub fn lookup(key: &str) -> Option<&'static str> {
+    let (_, zh, en) = MESSAGES.iter().find(|(name, _, _)| *name == key)?;
+    Some(match lang() {
+        Lang::ZhCn => zh,
+        Lang::En => en,
+    })
+}
+
+/// 当前语言的文本，目录中没有这个键时返回键本身，便于发现遗漏
+pub fn text(key: &'static str) -> &'static str {
+    lookup(key).unwrap_or(key)
+}
+
+/// 把模板中的 `{}` 依次替换为参数，`{0}`、`{1}` 替换为指定位置的参数，`{{` 和 `}}` 表示花括号本身
+pub fn format(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut result = String::new();
+    let mut next = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => result.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => result.push('}'),
+            '{' => {
+                let mut index = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    index.push(digit);
+                }
+                chars.next_if_eq(&'}');
+                let index = index.parse().unwrap_or_else(|_| {
+                    next += 1;
+                    next - 1
+                });
+                if let Some(arg) = args.get(index) {
+                    let _ = write!(result, "{}", arg);
+                }
+            },
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// 模板用到的参数位置
+    fn arg_indices(template: &str) -> BTreeSet<usize> {
+        let args: Vec<String> = (0..10).map(|index| format!("<{}>", index)).collect();
+        let args: Vec<&dyn fmt::Display> = args.iter().map(|arg| arg as &dyn fmt::Display).collect();
+        let text = format(template, &args);
+        (0..10).filter(|index| text.contains(&format!("<{}>", index))).collect()
+    }
+
+    #[test]
+    fn formats_templates() {
+        assert_eq!(format("{} + {} = {}", &[&1, &"二", &3.5]), "1 + 二 = 3.5");
+        assert_eq!(format("{1} 在 {0} 中", &[&"a", &"b"]), "b 在 a 中");
+        assert_eq!(format("{{}} {}", &[&"x"]), "{} x");
+        assert_eq!(format("缺少 {} {}", &[&"a"]), "缺少 a ");
+    }
+
+    #[test]
+    fn parses_languages() {
+        assert_eq!(Lang::parse("zh_CN.UTF-8"), Some(Lang::ZhCn));
+        assert_eq!(Lang::parse("EN_us"), Some(Lang::En));
+        assert_eq!(Lang::parse("C"), Some(Lang::En));
+        assert_eq!(Lang::parse("fr"), None);
+        assert!(parse_lang("fr").is_err());
+
+        assert_eq!(lang_arg(&["catshell", "--lang", "en", "ls"]), Some(Lang::En));
+        assert_eq!(lang_arg(&["catshell", "ls", "--lang=zh"]), Some(Lang::ZhCn));
+        assert_eq!(lang_arg(&["catshell", "--", "--lang=en"]), None);
+        assert_eq!(lang_arg(&["catshell", "--lang"]), None);
+    }
+
+    #[test]
+    fn switches_language_per_thread() {
+        with_lang(Some(Lang::En), || {
+            assert_eq!(tr!("cd.not_dir", "x"), "Error: Not a directory: x");
+            with_lang(Some(Lang::ZhCn), || assert_eq!(tr!("cd.not_dir", "x"), "错误: 不是目录: x"));
+            assert_eq!(lang(), Lang::En);
+            assert_eq!(text("no.such.key"), "no.such.key");
+        });
+    }
+
+    #[test]
+    fn catalog_is_consistent() {
+        let mut keys = BTreeSet::new();
+        for (key, zh, en) in MESSAGES {
+            assert!(keys.insert(key), "重复的键 {}", key);
+            assert_eq!(arg_indices(zh), arg_indices(en), "{} 的两种语言使用了不同的参数", key);
+        }
+    }
+}
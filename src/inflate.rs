@@ -0,0 +1,243 @@
+//! zlib（RFC 1950）和deflate（RFC 1951）格式的解压，用来读取git对象
+
+/// 长度码257-285的基础长度和附加位数
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+/// 距离码0-29的基础距离和附加位数
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// 动态哈夫曼块中码长的码长的出现顺序
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// 解压zlib格式的数据，不检查末尾的Adler-32校验和。数据损坏时返回 `None`
+pub fn inflate_zlib(data: &[u8]) -> Option<Vec<u8>> {
+    // 第一个字节的低4位是压缩方法，8表示deflate；设置了预置字典的数据不支持
+    match data {
+        [cmf, flg, rest @ ..] if cmf & 0x0f == 8 && flg & 0x20 == 0 => inflate(rest),
+        _ => None,
+    }
+}
+
+/// 解压deflate格式的数据
+pub fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = BitReader { data, pos: 0, buffer: 0, count: 0 };
+    let mut output = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                // 不压缩的块：跳到字节边界，然后是长度和长度的反码
+                reader.align();
+                let len = reader.bits(16)?;
+                if reader.bits(16)? != !len & 0xffff {
+                    return None;
+                }
+                for _ in 0..len {
+                    output.push(reader.bits(8)? as u8);
+                }
+            },
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                decode_block(&mut reader, &mut output, &literals, &distances)?;
+            },
+            2 => {
+                let (literals, distances) = read_dynamic_tables(&mut reader)?;
+                decode_block(&mut reader, &mut output, &literals, &distances)?;
+            },
+            _ => return None,
+        }
+        if last {
+            return Some(output);
+        }
+    }
+}
+
+/// 按从低位到高位的顺序读取比特
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> Option<u32> {
+        while self.count < n {
+            let byte = *self.data.get(self.pos)?;
+            self.pos += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u32 << n) - 1);
+        self.buffer >>= n;
+        self.count -= n;
+        Some(value)
+    }
+
+    /// 丢弃当前字节中剩下的比特
+    fn align(&mut self) {
+        let drop = self.count % 8;
+        self.buffer >>= drop;
+        self.count -= drop;
+    }
+}
+
+/// 规范哈夫曼编码：每种码长的符号数，以及按码长和符号值排序的符号
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[usize::from(len)] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0usize; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + usize::from(counts[len]);
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[usize::from(len)]] = symbol as u16;
+                offsets[usize::from(len)] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    /// 逐位读取，直到得到一个完整的编码
+    fn decode(&self, reader: &mut BitReader) -> Option<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = i32::from(self.counts[len]);
+            if code - count < first {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+/// 读取动态哈夫曼块开头的字面量/长度表和距离表
+fn read_dynamic_tables(reader: &mut BitReader) -> Option<(Huffman, Huffman)> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_length_table = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_length_table.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last()?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            18 => (0, 11 + reader.bits(7)?),
+            _ => return None,
+        };
+        for _ in 0..repeat {
+            lengths.push(value);
+        }
+    }
+    if lengths.len() > literal_count + distance_count {
+        return None;
+    }
+    let (literal_lengths, distance_lengths) = lengths.split_at(literal_count);
+    Some((Huffman::new(literal_lengths), Huffman::new(distance_lengths)))
+}
+
+/// 解码一个压缩块中的字面量和 (长度, 距离) 对，直到块结束符256
+fn decode_block(reader: &mut BitReader, output: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Option<()> {
+    loop {
+        let symbol = usize::from(literals.decode(reader)?);
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Some(()),
+            _ => {
+                let index = symbol - 257;
+                let len = usize::from(*LENGTH_BASE.get(index)?) + reader.bits(u32::from(LENGTH_EXTRA[index]))? as usize;
+                let index = usize::from(distances.decode(reader)?);
+                let distance = usize::from(*DISTANCE_BASE.get(index)?) + reader.bits(u32::from(DISTANCE_EXTRA[index]))? as usize;
+                if distance > output.len() {
+                    return None;
+                }
+                // 重复的内容可以和自身重叠，所以逐字节复制
+                let start = output.len() - distance;
+                for i in 0..len {
+                    output.push(output[start + i]);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn inflates_stored_blocks() {
+        // 两个不压缩的块，第二个是最后一个
+        let data = [0x00, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c', 0x01, 0x02, 0x00, 0xfd, 0xff, b'd', b'e'];
+        assert_eq!(inflate(&data).unwrap(), b"abcde");
+        // 长度和反码不一致
+        assert_eq!(inflate(&[0x01, 0x03, 0x00, 0xfc, 0xfe, b'a', b'b', b'c']), None);
+    }
+
+    #[test]
+    fn inflates_huffman_blocks() {
+        // 固定哈夫曼码，带有重复的引用
+        let fixed = [203, 72, 205, 201, 201, 87, 200, 64, 39, 1];
+        assert_eq!(inflate(&fixed).unwrap(), b"hello hello hello hello");
+
+        let text = b"the quick brown fox jumps over the lazy dog. ".repeat(3);
+        let fixed = unhex(
+            "2bc94855282ccd4cce56482aca2fcf5348cbaf50c82acd2d2856c82f4b2d5228014ae72456552aa4e4a7eb8179b4511ce2e1aa1018eae9ecade014e41feea7e0e61f0100",
+        );
+        assert_eq!(inflate(&fixed).unwrap(), [&text[..], b"THE QUICK BROWN FOX"].concat());
+
+        // 动态哈夫曼码
+        let text: String = (0..20).map(|i| format!("line {}: abcabcabc xyz\n", i)).collect();
+        let dynamic = unhex(
+            "75cf3b0e40501400d1de2aee128c3fbbe14521795163f5224a23996aba93b77d8d728a79496f719c57919f8bde4a6fadb7d1dbeaedf4f67a07bda32b7e70aec379b80f07e2429c881b71241fe50d",
+        );
+        assert_eq!(inflate(&dynamic).unwrap(), text.as_bytes());
+        assert_eq!(inflate(&dynamic[..dynamic.len() / 2]), None);
+    }
+
+    #[test]
+    fn inflates_zlib_streams() {
+        let data = [120, 218, 203, 72, 205, 201, 201, 87, 200, 64, 39, 1, 104, 3, 8, 177];
+        assert_eq!(inflate_zlib(&data).unwrap(), b"hello hello hello hello");
+        // 不是deflate方法，或者设置了预置字典
+        assert_eq!(inflate_zlib(&[0x79, 0xda, 0x03, 0x00]), None);
+        assert_eq!(inflate_zlib(&[0x78, 0xbb, 0x03, 0x00]), None);
+        assert_eq!(inflate_zlib(&[0x78]), None);
+    }
+}
@@ -0,0 +1,487 @@
+use std::fmt;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::config;
+use crate::i18n;
+use crate::shell::exit_code;
+use crate::signal;
+use crate::streams::Output;
+use crate::style;
+use crate::terminal;
+
+/// 在前台等待任务时检查任务状态和 Ctrl+C 的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// `kill` 可以发送的信号
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    Int,
+    Kill,
+    Term,
+    Stop,
+    Cont,
+}
+
+impl Signal {
+    pub const ALL: [Signal; 5] = [Signal::Int, Signal::Kill, Signal::Term, Signal::Stop, Signal::Cont];
+
+    /// 信号名称，不带 `SIG` 前缀
+    pub fn name(self) -> &'static str {
+        match self {
+            Signal::Int => "INT",
+            Signal::Kill => "KILL",
+            Signal::Term => "TERM",
+            Signal::Stop => "STOP",
+            Signal::Cont => "CONT",
+        }
+    }
+
+    /// 当前平台上的信号编号，`STOP` 和 `CONT` 在Linux和BSD上不同
+    pub fn number(self) -> i32 {
+        match self {
+            Signal::Int => 2,
+            Signal::Kill => 9,
+            Signal::Term => 15,
+            Signal::Stop => imp::SIGSTOP,
+            Signal::Cont => imp::SIGCONT,
+        }
+    }
+
+    /// 解析 `kill -信号` 中的信号，支持 `TERM`、`SIGTERM` 和编号
+    pub fn parse(text: &str) -> Option<Signal> {
+        let name = text.to_uppercase();
+        let name = name.strip_prefix("SIG").unwrap_or(&name);
+        Self::ALL
+            .into_iter()
+            .find(|signal| signal.name() == name || signal.number().to_string() == name)
+    }
+}
+
+/// 任务的状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    Running,
+    /// 被 `kill -STOP` 暂停，`bg` 或 `fg` 让它继续运行
+    Stopped,
+    /// 已经结束，记录退出状态
+    Done(i32),
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            State::Running => write!(f, "{}", tr!("jobs.running")),
+            State::Stopped => write!(f, "{}", tr!("jobs.stopped")),
+            State::Done(0) => write!(f, "{}", tr!("jobs.done")),
+            State::Done(code) => write!(f, "{}", tr!("jobs.exit", code)),
+        }
+    }
+}
+
+/// 任务的执行方式
+enum Task {
+    /// 外部程序，在自己的进程组中运行，终端上的 Ctrl+C 不会发给它
+    Process(Child),
+    /// 在线程中执行的 `Commands` 命令，通过取消标志通知它停止
+    Thread {
+        handle: Option<JoinHandle<i32>>,
+        cancel: Arc<AtomicBool>,
+    },
+}
+
+/// 一个后台任务
+pub struct Job {
+    /// 任务号，`%n` 中的n
+    pub id: usize,
+    /// 启动任务的命令，用于显示
+    pub command: String,
+    pub state: State,
+    task: Task,
+}
+
+impl Job {
+    /// 外部程序的进程号，内置命令在catshell进程中运行，没有自己的进程号
+    pub fn pid(&self) -> Option<u32> {
+        match &self.task {
+            Task::Process(child) => Some(child.id()),
+            Task::Thread { .. } => None,
+        }
+    }
+
+    /// `jobs` 显示的一行，`show_pid` 为 `true` 时在状态前显示进程号
+    pub fn describe(&self, show_pid: bool) -> String {
+        let state = self.state.to_string();
+        let padding = " ".repeat(8usize.saturating_sub(terminal::display_width(&state)));
+        let pid = match self.pid() {
+            Some(pid) if show_pid => format!("{:<7} ", pid),
+            None if show_pid => "-       ".to_string(),
+            _ => String::new(),
+        };
+        format!("[{}]  {}{}{}  {}", self.id, pid, state, padding, self.command)
+    }
+
+    /// 检查任务是否已经结束，不会阻塞
+    fn poll(&mut self) {
+        if let State::Done(_) = self.state {
+            return;
+        }
+        match &mut self.task {
+            Task::Process(child) => {
+                if let Ok(Some(status)) = child.try_wait() {
+                    self.state = State::Done(exit_code(status));
+                }
+            },
+            Task::Thread { handle, .. } => {
+                if handle.as_ref().is_some_and(|handle| handle.is_finished()) {
+                    // 线程panic时按失败处理
+                    let status = handle.take().map_or(1, |handle| handle.join().unwrap_or(1));
+                    self.state = State::Done(status);
+                }
+            },
+        }
+    }
+
+    fn send(&mut self, signal: Signal) -> Result<(), String> {
+        match &mut self.task {
+            Task::Process(child) => imp::send(child, signal),
+            Task::Thread { .. } if matches!(signal, Signal::Stop | Signal::Cont) => {
+                Err(tr!("jobs.builtin_stop", self.id))
+            },
+            // 内置命令检查到取消标志后自己停止，没有检查的命令会一直运行到结束
+            Task::Thread { cancel, .. } => {
+                cancel.store(true, Ordering::SeqCst);
+                Ok(())
+            },
+        }
+    }
+}
+
+/// 当前shell的任务表
+#[derive(Default)]
+pub struct Jobs {
+    jobs: Vec<Job>,
+}
+
+impl Jobs {
+    /// 记录一个已经启动的外部程序
+    pub fn add_process(&mut self, command: String, child: Child) -> &Job {
+        self.push(command, Task::Process(child))
+    }
+
+    /// 在新线程中执行 `task`，线程中的 [`signal::interrupted`] 检查的是这个任务自己的取消标志
+    pub fn spawn_thread<F>(&mut self, command: String, task: F) -> &Job
+    where
+        F: FnOnce() -> i32 + Send + 'static,
+    {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        let lang = i18n::lang();
+        let output_style = style::current();
+        let environment = config::environment();
+        let handle = thread::spawn(move || {
+            signal::set_cancel_flag(flag);
+            i18n::set_lang(Some(lang));
+            style::set(output_style);
+            config::with_environment(environment, task)
+        });
+        self.push(command, Task::Thread { handle: Some(handle), cancel })
+    }
+
+    fn push(&mut self, command: String, task: Task) -> &Job {
+        let id = self.jobs.last().map_or(1, |job| job.id + 1);
+        self.jobs.push(Job { id, command, state: State::Running, task });
+        &self.jobs[self.jobs.len() - 1]
+    }
+
+    /// 更新所有任务的状态后返回任务列表
+    pub fn list(&mut self) -> &[Job] {
+        self.jobs.iter_mut().for_each(Job::poll);
+        &self.jobs
+    }
+
+    /// 从任务表中移除已经结束的任务并返回它们，用于在提示符前报告
+    pub fn remove_finished(&mut self) -> Vec<Job> {
+        self.jobs.iter_mut().for_each(Job::poll);
+        let (finished, running) = std::mem::take(&mut self.jobs)
+            .into_iter()
+            .partition(|job| matches!(job.state, State::Done(_)));
+        self.jobs = running;
+        finished
+    }
+
+    pub fn get(&self, index: usize) -> &Job {
+        &self.jobs[index]
+    }
+
+    /// 按任务说明查找任务，返回它在任务表中的位置。省略、`%%` 和 `%+` 表示最近的任务，`%-` 表示前一个，
+    /// `%n` 是任务号，`%name` 是以name开头的命令
+    pub fn find(&self, spec: Option<&str>) -> Result<usize, String> {
+        let spec = spec.unwrap_or("%%");
+        let text = spec.strip_prefix('%').unwrap_or(spec);
+        let index = match text {
+            "" | "%" | "+" => self.jobs.len().checked_sub(1),
+            "-" => self.jobs.len().checked_sub(2),
+            _ => match text.parse::<usize>() {
+                Ok(id) => self.jobs.iter().position(|job| job.id == id),
+                Err(_) => self.jobs.iter().rposition(|job| job.command.starts_with(text)),
+            },
+        };
+        match index {
+            Some(index) => Ok(index),
+            None if self.jobs.is_empty() => Err(tr!("jobs.none")),
+            None => Err(tr!("jobs.no_such_job", spec)),
+        }
+    }
+
+    /// 在前台等待任务结束，返回它的退出状态。暂停的任务会先继续运行。
+    /// Ctrl+C 转发给任务：外部程序收到 SIGINT，内置命令被设置取消标志；
+    /// 内置命令没有响应时再按一次 Ctrl+C，任务留在后台继续运行
+    pub fn foreground(&mut self, index: usize) -> i32 {
+        let mut job = self.jobs.remove(index);
+        if job.state == State::Stopped && job.send(Signal::Cont).is_ok() {
+            job.state = State::Running;
+        }
+
+        let mut interrupted = false;
+        let status = loop {
+            job.poll();
+            if let State::Done(status) = job.state {
+                break status;
+            }
+            if signal::interrupted() {
+                signal::clear();
+                let cancelled = interrupted;
+                interrupted = true;
+                match job.task {
+                    Task::Thread { .. } if cancelled => {
+                        println!();
+                        eprintln!("{}{}", style::icon(&Output::Stderr, "⚠️"), tr!("jobs.not_responding", job.id));
+                        let position = self.jobs.partition_point(|other| other.id < job.id);
+                        self.jobs.insert(position, job);
+                        return 130;
+                    },
+                    _ => {
+                        let _ = job.send(Signal::Int);
+                    },
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        if interrupted {
+            // 终端上的 ^C 后面还没有换行
+            println!();
+            if status == 0 {
+                return 130;
+            }
+        }
+        status
+    }
+
+    /// 让暂停的任务在后台继续运行
+    pub fn resume(&mut self, index: usize) -> Result<(), String> {
+        let job = &mut self.jobs[index];
+        job.poll();
+        match job.state {
+            State::Stopped => {
+                job.send(Signal::Cont)?;
+                job.state = State::Running;
+                Ok(())
+            },
+            State::Running => Err(tr!("jobs.already_running", job.id)),
+            State::Done(_) => Err(tr!("jobs.already_done", job.id)),
+        }
+    }
+
+    /// 向任务发送信号。暂停的任务收到其他信号后会被继续运行，这样它才能处理这个信号
+    pub fn signal(&mut self, index: usize, signal: Signal) -> Result<(), String> {
+        let job = &mut self.jobs[index];
+        job.send(signal)?;
+        match signal {
+            Signal::Stop => job.state = State::Stopped,
+            _ if job.state == State::Stopped => {
+                job.send(Signal::Cont)?;
+                job.state = State::Running;
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+}
+
+/// 向不在任务表中的进程发送信号，对应 `kill 进程号`
+pub fn kill_pid(pid: u32, signal: Signal) -> Result<(), String> {
+    imp::send_pid(pid, signal)
+}
+
+/// 让后台启动的程序使用自己的进程组，终端上的 Ctrl+C 不会同时发给它
+pub fn detach(command: &mut Command) {
+    imp::detach(command);
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::io;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command};
+
+    use super::Signal;
+
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+    pub const SIGSTOP: i32 = 17;
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+    pub const SIGCONT: i32 = 19;
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd")))]
+    pub const SIGSTOP: i32 = 19;
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd")))]
+    pub const SIGCONT: i32 = 18;
+
+    extern "C" {
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+
+    /// 发给任务的整个进程组，程序自己启动的子进程也会收到
+    pub fn send(child: &mut Child, signal: Signal) -> Result<(), String> {
+        send_raw(-(child.id() as i32), signal)
+    }
+
+    pub fn send_pid(pid: u32, signal: Signal) -> Result<(), String> {
+        send_raw(pid as i32, signal)
+    }
+
+    fn send_raw(pid: i32, signal: Signal) -> Result<(), String> {
+        // SAFETY: kill只读取两个整数参数
+        if unsafe { kill(pid, signal.number()) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error().to_string())
+        }
+    }
+
+    pub fn detach(command: &mut Command) {
+        command.process_group(0);
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::os::windows::process::CommandExt;
+    use std::process::{Child, Command};
+
+    use super::Signal;
+
+    // Windows没有信号，这两个编号只用于 `kill -l` 的显示
+    pub const SIGSTOP: i32 = 19;
+    pub const SIGCONT: i32 = 18;
+
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x200;
+
+    /// Windows上不能暂停进程，其他信号都直接结束进程
+    pub fn send(child: &mut Child, signal: Signal) -> Result<(), String> {
+        match signal {
+            Signal::Stop | Signal::Cont => Err(tr!("jobs.windows_stop")),
+            _ => child.kill().map_err(|err| err.to_string()),
+        }
+    }
+
+    pub fn send_pid(_pid: u32, _signal: Signal) -> Result<(), String> {
+        Err(tr!("jobs.windows_kill"))
+    }
+
+    pub fn detach(command: &mut Command) {
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 等待任务结束，返回它的状态
+    fn wait(jobs: &mut Jobs, index: usize) -> State {
+        for _ in 0..200 {
+            let state = jobs.list()[index].state;
+            if let State::Done(_) = state {
+                return state;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("任务没有结束");
+    }
+
+    #[test]
+    fn parses_signals() {
+        assert_eq!(Signal::parse("TERM"), Some(Signal::Term));
+        assert_eq!(Signal::parse("sigkill"), Some(Signal::Kill));
+        assert_eq!(Signal::parse("2"), Some(Signal::Int));
+        assert_eq!(Signal::parse(&Signal::Stop.number().to_string()), Some(Signal::Stop));
+        assert_eq!(Signal::parse("HUP"), None);
+        assert_eq!(Signal::parse(""), None);
+    }
+
+    #[test]
+    fn finds_jobs_by_spec() {
+        let mut jobs = Jobs::default();
+        assert!(jobs.find(None).is_err());
+        jobs.spawn_thread("ls -R /".to_string(), || 0);
+        jobs.spawn_thread("ping example.com".to_string(), || 3);
+        jobs.spawn_thread("ls src".to_string(), || 0);
+
+        assert_eq!(jobs.find(None), Ok(2));
+        assert_eq!(jobs.find(Some("%+")), Ok(2));
+        assert_eq!(jobs.find(Some("%-")), Ok(1));
+        assert_eq!(jobs.find(Some("%1")), Ok(0));
+        assert_eq!(jobs.find(Some("%ping")), Ok(1));
+        assert_eq!(jobs.find(Some("%ls")), Ok(2));
+        assert!(jobs.find(Some("%9")).is_err());
+
+        assert_eq!(wait(&mut jobs, 1), State::Done(3));
+        wait(&mut jobs, 0);
+        wait(&mut jobs, 2);
+        let finished: Vec<usize> = jobs.remove_finished().iter().map(|job| job.id).collect();
+        assert_eq!(finished, [1, 2, 3]);
+        assert!(jobs.list().is_empty());
+    }
+
+    #[test]
+    fn cancels_builtin_threads() {
+        let mut jobs = Jobs::default();
+        jobs.spawn_thread("loop".to_string(), || {
+            while !signal::interrupted() {
+                thread::sleep(Duration::from_millis(5));
+            }
+            130
+        });
+        assert_eq!(jobs.list()[0].state, State::Running);
+        assert!(jobs.get(0).describe(true).starts_with("[1]  -       "));
+        assert!(jobs.signal(0, Signal::Stop).is_err());
+        jobs.signal(0, Signal::Term).unwrap();
+        assert_eq!(wait(&mut jobs, 0), State::Done(130));
+        assert!(jobs.resume(0).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signals_processes() {
+        let mut jobs = Jobs::default();
+        // 信号发给任务的进程组，和后台启动的程序一样先让它使用自己的进程组
+        let mut command = Command::new("sleep");
+        command.arg("30");
+        detach(&mut command);
+        let child = command.spawn().unwrap();
+        let pid = child.id();
+        jobs.add_process("sleep 30".to_string(), child);
+        assert_eq!(jobs.get(0).pid(), Some(pid));
+
+        jobs.signal(0, Signal::Stop).unwrap();
+        assert_eq!(jobs.list()[0].state, State::Stopped);
+        jobs.resume(0).unwrap();
+        assert!(jobs.resume(0).is_err());
+        jobs.signal(0, Signal::Term).unwrap();
+        assert_eq!(wait(&mut jobs, 0), State::Done(128 + 15));
+    }
+}
@@ -0,0 +1,339 @@
+//! `--json` 输出和插件协议使用的最小JSON实现
+
+use std::fmt;
+
+/// 一个JSON值。对象用 `Vec` 保存，输出时保持字段的插入顺序
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+/// 可以转换成JSON的命令结果
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+impl Value {
+    /// 从字段列表构造对象
+    pub fn object<const N: usize>(fields: [(&str, Value); N]) -> Value {
+        Value::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    /// 对象中的字段，不是对象或者没有该字段时返回 `None`
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// 解析一个JSON文本，前后可以有空白。格式错误时返回 `None`
+pub fn parse(text: &str) -> Option<Value> {
+    let mut parser = Parser { chars: text.chars().peekable() };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    parser.chars.peek().is_none().then_some(value)
+}
+
+/// 递归下降的JSON解析器
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    /// 跳过空白后读取指定的字符
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.skip_whitespace();
+        self.chars.next_if_eq(&expected).map(|_| ())
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        self.skip_whitespace();
+        match *self.chars.peek()? {
+            '{' => self.object(),
+            '[' => self.array(),
+            '"' => self.string().map(Value::String),
+            't' => self.keyword("true", Value::Bool(true)),
+            'f' => self.keyword("false", Value::Bool(false)),
+            'n' => self.keyword("null", Value::Null),
+            _ => self.number(),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Option<Value> {
+        for expected in word.chars() {
+            self.chars.next_if_eq(&expected)?;
+        }
+        Some(value)
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let mut text = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+            text.push(c);
+        }
+        match text.parse::<i64>() {
+            Ok(value) => Some(Value::Int(value)),
+            Err(_) => text.parse::<f64>().ok().map(Value::Float),
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.chars.next_if_eq(&'"')?;
+        let mut text = String::new();
+        loop {
+            match self.chars.next()? {
+                '"' => return Some(text),
+                '\\' => match self.chars.next()? {
+                    'n' => text.push('\n'),
+                    'r' => text.push('\r'),
+                    't' => text.push('\t'),
+                    'b' => text.push('\u{8}'),
+                    'f' => text.push('\u{c}'),
+                    'u' => text.push(self.unicode_escape()?),
+                    c => text.push(c),
+                },
+                c => text.push(c),
+            }
+        }
+    }
+
+    /// `\uXXXX` 转义，包括用两个转义表示的代理对
+    fn unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high);
+        }
+        self.chars.next_if_eq(&'\\')?;
+        self.chars.next_if_eq(&'u')?;
+        let low = self.hex4()?;
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low.checked_sub(0xDC00)?))
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..4 {
+            value = value * 16 + self.chars.next()?.to_digit(16)?;
+        }
+        Some(value)
+    }
+
+    fn array(&mut self) -> Option<Value> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        if self.expect(']').is_some() {
+            return Some(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.expect(']').is_some() {
+                return Some(Value::Array(items));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn object(&mut self) -> Option<Value> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        if self.expect('}').is_some() {
+            return Some(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            if self.expect('}').is_some() {
+                return Some(Value::Object(fields));
+            }
+            self.expect(',')?;
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Int(value) => write!(f, "{}", value),
+            // JSON没有NaN和无穷大
+            Value::Float(value) if value.is_finite() => write!(f, "{}", value),
+            Value::Float(_) => write!(f, "null"),
+            Value::String(text) => write_string(f, text),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+/// 输出带引号的字符串，转义引号、反斜杠和控制字符
+fn write_string(f: &mut fmt::Formatter, text: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map(Value::Int).unwrap_or(Value::Float(value as f64))
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Value::Int(value.into())
+    }
+}
+
+impl From<u16> for Value {
+    fn from(value: u16) -> Self {
+        Value::Int(value.into())
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::from(value as u64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(Value::Null)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        Value::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_values() {
+        let value = Value::object([
+            ("name", "a \"b\"\\\n\u{1}".into()),
+            ("size", 42u64.into()),
+            ("big", u64::MAX.into()),
+            ("ratio", 0.5.into()),
+            ("nan", f64::NAN.into()),
+            ("owner", Option::<String>::None.into()),
+            ("tags", vec!["x", "y"].into()),
+            ("empty", Value::Object(Vec::new())),
+        ]);
+        assert_eq!(
+            value.to_string(),
+            r#"{"name":"a \"b\"\\\n\u0001","size":42,"big":18446744073709552000,"ratio":0.5,"nan":null,"owner":null,"tags":["x","y"],"empty":{}}"#
+        );
+    }
+
+    #[test]
+    fn parses_what_it_encodes() {
+        let value = Value::object([
+            ("text", "猫 🐱 \t\"\\".into()),
+            ("list", vec![Value::Int(-1), Value::Float(2.5), Value::Bool(true), Value::Null].into()),
+            ("nested", Value::object([("empty", Value::Array(Vec::new()))])),
+        ]);
+        assert_eq!(parse(&value.to_string()), Some(value.clone()));
+        assert_eq!(parse(&format!("  {}\n", value)).unwrap().get("text").and_then(Value::as_str), Some("猫 🐱 \t\"\\"));
+        assert_eq!(parse(r#""\u00e9\ud83d\udc31\/""#), Some(Value::String("é🐱/".to_string())));
+        assert_eq!(parse(r#"{"code": 3}"#).unwrap().get("code").and_then(Value::as_i64), Some(3));
+    }
+
+    #[test]
+    fn rejects_malformed_text() {
+        for text in ["", "{", "[1,]", "{\"a\" 1}", "{a:1}", "tru", "nullx", "1 2", "\"abc", "\"\\ud83d\"", "-"] {
+            assert_eq!(parse(text), None, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crate::parser::{ParseError, RedirectKind};
+
+/// 词法单元。单词保留原始的引号和 `$` 表达式，执行时再由 `expand` 模块展开
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Word(String),
+    Pipe,
+    Redirect(RedirectKind),
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+    /// 单独的 `&`，在后台执行前面的命令
+    Background,
+    /// `;`
+    Semi,
+    Newline,
+    LParen,
+    RParen,
+}
+
+/// 把输入切分成词法单元，引号内的空白和操作符不会被切分。
+/// 引号或 `$(` 没有闭合时返回 `ParseError::Incomplete`，交互模式下可以继续读入下一行
+pub fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' => {
+                let end = find_quote_end(&chars, i).ok_or(ParseError::Incomplete)?;
+                word.extend(&chars[i..=end]);
+                i = end;
+            },
+            '"' => {
+                let end = find_quote_end(&chars, i).ok_or(ParseError::Incomplete)?;
+                word.extend(&chars[i..=end]);
+                i = end;
+            },
+            '\\' if chars.get(i + 1) == Some(&'\n') => {
+                // 行尾的反斜杠表示续行
+                i += 1;
+            },
+            '\\' => {
+                // 反斜杠和被转义的字符原样保留，展开时再去掉
+                word.push(c);
+                if let Some(&next) = chars.get(i + 1) {
+                    word.push(next);
+                    i += 1;
+                }
+            },
+            '$' if matches!(chars.get(i + 1), Some('(') | Some('{')) => {
+                let end = find_dollar_end(&chars, i).ok_or(ParseError::Incomplete)?;
+                word.extend(&chars[i..=end]);
+                i = end;
+            },
+            // 单词开头的 '#' 表示注释，一直到行尾
+            '#' if word.is_empty() => {
+                while i + 1 < chars.len() && chars[i + 1] != '\n' {
+                    i += 1;
+                }
+            },
+            '|' => {
+                flush_word(&mut word, &mut tokens);
+                if chars.get(i + 1) == Some(&'|') {
+                    tokens.push(Token::Or);
+                    i += 1;
+                } else {
+                    tokens.push(Token::Pipe);
+                }
+            },
+            '&' if chars.get(i + 1) == Some(&'&') => {
+                flush_word(&mut word, &mut tokens);
+                tokens.push(Token::And);
+                i += 1;
+            },
+            '&' => {
+                flush_word(&mut word, &mut tokens);
+                tokens.push(Token::Background);
+            },
+            ';' => {
+                flush_word(&mut word, &mut tokens);
+                tokens.push(Token::Semi);
+            },
+            '\n' => {
+                flush_word(&mut word, &mut tokens);
+                tokens.push(Token::Newline);
+            },
+            '(' => {
+                flush_word(&mut word, &mut tokens);
+                tokens.push(Token::LParen);
+            },
+            ')' => {
+                flush_word(&mut word, &mut tokens);
+                tokens.push(Token::RParen);
+            },
+            '<' => {
+                flush_word(&mut word, &mut tokens);
+                tokens.push(Token::Redirect(RedirectKind::Stdin));
+            },
+            '>' => {
+                // 单独的 "1" 或 "2" 紧跟 '>' 时表示重定向标准输出或标准错误
+                let stderr = word == "2";
+                if stderr || word == "1" {
+                    word.clear();
+                } else {
+                    flush_word(&mut word, &mut tokens);
+                }
+
+                // `2>&1` 和 `>&2` 把一个输出复制到另一个输出，其余的文件描述符复制不支持
+                if chars.get(i + 1) == Some(&'&') {
+                    let kind = match (stderr, chars.get(i + 2)) {
+                        (true, Some('1')) => RedirectKind::StderrToStdout,
+                        (false, Some('2')) => RedirectKind::StdoutToStderr,
+                        _ => return Err(ParseError::Syntax(tr!("parser.unsupported_duplicate"))),
+                    };
+                    if chars.get(i + 3).is_some_and(|&c| !c.is_whitespace() && !"|&;<>()".contains(c)) {
+                        return Err(ParseError::Syntax(tr!("parser.unsupported_duplicate")));
+                    }
+                    tokens.push(Token::Redirect(kind));
+                    i += 3;
+                    continue;
+                }
+
+                let append = chars.get(i + 1) == Some(&'>');
+                if append {
+                    i += 1;
+                    if chars.get(i + 1) == Some(&'&') {
+                        return Err(ParseError::Syntax(tr!("parser.unsupported_duplicate")));
+                    }
+                }
+
+                let kind = match (stderr, append) {
+                    (false, false) => RedirectKind::Stdout,
+                    (false, true) => RedirectKind::StdoutAppend,
+                    (true, false) => RedirectKind::Stderr,
+                    (true, true) => RedirectKind::StderrAppend,
+                };
+                tokens.push(Token::Redirect(kind));
+            },
+            c if c.is_whitespace() => flush_word(&mut word, &mut tokens),
+            c => word.push(c),
+        }
+        i += 1;
+    }
+    flush_word(&mut word, &mut tokens);
+
+    Ok(tokens)
+}
+
+/// 未加引号时反斜杠可以转义的字符：空白、引号、`$`、操作符和通配符等对shell有特殊含义的字符。
+/// 反斜杠后面是其他字符时保留反斜杠本身，所以 `cat C:\Users\a.txt` 这样的Windows路径可以直接使用
+pub fn is_special(c: char) -> bool {
+    c.is_whitespace() || "'\"\\|;&()<>$`*?[]{}#!,".contains(c)
+}
+
+fn flush_word(word: &mut String, tokens: &mut Vec<Token>) {
+    if !word.is_empty() {
+        tokens.push(Token::Word(std::mem::take(word)));
+    }
+}
+
+/// 从 `start` 处的引号开始，找到与之匹配的结束引号的位置
+pub fn find_quote_end(chars: &[char], start: usize) -> Option<usize> {
+    let quote = chars[start];
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            c if c == quote => return Some(i),
+            // 只有双引号内的反斜杠和 `$(...)` 需要特殊处理
+            '\\' if quote == '"' => i += 1,
+            '$' if quote == '"' && matches!(chars.get(i + 1), Some('(') | Some('{')) => {
+                i = find_dollar_end(chars, i)?;
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    None
+}
+
+/// 从 `start` 处的 `$(` 或 `${` 开始，找到匹配的 `)` 或 `}` 的位置，支持嵌套和引号
+pub fn find_dollar_end(chars: &[char], start: usize) -> Option<usize> {
+    let (open, close) = match chars.get(start + 1) {
+        Some('(') => ('(', ')'),
+        Some('{') => ('{', '}'),
+        _ => return None,
+    };
+    let mut depth = 0;
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\'' | '"' => i = find_quote_end(chars, i)?,
+            '\\' => i += 1,
+            '$' if matches!(chars.get(i + 1), Some('(') | Some('{')) => {
+                i = find_dollar_end(chars, i)?;
+            },
+            c if c == open => depth += 1,
+            c if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str) -> Token {
+        Token::Word(text.to_string())
+    }
+
+    #[test]
+    fn keeps_backslashes_in_words() {
+        assert_eq!(tokenize(r"cat C:\Users\a.txt").unwrap(), [word("cat"), word(r"C:\Users\a.txt")]);
+        assert_eq!(tokenize(r"cat a\ b\|c").unwrap(), [word("cat"), word(r"a\ b\|c")]);
+        assert_eq!(tokenize("echo a \\\nb").unwrap(), [word("echo"), word("a"), word("b")]);
+        assert!(is_special('*') && is_special(' ') && is_special('\\'));
+        assert!(!is_special('U') && !is_special('n') && !is_special('.'));
+    }
+
+    #[test]
+    fn duplicates_stdout_and_stderr() {
+        use RedirectKind::*;
+        assert_eq!(
+            tokenize("ls x 2>&1 | cat").unwrap(),
+            [word("ls"), word("x"), Token::Redirect(StderrToStdout), Token::Pipe, word("cat")]
+        );
+        assert_eq!(tokenize("echo a >&2").unwrap(), [word("echo"), word("a"), Token::Redirect(StdoutToStderr)]);
+        assert_eq!(tokenize("echo a 1>&2;").unwrap(), [word("echo"), word("a"), Token::Redirect(StdoutToStderr), Token::Semi]);
+        assert_eq!(
+            tokenize("ls 1>out 2>>err").unwrap(),
+            [word("ls"), Token::Redirect(Stdout), word("out"), Token::Redirect(StderrAppend), word("err")]
+        );
+        for source in ["ls 2>&3", "ls >&1", "ls >& file", "ls 2>&12", "ls 2>>&1"] {
+            assert!(matches!(tokenize(source), Err(ParseError::Syntax(_))), "{}", source);
+        }
+    }
+
+    #[test]
+    fn keeps_quotes_and_substitutions_in_one_word() {
+        assert_eq!(
+            tokenize(r#"echo "a b|c" 'd;e' x"y z"w"#).unwrap(),
+            [word("echo"), word(r#""a b|c""#), word("'d;e'"), word(r#"x"y z"w"#)]
+        );
+        assert_eq!(
+            tokenize("echo $(ls | grep \"a b\") ${HOME}/x").unwrap(),
+            [word("echo"), word("$(ls | grep \"a b\")"), word("${HOME}/x")]
+        );
+        assert_eq!(tokenize("echo \"$(echo \")\")\"").unwrap(), [word("echo"), word("\"$(echo \")\")\"")]);
+        assert_eq!(tokenize("echo $((1)) a#b # comment").unwrap(), [word("echo"), word("$((1))"), word("a#b")]);
+    }
+
+    #[test]
+    fn unclosed_quotes_are_incomplete() {
+        for source in ["echo \"abc", "echo 'abc", "echo $(ls", "echo ${HOME", "echo \"$(ls\""] {
+            assert_eq!(tokenize(source), Err(ParseError::Incomplete), "{}", source);
+        }
+    }
+
+    #[test]
+    fn finds_matching_ends() {
+        let chars: Vec<char> = r#""a\"b" 'c\' $(a $(b) ")") ${x}"#.chars().collect();
+        assert_eq!(find_quote_end(&chars, 0), Some(5));
+        assert_eq!(find_quote_end(&chars, 7), Some(10));
+        assert_eq!(find_dollar_end(&chars, 12), Some(24));
+        assert_eq!(find_dollar_end(&chars, 26), Some(29));
+    }
+}
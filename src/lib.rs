@@ -0,0 +1,61 @@
+//! catshell的库部分：命令行和REPL使用的所有命令都在这里实现，
+//! 其他Rust程序也可以直接调用带类型的接口，而不必解析命令输出。
+//!
+//! ```no_run
+//! let entries = catshell::ls::list(".", &catshell::ls::ListOptions::default()).unwrap();
+//! for entry in entries {
+//!     println!("{} {}", entry.name, entry.size);
+//! }
+//! ```
+
+#[macro_use]
+pub mod streams; // 输出宏需要最先声明
+#[macro_use]
+pub mod i18n; // 添加多语言消息模块，tr! 宏也需要先声明
+mod messages; // 添加消息目录模块
+pub mod style; // 添加输出样式模块
+pub mod error; // 添加统一错误类型模块
+pub mod json; // 添加JSON输出模块
+pub mod cli; // 添加命令行定义模块
+pub mod ls;
+mod datetime; // 添加本地时间模块
+mod tree; // 添加tree模块
+mod gitignore; // 添加.gitignore匹配模块
+pub mod ls_colors; // 添加LS_COLORS配色模块
+mod git; // 添加git状态模块
+mod sha1; // 添加SHA-1模块
+mod inflate; // 添加zlib解压模块
+mod pwd;
+mod rm;
+pub mod uname;
+mod cd; // 添加cd模块
+mod mkdir; // 添加mkdir模块
+pub mod cat; // 添加cat模块
+pub mod curl; // 添加curl模块
+mod cmatrix; // 添加cmatrix模块
+mod vim; // 添加vim模块
+mod open_browser; // 添加open_browser模块
+mod open; // 添加open模块
+pub mod server; // 添加server模块
+pub mod ping; // 添加ping模块
+pub mod zip; // 添加zip模块
+mod tui; // 添加TUI模块
+mod shell; // 添加交互式shell模块
+mod parser; // 添加命令行解析模块
+mod lexer; // 添加词法分析模块
+mod expand; // 添加变量和命令替换展开模块
+mod glob; // 添加通配符和大括号展开模块
+mod condition; // 添加test条件判断模块
+mod config; // 添加配置目录模块
+mod terminal; // 添加终端原始模式模块
+mod editor; // 添加行编辑器模块
+mod history; // 添加命令历史模块
+mod completion; // 添加Tab补全模块
+mod prompt; // 添加提示符模块
+mod signal; // 添加Ctrl+C处理模块
+mod which; // 添加PATH查找模块
+mod jobs; // 添加后台任务模块
+mod multicall; // 添加多命令调用模块
+pub mod plugin; // 添加插件模块
+#[cfg(test)]
+mod test_util; // 添加测试辅助模块
@@ -1,9 +1,24 @@
-use std::fs;
+use std::fs::{self, Metadata};
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime; // 移除未使用的Duration导入
+use std::time::{Duration, SystemTime};
+#[cfg(unix)]
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::sync::OnceLock;
+
+use crate::datetime::DateTime;
+use crate::error::{CatError, Result};
+use crate::git::{Status, Statuses};
+use crate::i18n;
+use crate::json::{ToJson, Value};
+use crate::ls_colors::LsColors;
+use crate::streams::{Output, Streams};
+use crate::style;
+use crate::terminal;
 
 // 定义文件类型枚举
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileType {
     Directory,
     File,
@@ -11,7 +26,20 @@ pub enum FileType {
     Other,
 }
 
+impl FileType {
+    /// `--json` 输出中使用的类型名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileType::Directory => "directory",
+            FileType::File => "file",
+            FileType::Symlink => "symlink",
+            FileType::Other => "other",
+        }
+    }
+}
+
 // 定义文件信息结构体
+#[derive(Debug, Clone)]
 pub struct FileInfo {
     pub path: PathBuf,
     pub name: String,
@@ -19,113 +47,898 @@ pub struct FileInfo {
     pub size: u64,
     pub modified: SystemTime,
     pub is_hidden: bool,
+    /// 符号链接指向的路径，不是符号链接时为 `None`
+    pub link_target: Option<PathBuf>,
+    /// Unix的 `st_mode`，包括文件类型和权限位；Windows上为 `None`
+    pub mode: Option<u32>,
+    /// Windows的文件属性（只读、隐藏、系统等）；Unix上为 `None`
+    pub attributes: Option<u32>,
+    /// 硬链接数，Windows上总是1
+    pub nlink: u64,
+    /// inode号，Windows上为 `None`
+    pub inode: Option<u64>,
+    /// 所有者的用户名，找不到名字时是数字ID；Windows上为 `None`
+    pub owner: Option<String>,
+    /// 所属的组名，找不到名字时是数字ID；Windows上为 `None`
+    pub group: Option<String>,
+    /// 当前用户是否不能写入：Unix上没有任何写权限位，Windows上带有只读属性
+    pub readonly: bool,
+}
+
+impl ToJson for FileInfo {
+    fn to_json(&self) -> Value {
+        // 修改时间输出为Unix时间戳（秒）
+        let modified = self.modified.duration_since(SystemTime::UNIX_EPOCH).map(|dur| dur.as_secs()).ok();
+        Value::object([
+            ("name", self.name.as_str().into()),
+            ("path", self.path.display().to_string().into()),
+            ("type", self.file_type.name().into()),
+            ("size", self.size.into()),
+            ("modified", modified.into()),
+            ("hidden", self.is_hidden.into()),
+            ("target", self.link_target.as_ref().map(|target| target.display().to_string()).into()),
+            ("permissions", self.permissions().into()),
+            ("mode", self.mode.map(|mode| mode & 0o7777).into()),
+            ("nlink", self.nlink.into()),
+            ("inode", self.inode.into()),
+            ("owner", self.owner.clone().into()),
+            ("group", self.group.clone().into()),
+            ("readonly", self.readonly.into()),
+        ])
+    }
 }
 
-// 从路径获取文件信息
+impl FileInfo {
+    /// 长格式中的权限列。Unix上是 `drwxr-xr-x` 这样的类型和模式位，
+    /// Windows上是和PowerShell的Mode列一样的 `darhsl` 属性
+    pub fn permissions(&self) -> String {
+        match (self.mode, self.attributes) {
+            (Some(mode), _) => unix_permissions(mode),
+            (None, Some(attributes)) => windows_attributes(attributes),
+            (None, None) => "?".to_string(),
+        }
+    }
+
+    /// 是否是可执行文件：Unix上带有任何执行位，Windows上按扩展名判断
+    pub fn is_executable(&self) -> bool {
+        if self.file_type != FileType::File {
+            return false;
+        }
+        match self.mode {
+            Some(mode) => mode & 0o111 != 0,
+            None => self
+                .path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ["exe", "bat", "cmd", "com", "ps1"].contains(&ext.to_ascii_lowercase().as_str())),
+        }
+    }
+
+    /// 是否是目录，指向目录的符号链接也算
+    pub fn is_dir_like(&self) -> bool {
+        match self.file_type {
+            FileType::Directory => true,
+            FileType::Symlink => self.path.is_dir(),
+            FileType::File | FileType::Other => false,
+        }
+    }
+
+    /// `ls -F` 附加在名称后面的类型标记：目录 `/`、可执行文件 `*`、符号链接 `@`、管道 `|`、套接字 `=`
+    pub fn indicator(&self) -> Option<char> {
+        match self.file_type {
+            FileType::Directory => Some('/'),
+            FileType::Symlink => Some('@'),
+            FileType::File => self.is_executable().then_some('*'),
+            FileType::Other => match self.mode.map(|mode| mode & 0o170000) {
+                Some(0o010000) => Some('|'),
+                Some(0o140000) => Some('='),
+                _ => None,
+            },
+        }
+    }
+}
+
+// 从路径获取文件信息。使用 symlink_metadata，符号链接本身作为一个条目而不是它指向的文件
 pub fn get_file_info(path: &Path) -> Option<FileInfo> {
-    if let Ok(metadata) = fs::metadata(path) {
+    if let Ok(metadata) = fs::symlink_metadata(path) {
         let name = path
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("")
             .to_string();
         
-        let file_type = if metadata.is_dir() {
+        let file_type = if metadata.is_symlink() {
+            FileType::Symlink
+        } else if metadata.is_dir() {
             FileType::Directory
         } else if metadata.is_file() {
             FileType::File
         } else {
             FileType::Other
         };
+        let link_target = if file_type == FileType::Symlink { fs::read_link(path).ok() } else { None };
         
-        let is_hidden = name.starts_with('.');
-        
-        return Some(FileInfo {
+        let mut info = FileInfo {
             path: path.to_path_buf(),
+            is_hidden: name.starts_with('.'),
             name,
             file_type,
             size: metadata.len(),
             modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
-            is_hidden,
-        });
+            link_target,
+            mode: None,
+            attributes: None,
+            nlink: 1,
+            inode: None,
+            owner: None,
+            group: None,
+            readonly: metadata.permissions().readonly(),
+        };
+        read_platform_metadata(&mut info, &metadata);
+        return Some(info);
     }
     None
 }
 
-// 简化的时间格式化函数，使用标准库实现
-pub fn format_time_simple(time: SystemTime) -> String {
-    match time.duration_since(SystemTime::UNIX_EPOCH) {
-        Ok(dur) => {
-            // 获取总秒数
-            let seconds = dur.as_secs();
-            
-            // 简单地返回时间戳字符串
-            format!("{}", seconds)
-        },
-        Err(_) => "Unknown".to_string(),
+/// Unix上的模式位、硬链接数、inode和所有者
+#[cfg(unix)]
+fn read_platform_metadata(info: &mut FileInfo, metadata: &Metadata) {
+    use std::os::unix::fs::MetadataExt;
+
+    info.mode = Some(metadata.mode());
+    info.nlink = metadata.nlink();
+    info.inode = Some(metadata.ino());
+    info.owner = Some(user_name(metadata.uid()));
+    info.group = Some(group_name(metadata.gid()));
+}
+
+/// Windows上的文件属性，带有隐藏属性的文件也算隐藏文件
+#[cfg(windows)]
+fn read_platform_metadata(info: &mut FileInfo, metadata: &Metadata) {
+    use std::os::windows::fs::MetadataExt;
+
+    let attributes = metadata.file_attributes();
+    info.attributes = Some(attributes);
+    info.is_hidden |= attributes & FILE_ATTRIBUTE_HIDDEN != 0;
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_platform_metadata(_info: &mut FileInfo, _metadata: &Metadata) {}
+
+/// `drwxr-xr-x` 格式的类型和权限，包括setuid（`s`）、setgid（`s`）和粘滞位（`t`），
+/// 对应的执行位没有设置时显示为大写
+fn unix_permissions(mode: u32) -> String {
+    let file_type = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o010000 => 'p',
+        0o140000 => 's',
+        _ => '-',
+    };
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    let special = |execute: u32, special: u32, c: char| match (mode & execute != 0, mode & special != 0) {
+        (true, true) => c,
+        (false, true) => c.to_ascii_uppercase(),
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+    [
+        file_type,
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        special(0o100, 0o4000, 's'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        special(0o010, 0o2000, 's'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        special(0o001, 0o1000, 't'),
+    ]
+    .iter()
+    .collect()
+}
+
+const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+const FILE_ATTRIBUTE_ARCHIVE: u32 = 0x20;
+const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+
+/// PowerShell的Mode列：目录、存档、只读、隐藏、系统、重解析点（符号链接等）
+fn windows_attributes(attributes: u32) -> String {
+    [
+        (FILE_ATTRIBUTE_DIRECTORY, 'd'),
+        (FILE_ATTRIBUTE_ARCHIVE, 'a'),
+        (FILE_ATTRIBUTE_READONLY, 'r'),
+        (FILE_ATTRIBUTE_HIDDEN, 'h'),
+        (FILE_ATTRIBUTE_SYSTEM, 's'),
+        (FILE_ATTRIBUTE_REPARSE_POINT, 'l'),
+    ]
+    .iter()
+    .map(|&(mask, c)| if attributes & mask != 0 { c } else { '-' })
+    .collect()
+}
+
+/// 用户ID对应的用户名，`/etc/passwd` 中没有时（例如来自LDAP的用户）返回数字ID
+#[cfg(unix)]
+fn user_name(uid: u32) -> String {
+    static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    let users = USERS.get_or_init(|| read_id_names("/etc/passwd"));
+    users.get(&uid).cloned().unwrap_or_else(|| uid.to_string())
+}
+
+/// 组ID对应的组名，`/etc/group` 中没有时返回数字ID
+#[cfg(unix)]
+fn group_name(gid: u32) -> String {
+    static GROUPS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    let groups = GROUPS.get_or_init(|| read_id_names("/etc/group"));
+    groups.get(&gid).cloned().unwrap_or_else(|| gid.to_string())
+}
+
+/// 读取 `/etc/passwd` 或 `/etc/group` 中ID到名称的映射，两个文件的前三列都是 `名称:密码:ID`。
+/// 同一个ID出现多次时使用第一个名称
+#[cfg(unix)]
+fn read_id_names(path: &str) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for line in fs::read_to_string(path).unwrap_or_default().lines() {
+        let mut fields = line.split(':');
+        if let (Some(name), Some(_), Some(id)) = (fields.next(), fields.next(), fields.next()) {
+            if let Ok(id) = id.parse() {
+                names.entry(id).or_insert_with(|| name.to_string());
+            }
+        }
     }
+    names
 }
 
-// 列出目录内容
-pub fn list_directory(path: &Path, show_hidden: bool, long_format: bool, recursive: bool) {
-    if let Ok(entries) = fs::read_dir(path) {
-        let mut file_infos = Vec::new();
-        
-        // 收集文件信息
-        for entry in entries {
-            if let Ok(entry) = entry {
-                if let Some(info) = get_file_info(&entry.path()) {
-                    if show_hidden || !info.is_hidden {
-                        file_infos.push(info);
-                    }
-                }
+/// 条目的排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortBy {
+    /// 按名称
+    #[default]
+    Name,
+    /// `-t`：按修改时间，最新的在前
+    Time,
+    /// `-S`：按大小，最大的在前
+    Size,
+    /// `-X`：按扩展名，没有扩展名的在前
+    Extension,
+}
+
+/// [`list`] 的选项，默认不包含隐藏文件，按名称排序
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// 包含以 `.` 开头的隐藏文件
+    pub all: bool,
+    /// 排序方式
+    pub sort: SortBy,
+    /// `-r`：反转排序结果
+    pub reverse: bool,
+    /// `--group-directories-first`：目录（包括指向目录的符号链接）排在文件前面，不受 `reverse` 影响
+    pub directories_first: bool,
+}
+
+/// 按选项给条目排序，排序依据相同的条目按名称排列
+pub fn sort(file_infos: &mut [FileInfo], options: &ListOptions) {
+    file_infos.sort_by(|a, b| {
+        let order = match options.sort {
+            SortBy::Name => std::cmp::Ordering::Equal,
+            SortBy::Time => b.modified.cmp(&a.modified),
+            SortBy::Size => b.size.cmp(&a.size),
+            SortBy::Extension => extension(&a.name).cmp(extension(&b.name)),
+        };
+        order.then_with(|| a.name.cmp(&b.name))
+    });
+    if options.reverse {
+        file_infos.reverse();
+    }
+    if options.directories_first {
+        file_infos.sort_by_key(|info| !info.is_dir_like());
+    }
+}
+
+/// `-X` 使用的扩展名，隐藏文件开头的 `.` 不算
+fn extension(name: &str) -> &str {
+    match name.rfind('.') {
+        Some(index) if index > 0 => &name[index + 1..],
+        _ => "",
+    }
+}
+
+/// 读取目录中的文件信息，按选项排序。库使用者可以直接调用它而不必解析 `ls` 的输出
+pub fn list(path: impl AsRef<Path>, options: &ListOptions) -> Result<Vec<FileInfo>> {
+    let path = path.as_ref();
+    let Ok(entries) = fs::read_dir(path) else {
+        return Err(CatError::Failed(tr!("ls.read_dir_failed", path.display())));
+    };
+    let mut file_infos: Vec<FileInfo> = entries
+        .flatten()
+        .filter_map(|entry| get_file_info(&entry.path()))
+        .filter(|info| options.all || !info.is_hidden)
+        .collect();
+    sort(&mut file_infos, options);
+    Ok(file_infos)
+}
+
+/// `ls -d` 或者参数不是目录时，把路径本身作为一个条目，名称显示为给出的路径
+fn path_entry(path: &Path) -> Result<FileInfo> {
+    let mut info = get_file_info(path).ok_or_else(|| CatError::Failed(tr!("ls.access_failed", path.display())))?;
+    info.name = path.display().to_string();
+    Ok(info)
+}
+
+/// `ls -l` 中修改时间的格式
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TimeStyle {
+    /// 默认格式：半年以内的文件显示月、日和时间，更早或者未来的文件显示年份而不是时间
+    #[default]
+    Locale,
+    /// `iso`：近期的文件显示 `10-18 14:02`，其他文件显示 `2023-10-18`
+    Iso,
+    /// `long-iso`：`2023-10-18 14:02`
+    LongIso,
+    /// `full-iso`：`2023-10-18 14:02:05.123456789 +0800`
+    FullIso,
+    /// `+FORMAT`：`strftime` 风格的格式。`+格式1\n格式2` 对较早的文件使用格式1，对近期的文件使用格式2
+    Format { old: String, recent: String },
+}
+
+impl TimeStyle {
+    /// 解析 `--time-style` 的值，和GNU ls一样可以带有 `posix-` 前缀
+    pub fn parse(value: &str) -> Option<TimeStyle> {
+        if let Some(format) = value.strip_prefix('+') {
+            let (old, recent) = format.split_once('\n').unwrap_or((format, format));
+            return Some(TimeStyle::Format { old: old.to_string(), recent: recent.to_string() });
+        }
+        match value.strip_prefix("posix-").unwrap_or(value) {
+            "locale" => Some(TimeStyle::Locale),
+            "iso" => Some(TimeStyle::Iso),
+            "long-iso" => Some(TimeStyle::LongIso),
+            "full-iso" | "full" => Some(TimeStyle::FullIso),
+            _ => None,
+        }
+    }
+
+    /// 按这个格式显示本地时间 `time`，`now` 用来判断是否是近期（半年以内）的文件
+    pub fn format(&self, time: SystemTime, now: SystemTime) -> String {
+        const SIX_MONTHS: Duration = Duration::from_secs(31_556_952 / 2);
+        let recent = time <= now && now.duration_since(time).is_ok_and(|age| age < SIX_MONTHS);
+        let date = DateTime::local(time);
+        match self {
+            TimeStyle::Locale => date.format(i18n::text(if recent { "ls.time_recent" } else { "ls.time_old" })),
+            TimeStyle::Iso => date.format(if recent { "%m-%d %H:%M" } else { "%Y-%m-%d " }),
+            TimeStyle::LongIso => date.format("%Y-%m-%d %H:%M"),
+            TimeStyle::FullIso => date.format("%Y-%m-%d %H:%M:%S.%N %z"),
+            TimeStyle::Format { old, recent: recent_format } => date.format(if recent { recent_format } else { old }),
+        }
+    }
+}
+
+/// `--time-style` 的clap解析函数
+pub fn parse_time_style(value: &str) -> std::result::Result<TimeStyle, String> {
+    TimeStyle::parse(value).ok_or_else(|| tr!("ls.bad_time_style", value))
+}
+
+/// `ls -l` 中文件大小的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeFormat {
+    /// 字节数
+    #[default]
+    Bytes,
+    /// `-h`：以1024为进制，例如 `1.5K`、`12M`
+    Binary,
+    /// `--si`：以1000为进制，例如 `1.6k`、`13M`
+    Si,
+}
+
+impl SizeFormat {
+    /// 和GNU ls一样向上取整，小于10时保留一位小数
+    pub fn format(self, size: u64) -> String {
+        let (base, units) = match self {
+            SizeFormat::Bytes => return size.to_string(),
+            SizeFormat::Binary => (1024.0, ["K", "M", "G", "T", "P", "E"]),
+            SizeFormat::Si => (1000.0, ["k", "M", "G", "T", "P", "E"]),
+        };
+        if (size as f64) < base {
+            return size.to_string();
+        }
+        let mut value = size as f64 / base;
+        let mut unit = 0;
+        loop {
+            let rounded = if value < 10.0 { (value * 10.0).ceil() / 10.0 } else { value.ceil() };
+            if rounded >= base && unit + 1 < units.len() {
+                value /= base;
+                unit += 1;
+                continue;
             }
+            return if rounded < 10.0 {
+                format!("{:.1}{}", rounded, units[unit])
+            } else {
+                format!("{}{}", rounded, units[unit])
+            };
         }
-        
-        // 按名称排序
-        file_infos.sort_by(|a, b| a.name.cmp(&b.name));
-        
-        // 显示文件信息
-        for info in file_infos {
-            // 根据文件类型选择适当的emoji
-            let file_emoji = match info.file_type {
-                FileType::Directory => "📁",
-                FileType::File => "📄",
-                FileType::Symlink => "🔗",
-                FileType::Other => "❓",
+    }
+}
+
+/// [`list_directory`] 的显示选项
+#[derive(Debug, Clone, Default)]
+pub struct DisplayOptions {
+    /// `-l`：长格式
+    pub long: bool,
+    /// `-R`：递归列出子目录
+    pub recursive: bool,
+    /// `-d`：列出目录本身而不是目录的内容
+    pub directory: bool,
+    /// `-i`：在每个条目前面显示inode号
+    pub inode: bool,
+    /// `-F`：在名称后面显示类型标记，见 [`FileInfo::indicator`]
+    pub classify: bool,
+    /// 非长格式时按终端宽度分多列显示。命令行在输出是终端时开启，`-1` 和 `-C` 可以指定
+    pub columns: bool,
+    /// `-h`/`--si`：长格式中文件大小的单位
+    pub sizes: SizeFormat,
+    /// `--time-style`：长格式中修改时间的格式
+    pub time_style: TimeStyle,
+    /// 长格式中在名称前面显示文件的git状态，`--no-git` 关闭
+    pub git: bool,
+}
+
+/// `ls` 命令：和GNU ls一样，先列出不是目录的参数（使用 `-d` 时是全部参数），再依次列出每个目录的内容，
+/// 有多个参数时在每个目录前面显示目录名。`--json` 时每组条目输出一个JSON数组
+pub fn list_paths(paths: &[&Path], list_options: &ListOptions, options: &DisplayOptions, json: bool, io: &mut Streams) -> Result<()> {
+    let mut errors = Vec::new();
+    let (dirs, files): (Vec<&Path>, Vec<&Path>) = paths.iter().partition(|path| !options.directory && path.is_dir());
+    let mut file_infos = Vec::new();
+    for path in files {
+        match path_entry(path) {
+            Ok(info) => file_infos.push(info),
+            Err(err) => errors.push(err),
+        }
+    }
+    sort(&mut file_infos, list_options);
+
+    let mut separate = false;
+    if !file_infos.is_empty() {
+        if json {
+            outln!(io, "{}", Value::Array(file_infos.iter().map(ToJson::to_json).collect()));
+        } else {
+            print_entries(&file_infos, options, &mut Statuses::default(), io);
+        }
+        separate = true;
+    }
+    for dir in dirs {
+        if (paths.len() > 1 || options.recursive) && !json {
+            if separate {
+                outln!(io);
+            }
+            outln!(io, "{}:", dir.display());
+        }
+        separate = true;
+        if let Err(err) = list_directory(dir, list_options, options, json, io) {
+            errors.push(err);
+        }
+    }
+    CatError::collect(errors)
+}
+
+// 列出目录内容，递归时无法读取的子目录不会中断列出其他目录
+pub fn list_directory(path: &Path, list_options: &ListOptions, options: &DisplayOptions, json: bool, io: &mut Streams) -> Result<()> {
+    if json {
+        return print_json(path, list_options, options.recursive, io);
+    }
+    let mut ancestors: Vec<DirId> = dir_id(path).into_iter().collect();
+    list_recursive(path, list_options, options, &mut ancestors, &mut Statuses::default(), io)
+}
+
+/// 列出 `path` 的内容，`-R` 时接着列出每个子目录，子目录前面是从参数开始的完整路径。
+/// 不进入指向目录的符号链接，`ancestors` 中已经出现过的目录（例如绑定挂载造成的循环）也会跳过
+fn list_recursive(
+    path: &Path,
+    list_options: &ListOptions,
+    options: &DisplayOptions,
+    ancestors: &mut Vec<DirId>,
+    git: &mut Statuses,
+    io: &mut Streams,
+) -> Result<()> {
+    let file_infos = list(path, list_options)?;
+    print_entries(&file_infos, options, git, io);
+
+    let mut errors = Vec::new();
+    if options.recursive {
+        for info in file_infos.iter().filter(|info| info.file_type == FileType::Directory) {
+            outln!(io, "\n{}:", info.path.display());
+            let Some(id) = dir_id(&info.path) else {
+                errors.push(CatError::Failed(tr!("ls.read_dir_failed", info.path.display())));
+                continue;
             };
-            
-            if long_format {
-                // 长格式显示
-                let size_str = format!("{:10}", info.size);
-                let time_str = format_time_simple(info.modified);
-                let type_str = match info.file_type {
-                    FileType::Directory => "d",
-                    FileType::File => "-",
-                    FileType::Symlink => "l",
-                    FileType::Other => "?",
-                };
-                
-                // 在Windows上我们无法直接获取UNIX风格的权限
-                // 这里简化处理
-                let permissions = format!("{:10}", "rwxrwxrwx");
-                
-                // 修复格式说明符数量，添加一个额外的{}
-                println!("{}{} {} {} {} {}", file_emoji, type_str, permissions, size_str, time_str, info.name);
-            } else {
-                // 简单格式显示
-                println!("{} {}", file_emoji, info.name);
+            if ancestors.contains(&id) {
+                errors.push(CatError::Failed(tr!("ls.cycle", info.path.display())));
+                continue;
             }
-            
-            // 递归显示子目录
-            if recursive {
-                if let FileType::Directory = info.file_type {
-                    println!("\n{}/:", info.name);
-                    list_directory(&info.path, show_hidden, long_format, recursive);
-                }
+            ancestors.push(id);
+            let result = list_recursive(&info.path, list_options, options, ancestors, git, io);
+            ancestors.pop();
+            if let Err(err) = result {
+                errors.push(err);
             }
         }
+    }
+    CatError::collect(errors)
+}
+
+/// 显示一组条目：长格式每行一个并对齐各列，否则按 `options.columns` 每行一个或者分多列。
+/// 长格式中有条目在git工作树中时，名称前面是两个字符的状态列
+fn print_entries(file_infos: &[FileInfo], options: &DisplayOptions, git: &mut Statuses, io: &mut Streams) {
+    let now = SystemTime::now();
+    let statuses: Vec<Option<Status>> = file_infos
+        .iter()
+        .map(|info| {
+            (options.long && options.git)
+                .then(|| git.status(&info.path, info.file_type == FileType::Directory))
+                .flatten()
+        })
+        .collect();
+    let show_git = statuses.iter().any(Option::is_some);
+
+    // 长格式的各列按这个目录中最宽的值对齐
+    let width = |value: &dyn Fn(&FileInfo) -> String| file_infos.iter().map(|info| value(info).chars().count()).max().unwrap_or(0);
+    let inode_width = width(&|info| inode_string(info));
+    let nlink_width = width(&|info| info.nlink.to_string());
+    let owner_width = width(&|info| info.owner.clone().unwrap_or_default());
+    let group_width = width(&|info| info.group.clone().unwrap_or_default());
+    let size_width = width(&|info| options.sizes.format(info.size));
+
+    let mut items = Vec::new();
+    for (info, status) in file_infos.iter().zip(statuses) {
+        let file_emoji = icon(info, &io.stdout);
+        let name = paint_name(info, &io.stdout);
+        let inode_column = if options.inode { format!("{:>1$} ", inode_string(info), inode_width) } else { String::new() };
+        // 长格式中符号链接后面是它指向的路径，不显示 `@`
+        let indicator = match info.indicator() {
+            Some(indicator) if options.classify && !(options.long && info.file_type == FileType::Symlink) => indicator.to_string(),
+            _ => String::new(),
+        };
+        
+        if options.long {
+            // 长格式显示：权限、硬链接数、所有者、组、大小、修改时间、名称，符号链接后面显示它指向的路径
+            let time_str = options.time_style.format(info.modified, now);
+            let owner = match (&info.owner, &info.group) {
+                (Some(owner), Some(group)) => format!("{:<2$} {:<3$} ", owner, group, owner_width, group_width),
+                _ => String::new(),
+            };
+            let target = match &info.link_target {
+                Some(target) => format!(" -> {}", target.display()),
+                None => String::new(),
+            };
+            let git_column = if show_git { format!("{} ", git_marker(status, &io.stdout)) } else { String::new() };
+            outln!(
+                io,
+                "{}{}{} {:>nlink_width$} {}{:>size_width$} {} {}{}{}{}",
+                file_emoji, inode_column, info.permissions(), info.nlink, owner, options.sizes.format(info.size), time_str,
+                git_column, name, indicator, target
+            );
+        } else {
+            // 简单格式显示
+            items.push(format!("{}{}{}{}", file_emoji, inode_column, name, indicator));
+        }
+    }
+
+    if options.columns {
+        for line in terminal::columns(&items, terminal::width()) {
+            outln!(io, "{}", line);
+        }
     } else {
-        eprintln!("❌ Error: Could not read directory '{}'", path.display());
+        for item in items {
+            outln!(io, "{}", item);
+        }
+    }
+}
+
+/// 根据文件类型选择的emoji，`ls` 和 `tree` 共用
+pub fn icon(info: &FileInfo, output: &Output) -> style::Icon {
+    style::icon(output, match info.file_type {
+        FileType::Directory => "📁",
+        FileType::File => "📄",
+        FileType::Symlink => "🔗",
+        FileType::Other => "❓",
+    })
+}
+
+/// git状态列的两个字符：暂存的变化为绿色，没有暂存的变化、未跟踪的文件和冲突为红色，被忽略的文件为灰色。
+/// 不在工作树中的条目显示为空白，`ls` 和 `tree` 共用
+pub(crate) fn git_marker(status: Option<Status>, output: &Output) -> String {
+    match status {
+        None => "  ".to_string(),
+        Some(Status::IGNORED) => style::paint(output, style::GRAY, "!!").to_string(),
+        Some(status @ (Status::UNTRACKED | Status { index: 'U', .. })) => {
+            style::paint(output, style::RED, format!("{}{}", status.index, status.worktree)).to_string()
+        },
+        Some(status) => {
+            let paint = |code, column: char| style::paint(output, if column == ' ' { "" } else { code }, column);
+            format!("{}{}", paint(style::GREEN, status.index), paint(style::RED, status.worktree))
+        },
     }
-}
\ No newline at end of file
+}
+
+/// 按 `LS_COLORS` 着色的名称，见 [`LsColors`](crate::ls_colors::LsColors)
+pub fn paint_name<'a>(info: &'a FileInfo, output: &Output) -> style::Painted<&'a str> {
+    style::paint(output, LsColors::current().code(info), info.name.as_str())
+}
+
+/// 识别同一个目录的标识，用来在递归时发现符号链接等造成的循环。Unix上是设备号和inode号
+#[cfg(unix)]
+pub type DirId = (u64, u64);
+
+/// 识别同一个目录的标识，没有inode的平台上是规范化的路径
+#[cfg(not(unix))]
+pub type DirId = PathBuf;
+
+/// `path`（跟随符号链接）指向的目录的标识，无法读取时返回 `None`
+#[cfg(unix)]
+pub fn dir_id(path: &Path) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// `path`（跟随符号链接）指向的目录的标识，无法读取时返回 `None`
+#[cfg(not(unix))]
+pub fn dir_id(path: &Path) -> Option<DirId> {
+    fs::canonicalize(path).ok()
+}
+
+/// `-r` 以前表示递归，现在和GNU ls一样表示反转排序。只在标准错误是终端时提示，
+/// 不影响脚本和管道的输出
+pub fn reverse_hint(io: &mut Streams) {
+    if io.stderr.is_terminal() {
+        errln!(io, "{}", i18n::text("ls.reverse_hint"));
+    }
+}
+
+/// inode列的内容，Windows上没有inode号时显示 `-`
+fn inode_string(info: &FileInfo) -> String {
+    info.inode.map_or_else(|| "-".to_string(), |inode| inode.to_string())
+}
+
+/// `ls --json`：输出文件信息数组，递归时目录对象带有子目录内容的 `entries` 字段
+fn print_json(path: &Path, list_options: &ListOptions, recursive: bool, io: &mut Streams) -> Result<()> {
+    let mut errors = Vec::new();
+    let mut ancestors: Vec<DirId> = dir_id(path).into_iter().collect();
+    let value = list_json(path, list_options, recursive, &mut ancestors, &mut errors)?;
+    outln!(io, "{}", value);
+    CatError::collect(errors)
+}
+
+// 递归构造目录的JSON数组，无法读取的子目录记录到errors中。
+// 和 `list_recursive` 一样跳过 `ancestors` 中已经出现过的目录，这样的目录没有 `entries` 字段
+fn list_json(
+    path: &Path,
+    list_options: &ListOptions,
+    recursive: bool,
+    ancestors: &mut Vec<DirId>,
+    errors: &mut Vec<CatError>,
+) -> Result<Value> {
+    let mut items = Vec::new();
+    for info in list(path, list_options)? {
+        let mut value = info.to_json();
+        if recursive && info.file_type == FileType::Directory {
+            match dir_id(&info.path) {
+                None => errors.push(CatError::Failed(tr!("ls.read_dir_failed", info.path.display()))),
+                Some(id) if ancestors.contains(&id) => errors.push(CatError::Failed(tr!("ls.cycle", info.path.display()))),
+                Some(id) => {
+                    ancestors.push(id);
+                    let result = list_json(&info.path, list_options, recursive, ancestors, errors);
+                    ancestors.pop();
+                    match result {
+                        Ok(entries) => {
+                            if let Value::Object(fields) = &mut value {
+                                fields.push(("entries".to_string(), entries));
+                            }
+                        },
+                        Err(err) => errors.push(err),
+                    }
+                },
+            }
+        }
+        items.push(value);
+    }
+    Ok(Value::Array(items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{output, streams, TempDir};
+
+    #[test]
+    fn reverse_hint_is_only_shown_on_terminals() {
+        let mut io = streams(b"");
+        reverse_hint(&mut io);
+        assert_eq!(output(io), (String::new(), String::new()));
+    }
+
+    /// 条目数组中名为 `name` 的对象的 `entries` 字段
+    fn entries<'a>(items: &'a Value, name: &str) -> Option<&'a Value> {
+        let Value::Array(items) = items else { return None };
+        items.iter().find_map(|item| match item {
+            Value::Object(fields) if fields.iter().any(|(key, value)| key == "name" && *value == Value::String(name.to_string())) => {
+                fields.iter().find(|(key, _)| key == "entries").map(|(_, value)| value)
+            },
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn json_recursion_skips_directories_already_visited() {
+        let dir = TempDir::new();
+        dir.file("a/b/c.txt", "");
+        let options = ListOptions::default();
+
+        let mut errors = Vec::new();
+        let mut ancestors: Vec<DirId> = dir_id(dir.path()).into_iter().collect();
+        let value = list_json(dir.path(), &options, true, &mut ancestors, &mut errors).unwrap();
+        assert!(errors.is_empty());
+        let b = entries(entries(&value, "a").unwrap(), "b").unwrap();
+        assert!(matches!(b, Value::Array(items) if items.len() == 1));
+        assert_eq!(ancestors.len(), 1);
+
+        // 子目录 b 已经在祖先中时（例如绑定挂载到自身下面）不再进入
+        let mut ancestors: Vec<DirId> = dir_id(&dir.path().join("a/b")).into_iter().collect();
+        let value = list_json(dir.path(), &options, true, &mut ancestors, &mut errors).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], CatError::Failed(message) if message.contains("a/b") || message.contains("a\\b")));
+        let a = entries(&value, "a").unwrap();
+        assert!(matches!(a, Value::Array(items) if items.len() == 1));
+        assert!(entries(a, "b").is_none());
+    }
+
+    #[test]
+    fn formats_permission_bits() {
+        assert_eq!(unix_permissions(0o040755), "drwxr-xr-x");
+        assert_eq!(unix_permissions(0o100644), "-rw-r--r--");
+        assert_eq!(unix_permissions(0o120777), "lrwxrwxrwx");
+        assert_eq!(unix_permissions(0o104755), "-rwsr-xr-x");
+        assert_eq!(unix_permissions(0o102644), "-rw-r-Sr--");
+        assert_eq!(unix_permissions(0o041777), "drwxrwxrwt");
+        assert_eq!(unix_permissions(0o041776), "drwxrwxrwT");
+        assert_eq!(unix_permissions(0o010600), "prw-------");
+        assert_eq!(windows_attributes(FILE_ATTRIBUTE_DIRECTORY), "d-----");
+        assert_eq!(windows_attributes(FILE_ATTRIBUTE_ARCHIVE | FILE_ATTRIBUTE_READONLY | FILE_ATTRIBUTE_HIDDEN), "-arh--");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_unix_metadata() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = TempDir::new();
+        let script = dir.file("run.sh", "#!/bin/sh\n");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o555)).unwrap();
+        symlink("run.sh", dir.path().join("link")).unwrap();
+
+        let info = get_file_info(&script).unwrap();
+        assert_eq!(info.permissions(), "-r-xr-xr-x");
+        assert!(info.readonly);
+        assert!(info.is_executable());
+        assert_eq!(info.indicator(), Some('*'));
+        assert!(info.owner.is_some() && info.group.is_some());
+        assert_eq!(info.to_json().get("mode").and_then(Value::as_i64), Some(0o555));
+
+        let link = get_file_info(&dir.path().join("link")).unwrap();
+        assert_eq!(link.file_type, FileType::Symlink);
+        assert_eq!(link.link_target, Some(PathBuf::from("run.sh")));
+        assert!(link.permissions().starts_with('l'));
+        assert_eq!(link.indicator(), Some('@'));
+
+        let info = get_file_info(dir.path()).unwrap();
+        assert!(info.permissions().starts_with('d'));
+        assert_eq!(info.indicator(), Some('/'));
+    }
+
+    #[test]
+    fn formats_sizes_like_gnu_ls() {
+        let binary = |size| SizeFormat::Binary.format(size);
+        assert_eq!(SizeFormat::Bytes.format(123_456), "123456");
+        assert_eq!(binary(1023), "1023");
+        assert_eq!(binary(1024), "1.0K");
+        assert_eq!(binary(1025), "1.1K");
+        assert_eq!(binary(10 * 1024), "10K");
+        assert_eq!(binary(10 * 1024 + 1), "11K");
+        assert_eq!(binary(1024 * 1024 - 1), "1.0M");
+        assert_eq!(binary(5 * 1024 * 1024 * 1024), "5.0G");
+        assert_eq!(binary(u64::MAX), "16E");
+        assert_eq!(SizeFormat::Si.format(999), "999");
+        assert_eq!(SizeFormat::Si.format(1500), "1.5k");
+        assert_eq!(SizeFormat::Si.format(999_999), "1.0M");
+    }
+
+    #[test]
+    fn parses_time_styles() {
+        assert_eq!(TimeStyle::parse("posix-long-iso"), Some(TimeStyle::LongIso));
+        assert_eq!(TimeStyle::parse("full"), Some(TimeStyle::FullIso));
+        assert_eq!(
+            TimeStyle::parse("+%Y\n%H:%M"),
+            Some(TimeStyle::Format { old: "%Y".to_string(), recent: "%H:%M".to_string() })
+        );
+        assert_eq!(TimeStyle::parse("+%F"), Some(TimeStyle::Format { old: "%F".to_string(), recent: "%F".to_string() }));
+        assert!(parse_time_style("week").is_err());
+    }
+
+    fn info(name: &str, file_type: FileType, size: u64, modified: u64) -> FileInfo {
+        FileInfo {
+            path: PathBuf::from(name),
+            name: name.to_string(),
+            file_type,
+            size,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(modified),
+            is_hidden: name.starts_with('.'),
+            link_target: None,
+            mode: None,
+            attributes: None,
+            nlink: 1,
+            inode: None,
+            owner: None,
+            group: None,
+            readonly: false,
+        }
+    }
+
+    fn sorted(options: ListOptions) -> Vec<String> {
+        let mut infos = vec![
+            info("b.txt", FileType::File, 10, 3),
+            info("a.rs", FileType::File, 30, 1),
+            info("src", FileType::Directory, 4096, 2),
+            info(".hidden", FileType::File, 30, 4),
+            info("c.rs", FileType::File, 20, 3),
+        ];
+        sort(&mut infos, &options);
+        infos.into_iter().map(|info| info.name).collect()
+    }
+
+    #[test]
+    fn sorts_entries() {
+        assert_eq!(sorted(ListOptions::default()), [".hidden", "a.rs", "b.txt", "c.rs", "src"]);
+        assert_eq!(sorted(ListOptions { sort: SortBy::Time, ..Default::default() }), [".hidden", "b.txt", "c.rs", "src", "a.rs"]);
+        assert_eq!(sorted(ListOptions { sort: SortBy::Size, ..Default::default() }), ["src", ".hidden", "a.rs", "c.rs", "b.txt"]);
+        assert_eq!(sorted(ListOptions { sort: SortBy::Extension, ..Default::default() }), [".hidden", "src", "a.rs", "c.rs", "b.txt"]);
+        assert_eq!(
+            sorted(ListOptions { sort: SortBy::Size, reverse: true, ..Default::default() }),
+            ["b.txt", "c.rs", "a.rs", ".hidden", "src"]
+        );
+        // 目录排在前面不受反转影响
+        assert_eq!(
+            sorted(ListOptions { reverse: true, directories_first: true, ..Default::default() }),
+            ["src", "c.rs", "b.txt", "a.rs", ".hidden"]
+        );
+    }
+
+    #[test]
+    fn lists_files_before_directories() {
+        let dir = TempDir::new();
+        dir.file("d/x", "");
+        dir.file("d/.y", "");
+        let file = dir.file("f.txt", "");
+        let sub = dir.path().join("d");
+        let missing = dir.path().join("missing");
+        let paths = [sub.as_path(), missing.as_path(), file.as_path()];
+
+        let mut io = streams(b"");
+        let options = DisplayOptions { classify: true, ..Default::default() };
+        let error = list_paths(&paths, &ListOptions::default(), &options, false, &mut io).unwrap_err();
+        assert_eq!(error.exit_code(), 1);
+        assert!(error.to_string().contains("missing"));
+        assert_eq!(output(io).0, format!("{}\n\n{}:\nx\n", file.display(), sub.display()));
+
+        let mut io = streams(b"");
+        let options = DisplayOptions { directory: true, classify: true, ..Default::default() };
+        list_paths(&[sub.as_path()], &ListOptions { all: true, ..Default::default() }, &options, false, &mut io).unwrap();
+        assert_eq!(output(io).0, format!("{}/\n", sub.display()));
+
+        let names: Vec<String> = list(&sub, &ListOptions { all: true, ..Default::default() }).unwrap().into_iter().map(|info| info.name).collect();
+        assert_eq!(names, [".y", "x"]);
+    }
+}
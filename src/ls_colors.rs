@@ -0,0 +1,281 @@
+//! `ls` 和 `tree` 的文件名颜色。规则来自 `LS_COLORS` 环境变量（`dircolors` 输出的格式），
+//! 没有设置时读取 `~/.dircolors` 或 `~/.dir_colors` 数据库文件，都没有时使用内置的默认配色
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::config;
+use crate::ls::{self, FileInfo, FileType};
+use crate::server;
+use crate::zip;
+
+/// 内置配色，和GNU `dircolors` 的默认配置一致
+#[cfg(not(windows))]
+const BUILTIN: &str = "di=01;34:ln=01;36:or=40;31;01:pi=40;33:so=01;35:bd=40;33;01:cd=40;33;01:\
+                       su=37;41:sg=30;43:tw=30;42:ow=34;42:st=37;44:ex=01;32";
+/// 内置配色中压缩包和图片的颜色
+#[cfg(not(windows))]
+const BUILTIN_ARCHIVE: &str = "01;31";
+#[cfg(not(windows))]
+const BUILTIN_IMAGE: &str = "01;35";
+
+/// Windows控制台的内置配色：使用高亮色而不是粗体和背景色，在默认的控制台配色中也容易分辨
+#[cfg(windows)]
+const BUILTIN: &str = "di=94:ln=96:or=91:pi=33:so=95:bd=93:cd=93:ex=92";
+#[cfg(windows)]
+const BUILTIN_ARCHIVE: &str = "91";
+#[cfg(windows)]
+const BUILTIN_IMAGE: &str = "95";
+
+/// `dircolors` 数据库中的关键字和 `LS_COLORS` 中的两个字母的对应关系
+const DATABASE_KEYWORDS: [(&str, &str); 24] = [
+    ("NORMAL", "no"), ("NORM", "no"), ("FILE", "fi"), ("RESET", "rs"), ("DIR", "di"),
+    ("LINK", "ln"), ("LNK", "ln"), ("SYMLINK", "ln"), ("MULTIHARDLINK", "mh"),
+    ("FIFO", "pi"), ("PIPE", "pi"), ("SOCK", "so"), ("DOOR", "do"),
+    ("BLK", "bd"), ("BLOCK", "bd"), ("CHR", "cd"), ("CHAR", "cd"),
+    ("ORPHAN", "or"), ("MISSING", "mi"), ("SETUID", "su"), ("SETGID", "sg"),
+    ("STICKY_OTHER_WRITABLE", "tw"), ("OTHER_WRITABLE", "ow"), ("EXEC", "ex"),
+];
+
+/// 文件名的配色规则
+#[derive(Debug, Default)]
+pub struct LsColors {
+    /// 按类型的颜色，键是 `di`、`ln`、`ex` 这样的两个字母
+    types: HashMap<String, String>,
+    /// 按名称后缀的颜色，例如 `*.tar=01;31` 中的 `.tar`。后面的规则优先
+    suffixes: Vec<(String, String)>,
+    /// 内置配色还按 [`zip`] 能解压的压缩包和 [`server`] 认识的图片类型着色
+    builtin: bool,
+}
+
+impl LsColors {
+    /// 解析 `LS_COLORS` 的值，例如 `di=01;34:ln=01;36:*.tar=01;31`
+    pub fn parse(value: &str) -> LsColors {
+        let mut colors = LsColors::default();
+        for entry in value.split(':') {
+            if let Some((key, code)) = entry.split_once('=') {
+                colors.insert(key, code);
+            }
+        }
+        colors
+    }
+
+    /// 解析 `dircolors --print-database` 格式的数据库，每行是一个关键字和颜色，例如 `DIR 01;34` 或 `.tar 01;31`。
+    /// `TERM`、`COLOR` 等和终端相关的行被忽略
+    pub fn parse_database(text: &str) -> LsColors {
+        let mut colors = LsColors::default();
+        for line in text.lines() {
+            let mut words = line.split('#').next().unwrap_or_default().split_whitespace();
+            let (Some(keyword), Some(code)) = (words.next(), words.next()) else {
+                continue;
+            };
+            if keyword.starts_with('.') || keyword.starts_with('*') {
+                colors.insert(keyword, code);
+            } else if let Some((_, key)) = DATABASE_KEYWORDS.iter().find(|(name, _)| name.eq_ignore_ascii_case(keyword)) {
+                colors.insert(key, code);
+            }
+        }
+        colors
+    }
+
+    fn builtin() -> LsColors {
+        LsColors { builtin: true, ..LsColors::parse(BUILTIN) }
+    }
+
+    fn insert(&mut self, key: &str, code: &str) {
+        match key.strip_prefix('*') {
+            Some(suffix) => self.suffixes.push((suffix.to_string(), code.to_string())),
+            None if key.starts_with('.') => self.suffixes.push((key.to_string(), code.to_string())),
+            None => {
+                self.types.insert(key.to_string(), code.to_string());
+            },
+        }
+    }
+
+    /// 当前的配色。REPL中可以用 `export LS_COLORS=...` 修改，所以按 `LS_COLORS` 的值缓存
+    pub fn current() -> Arc<LsColors> {
+        static CACHE: Mutex<Option<(Option<OsString>, Arc<LsColors>)>> = Mutex::new(None);
+        let value = config::var_os("LS_COLORS").filter(|value| !value.is_empty());
+        let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+        match &*cache {
+            Some((key, colors)) if *key == value => colors.clone(),
+            _ => {
+                let colors = Arc::new(match &value {
+                    Some(value) => LsColors::parse(&value.to_string_lossy()),
+                    None => database_files()
+                        .into_iter()
+                        .find_map(|path| fs::read_to_string(path).ok())
+                        .map(|text| LsColors::parse_database(&text))
+                        .unwrap_or_else(LsColors::builtin),
+                });
+                *cache = Some((value, colors.clone()));
+                colors
+            },
+        }
+    }
+
+    /// 设置了颜色的类型，`0` 和 `00` 表示不着色
+    fn get(&self, key: &str) -> Option<&str> {
+        self.types.get(key).map(String::as_str).filter(|code| !matches!(*code, "" | "0" | "00"))
+    }
+
+    /// `info` 的ANSI颜色代码，不需要颜色时返回空字符串。
+    /// 和GNU ls一样，setuid、可执行等特殊权限优先于扩展名，`ln=target` 时符号链接使用它指向的文件的颜色
+    pub fn code(&self, info: &FileInfo) -> &str {
+        let mode = info.mode.unwrap_or(0);
+        let code = match info.file_type {
+            FileType::Symlink if !info.path.exists() => {
+                self.get("or").or_else(|| self.get("ln").filter(|code| *code != "target"))
+            },
+            FileType::Symlink if self.get("ln") == Some("target") => {
+                let target = fs::canonicalize(&info.path).ok().and_then(|path| ls::get_file_info(&path));
+                return target.map_or("", |target| self.code(&target));
+            },
+            FileType::Symlink => self.get("ln"),
+            FileType::Directory => {
+                let sticky = mode & 0o1000 != 0;
+                let other_writable = mode & 0o002 != 0;
+                (sticky && other_writable)
+                    .then(|| self.get("tw"))
+                    .flatten()
+                    .or_else(|| other_writable.then(|| self.get("ow")).flatten())
+                    .or_else(|| sticky.then(|| self.get("st")).flatten())
+                    .or_else(|| self.get("di"))
+            },
+            FileType::File => (mode & 0o4000 != 0)
+                .then(|| self.get("su"))
+                .flatten()
+                .or_else(|| (mode & 0o2000 != 0).then(|| self.get("sg")).flatten())
+                .or_else(|| info.is_executable().then(|| self.get("ex")).flatten())
+                .or_else(|| (info.nlink > 1).then(|| self.get("mh")).flatten())
+                .or_else(|| self.suffix_code(&info.name))
+                .or_else(|| self.category_code(info))
+                .or_else(|| self.get("fi")),
+            FileType::Other => match mode & 0o170000 {
+                0o010000 => self.get("pi"),
+                0o140000 => self.get("so"),
+                0o060000 => self.get("bd"),
+                0o020000 => self.get("cd"),
+                _ => None,
+            },
+        };
+        code.unwrap_or("")
+    }
+
+    /// 按名称后缀查找颜色，先区分大小写，找不到时再不区分大小写
+    fn suffix_code(&self, name: &str) -> Option<&str> {
+        let lower = name.to_lowercase();
+        let find = |matches: &dyn Fn(&str) -> bool| {
+            self.suffixes.iter().rev().find(|(suffix, _)| matches(suffix)).map(|(_, code)| code.as_str())
+        };
+        find(&|suffix| name.ends_with(suffix))
+            .or_else(|| find(&|suffix| lower.ends_with(&suffix.to_lowercase())))
+            .filter(|code| !matches!(*code, "" | "0" | "00"))
+    }
+
+    /// 内置配色中压缩包和图片的颜色
+    fn category_code(&self, info: &FileInfo) -> Option<&str> {
+        if !self.builtin {
+            None
+        } else if zip::is_archive(&info.path) {
+            Some(BUILTIN_ARCHIVE)
+        } else if server::get_content_type(&info.path).starts_with("image/") {
+            Some(BUILTIN_IMAGE)
+        } else {
+            None
+        }
+    }
+}
+
+/// 没有设置 `LS_COLORS` 时依次尝试的 `dircolors` 数据库文件
+fn database_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = config::home_dir()
+        .map(|home| vec![home.join(".dircolors"), home.join(".dir_colors")])
+        .unwrap_or_default();
+    if cfg!(unix) {
+        files.push(PathBuf::from("/etc/DIR_COLORS"));
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// `name` 文件的信息，`mode` 为 `Some` 时替换其中的权限位
+    fn file(dir: &TempDir, name: &str, mode: Option<u32>) -> FileInfo {
+        let mut info = ls::get_file_info(&dir.file(name, "")).unwrap();
+        if let Some(mode) = mode {
+            info.mode = Some(0o100000 | mode);
+        }
+        info
+    }
+
+    #[test]
+    fn colors_files_by_suffix() {
+        let dir = TempDir::new();
+        let colors = LsColors::parse("fi=0:*.tar=01;31:*.tar=31:*.md=33:*README.md=01;33:*.gz=00");
+        assert_eq!(colors.code(&file(&dir, "a.tar", Some(0o644))), "31");
+        assert_eq!(colors.code(&file(&dir, "B.TAR", Some(0o644))), "31");
+        assert_eq!(colors.code(&file(&dir, "notes.md", Some(0o644))), "33");
+        assert_eq!(colors.code(&file(&dir, "README.md", Some(0o644))), "01;33");
+        assert_eq!(colors.code(&file(&dir, "a.gz", Some(0o644))), "");
+        assert_eq!(colors.code(&file(&dir, "plain", Some(0o644))), "");
+    }
+
+    #[test]
+    fn special_permissions_come_first() {
+        let dir = TempDir::new();
+        let colors = LsColors::parse("ex=32:su=37;41:sg=30;43:*.sh=33");
+        assert_eq!(colors.code(&file(&dir, "run.sh", Some(0o755))), "32");
+        assert_eq!(colors.code(&file(&dir, "run.sh", Some(0o4755))), "37;41");
+        assert_eq!(colors.code(&file(&dir, "run.sh", Some(0o2755))), "30;43");
+        assert_eq!(colors.code(&file(&dir, "run.sh", Some(0o644))), "33");
+
+        let mut info = ls::get_file_info(dir.path()).unwrap();
+        let colors = LsColors::parse("di=34:tw=30;42:ow=34;42:st=37;44");
+        for (mode, code) in [(0o755, "34"), (0o1777, "30;42"), (0o777, "34;42"), (0o1755, "37;44")] {
+            info.mode = Some(0o040000 | mode);
+            assert_eq!(colors.code(&info), code, "{:o}", mode);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn colors_symlinks() {
+        let dir = TempDir::new();
+        dir.file("archive.zip", "");
+        std::os::unix::fs::symlink("archive.zip", dir.path().join("good")).unwrap();
+        std::os::unix::fs::symlink("missing", dir.path().join("broken")).unwrap();
+        let good = ls::get_file_info(&dir.path().join("good")).unwrap();
+        let broken = ls::get_file_info(&dir.path().join("broken")).unwrap();
+
+        let colors = LsColors::parse("ln=36:or=31:*.zip=01;31");
+        assert_eq!((colors.code(&good), colors.code(&broken)), ("36", "31"));
+        let colors = LsColors::parse("ln=target:*.zip=01;31");
+        assert_eq!((colors.code(&good), colors.code(&broken)), ("01;31", ""));
+    }
+
+    #[test]
+    fn reads_dircolors_databases() {
+        let dir = TempDir::new();
+        let colors = LsColors::parse_database(
+            "# 注释\nTERM xterm-256color\nCOLOR tty\nDIR 01;34 # 目录\nexec 01;32\n.TAR 01;31\n*~ 00;90\nUNKNOWN 1\n",
+        );
+        assert_eq!(colors.code(&ls::get_file_info(dir.path()).unwrap()), "01;34");
+        assert_eq!(colors.code(&file(&dir, "x.tar", Some(0o644))), "01;31");
+        assert_eq!(colors.code(&file(&dir, "x~", Some(0o644))), "00;90");
+        assert_eq!(colors.code(&file(&dir, "x", Some(0o755))), "01;32");
+        assert_eq!(colors.types.len(), 2);
+
+        // 内置配色还按类型给压缩包和图片着色
+        let builtin = LsColors::builtin();
+        assert_eq!(builtin.code(&file(&dir, "a.JAR", Some(0o644))), BUILTIN_ARCHIVE);
+        assert_eq!(builtin.code(&file(&dir, "a.png", Some(0o644))), BUILTIN_IMAGE);
+        assert_eq!(builtin.code(&file(&dir, "a.txt", Some(0o644))), "");
+    }
+}
@@ -0,0 +1,335 @@
+/// 消息目录：键、中文（zh-CN）、英文（en）。文本中的 `{}` 由 `tr!` 依次替换为参数，
+/// `{0}`、`{1}` 按位置替换，两种语言的语序不同时使用
+pub const MESSAGES: &[(&str, &str, &str)] = &[
+    // pwd
+    ("pwd.current_dir_failed", "无法获取当前工作目录", "Could not get the current working directory"),
+    ("pwd.not_utf8", "无法将当前目录转换为字符串", "Could not convert the current directory to a string"),
+    ("pwd.location", "位于: {}", "Location: {}"),
+    ("pwd.unknown", "未知目录", "unknown directory"),
+
+    // cd
+    ("cd.no_home", "错误: 无法确定用户主目录", "Error: Could not determine the home directory"),
+    ("cd.current_dir_failed", "错误: 无法获取当前目录", "Error: Could not get the current directory"),
+    ("cd.at_root", "错误: 已经是根目录，无法再上一级", "Error: Already at the root directory"),
+    ("cd.canonicalize_failed", "错误: 无法规范化路径: {}", "Error: Could not canonicalize path: {}"),
+    ("cd.not_found", "错误: 找不到文件或目录: {}", "Error: No such file or directory: {}"),
+    ("cd.not_dir", "错误: 不是目录: {}", "Error: Not a directory: {}"),
+    ("cd.failed", "错误: 更改目录失败: {}", "Error: Failed to change directory: {}"),
+    ("cd.changed", "已切换到: {}", "Changed to: {}"),
+
+    // mkdir
+    ("mkdir.dir_exists", "错误: 目录 '{}' 已存在", "Error: Directory '{}' already exists"),
+    ("mkdir.file_exists", "错误: 文件 '{}' 已存在且不是目录", "Error: File '{}' exists and is not a directory"),
+    ("mkdir.created", "已创建目录: {}", "Created directory: {}"),
+    ("mkdir.failed", "错误: 无法创建目录 '{}': {}", "Error: Failed to create directory '{}': {}"),
+
+    // rm
+    ("rm.not_found", "错误: '{}' 不存在", "Error: '{}' does not exist"),
+    ("rm.file_failed", "删除文件 '{}' 时出错: {}", "Error removing file '{}': {}"),
+    ("rm.removed_file", "已删除文件 '{}'", "Removed file '{}'"),
+    ("rm.dir_failed", "删除目录 '{}' 时出错: {}", "Error removing directory '{}': {}"),
+    ("rm.removed_tree", "已删除目录 '{}' 及其全部内容", "Removed directory '{}' and all its contents"),
+    ("rm.dir_not_empty", "删除目录 '{}' 时出错: {}\n使用 -r 或 --recursive 删除非空目录", "Error removing directory '{}': {}\nUse -r or --recursive to remove non-empty directories"),
+    ("rm.removed_dir", "已删除目录 '{}'", "Removed directory '{}'"),
+
+    // ls
+    ("ls.read_dir_failed", "错误: 无法读取目录 '{}'", "Error: Could not read directory '{}'"),
+    ("ls.access_failed", "错误: 无法访问 '{}'", "Error: Cannot access '{}'"),
+    ("ls.cycle", "错误: 目录 '{}' 造成循环，已跳过", "Error: Directory '{}' causes a loop, skipped"),
+    ("ls.reverse_hint", "提示: ls -r 现在和GNU ls一样表示反转排序，递归列出子目录请使用 -R", "Note: ls -r now reverses the sort order as in GNU ls; use -R to list subdirectories recursively"),
+    ("ls.bad_time_style", "无效的时间格式 {}，可用的格式有locale、iso、long-iso、full-iso和+FORMAT", "Invalid time style {}, expected locale, iso, long-iso, full-iso or +FORMAT"),
+    ("ls.time_recent", "%m月%d日 %H:%M", "%b %e %H:%M"),
+    ("ls.time_old", "%Y年%m月%d日", "%b %e  %Y"),
+
+    // tree
+    ("tree.recursive", "循环链接，未展开", "recursive, not followed"),
+    ("tree.open_failed", "无法打开目录", "error opening dir"),
+    ("tree.summary", "{} 个目录，{} 个文件", "{} directories, {} files"),
+    ("tree.summary_dirs", "{} 个目录", "{} directories"),
+
+    // cat
+    ("cat.not_found", "错误: 找不到文件或目录: {}", "Error: No such file or directory: {}"),
+    ("cat.not_file", "错误: 不是文件: {}", "Error: Not a file: {}"),
+    ("cat.read_failed", "错误: 无法读取文件 '{}': {}", "Error: Failed to read file '{}': {}"),
+    ("cat.binary", "二进制文件 '{}'（{} 字节）", "Binary file '{}' ({} bytes)"),
+    ("cat.stdin_failed", "错误: 无法读取标准输入: {}", "Error: Failed to read from stdin: {}"),
+
+    // vim
+    ("vim.title", "CatShell Vim (简化版)", "CatShell Vim (lite)"),
+    ("vim.hint", "提示: 这是一个简化版vim，仅支持基本功能", "Note: this is a simplified vim with basic features only"),
+    ("vim.save_hint", "保存并退出: 使用编辑器自带的保存功能", "Save and quit: use the editor's own save command"),
+    ("vim.quit_hint", "退出: 关闭编辑器窗口", "Quit: close the editor window"),
+    ("vim.starting", "正在启动编辑器...", "Starting editor..."),
+    ("vim.exit_status", "编辑器异常退出: {}", "Editor exited abnormally: {}"),
+    ("vim.run_failed", "编辑器执行失败: {}", "Failed to run editor: {}"),
+    ("vim.start_failed", "无法启动编辑器: {}", "Could not start editor: {}"),
+    ("vim.try_cat", "尝试使用 `cat` 命令查看文件内容", "Try `cat` to view the file contents"),
+    ("vim.basic_editor", "使用基础编辑器编辑文件: {}", "Editing with the basic editor: {}"),
+    ("vim.basic_hint", "输入内容，按Ctrl+Z然后Enter保存退出", "Type the contents, then press Ctrl+Z and Enter to save and quit"),
+    ("vim.edit_mode", "编辑模式 (Ctrl+Z+Enter 保存退出)", "Edit mode (Ctrl+Z+Enter to save and quit)"),
+    ("vim.create_failed", "无法创建文件: {}", "Could not create file: {}"),
+    ("vim.write_failed", "无法写入文件: {}", "Could not write file: {}"),
+    ("vim.saved", "文件已保存: {}", "File saved: {}"),
+
+    // open
+    ("open.current_dir_failed", "无法获取当前工作目录: {}", "Could not get the current working directory: {}"),
+    ("open.opening", "正在打开目录: {}", "Opening directory: {}"),
+    ("open.opened", "目录已成功打开", "Directory opened"),
+
+    // browser
+    ("browser.opening", "正在打开浏览器访问: {}", "Opening browser at: {}"),
+    ("browser.opened", "浏览器已成功打开", "Browser opened"),
+
+    // cmatrix
+    ("cmatrix.no_ansi", "错误: 当前终端不支持ANSI转义序列，无法运行cmatrix", "Error: this terminal does not support ANSI escape sequences, cannot run cmatrix"),
+    ("cmatrix.banner", "CatShell Matrix 动画\n按 Ctrl+C 退出...", "CatShell Matrix animation\nPress Ctrl+C to quit..."),
+
+    // ping
+    ("ping.error", "错误: {}", "Error: {}"),
+    ("ping.reachable", "{} 可访问", "{} is reachable"),
+    ("ping.unreachable", "{} 不可访问", "{} is unreachable"),
+
+    // zip
+    ("zip.create_failed", "无法创建压缩文件 {}: {}", "Could not create archive {}: {}"),
+    ("zip.missing", "{} 不存在，已跳过", "{} does not exist, skipped"),
+    ("zip.add_file_failed", "添加文件 {} 失败: {}", "Failed to add file {}: {}"),
+    ("zip.add_dir_failed", "添加目录 {} 失败: {}", "Failed to add directory {}: {}"),
+    ("zip.finish_failed", "完成压缩文件失败: {}", "Failed to finish archive: {}"),
+    ("zip.open_failed", "无法打开压缩文件 {}: {}", "Could not open archive {}: {}"),
+    ("zip.parse_failed", "无法解析压缩文件 {}: {}", "Could not read archive {}: {}"),
+    ("zip.output_dir_failed", "无法创建输出目录 {}: {}", "Could not create output directory {}: {}"),
+    ("zip.entry_failed", "读取压缩文件条目失败: {}", "Failed to read archive entry: {}"),
+    ("zip.unsafe_path", "不安全的条目路径 {}，已跳过", "Unsafe entry path {}, skipped"),
+    ("zip.mkdir_failed", "创建目录 {} 失败: {}", "Failed to create directory {}: {}"),
+    ("zip.parent_failed", "创建父目录 {} 失败: {}", "Failed to create parent directory {}: {}"),
+    ("zip.file_failed", "创建文件 {} 失败: {}", "Failed to create file {}: {}"),
+    ("zip.write_failed", "写入文件 {} 失败: {}", "Failed to write file {}: {}"),
+    ("zip.created", "成功创建压缩文件: {}", "Created archive: {}"),
+    ("zip.extracted", "成功解压缩到: {}", "Extracted to: {}"),
+    ("zip.both_modes", "错误: 不能同时使用 --create 和 --extract 选项", "Error: --create and --extract cannot be used together"),
+    ("zip.no_archive", "错误: 解压缩模式需要指定zip文件", "Error: extract mode needs a zip file"),
+    ("zip.no_mode", "错误: 必须指定 --create 或 --extract 选项", "Error: either --create or --extract is required"),
+
+    // server
+    ("server.bad_root", "错误: 目录 '{}' 不存在或不是一个有效目录", "Error: Directory '{}' does not exist or is not a directory"),
+    ("server.bind_failed", "无法绑定到地址 {}: {}", "Could not bind to {}: {}"),
+    ("server.nonblocking_failed", "无法设置非阻塞模式: {}", "Could not enable non-blocking mode: {}"),
+    ("server.accept_failed", "接受连接失败: {}", "Failed to accept connection: {}"),
+    ("server.starting", "正在启动HTTP服务器，服务目录: {}", "Starting HTTP server, serving directory: {}"),
+    ("server.url", "服务器将在 http://localhost:{}/ 上运行", "Server running at http://localhost:{}/"),
+    ("server.stop_hint", "按 Ctrl+C 停止服务器", "Press Ctrl+C to stop the server"),
+    ("server.stopped", "服务器已停止", "Server stopped"),
+    ("server.read_failed", "读取请求失败: {}", "Failed to read request: {}"),
+    ("server.not_found", "请求的资源不存在", "The requested resource was not found"),
+    ("server.internal_error", "服务器内部错误", "Internal server error"),
+
+    // curl
+    ("curl.write_failed", "无法写入文件 {}: {}", "Could not write file {}: {}"),
+    ("curl.saved", "响应已保存到 {}", "Response saved to {}"),
+    ("curl.request_failed", "请求失败: {}", "Request failed: {}"),
+    ("curl.failed", "请求失败", "Request failed"),
+    ("curl.bad_output", "无法解析PowerShell的输出", "Could not parse PowerShell output"),
+    ("curl.bad_header", "格式错误: 请使用 'Key: Value' 格式", "Invalid format: use 'Key: Value'"),
+
+    // error
+    ("error.command_not_found", "未找到命令: {}", "Command not found: {}"),
+    ("error.spawn_failed", "无法启动 {}: {}", "Could not start {}: {}"),
+
+    // install
+    ("install.exe_failed", "无法确定catshell的位置: {}", "Could not locate the catshell executable: {}"),
+    ("install.mkdir_failed", "无法创建目录 {}: {}", "Could not create directory {}: {}"),
+    ("install.exists", "{} 已存在，已跳过", "{} already exists, skipped"),
+    ("install.link_failed", "无法创建 {}: {}", "Could not create {}: {}"),
+    ("install.done", "把 {} 加入 PATH 后就可以直接使用 {}", "Add {} to PATH to use {} directly"),
+
+    // plugin
+    ("plugin.not_found", "未找到命令: {0}（也没有找到插件 {1}{0}）", "Command not found: {0} (no plugin {1}{0} either)"),
+    ("plugin.none", "没有找到插件，把名为 {}<命令> 的可执行文件放到 PATH 中就可以添加命令", "No plugins found. Put an executable named {}<command> on PATH to add a command"),
+    ("plugin.status", "插件 {} 的退出状态为 {}", "Plugin {} exited with status {}"),
+
+    // jobs
+    ("jobs.running", "运行中", "Running"),
+    ("jobs.stopped", "已停止", "Stopped"),
+    ("jobs.done", "已完成", "Done"),
+    ("jobs.exit", "退出 {}", "Exit {}"),
+    ("jobs.builtin_stop", "%{}: 内置命令不能暂停", "%{}: builtin commands cannot be stopped"),
+    ("jobs.none", "没有任务", "No jobs"),
+    ("jobs.no_such_job", "{}: 没有这个任务", "{}: no such job"),
+    ("jobs.not_responding", "任务 %{} 没有响应，仍在后台运行", "Job %{} is not responding and is still running in the background"),
+    ("jobs.already_running", "%{}: 任务已经在后台运行", "%{}: job is already running in the background"),
+    ("jobs.already_done", "%{}: 任务已经结束", "%{}: job has already finished"),
+    ("jobs.windows_stop", "Windows上不支持暂停任务", "Stopping jobs is not supported on Windows"),
+    ("jobs.windows_kill", "Windows上只能结束catshell启动的任务，请使用 %任务号", "On Windows only jobs started by catshell can be killed, use %job"),
+
+    // glob
+    ("glob.no_match", "没有匹配的文件: {}", "No matches found: {}"),
+
+    // test
+    ("test.too_many_args", "参数过多: {}", "Too many arguments: {}"),
+    ("test.unknown_unary", "未知的一元运算符: {}", "Unknown unary operator: {}"),
+    ("test.unknown_binary", "未知的二元运算符: {}", "Unknown binary operator: {}"),
+    ("test.integer_expected", "需要整数表达式: {}", "Integer expression expected: {}"),
+
+    // parser
+    ("parser.incomplete", "语法错误: 输入意外结束", "Syntax error: unexpected end of input"),
+    ("parser.syntax", "语法错误: {}", "Syntax error: {}"),
+    ("parser.expected", "期望 '{}'，实际是 '{}'", "Expected '{}', found '{}'"),
+    ("parser.incomplete_alias", "别名 {} 的内容不完整", "Alias {} is incomplete"),
+    ("parser.redirect_target", "重定向缺少目标文件", "Missing redirection target"),
+    ("parser.unsupported_duplicate", "不支持的重定向，文件描述符只能用 2>&1 或 >&2 复制", "Unsupported redirection, only 2>&1 and >&2 can duplicate file descriptors"),
+    ("parser.pipe_command", "'|' 前缺少命令", "Missing command before '|'"),
+    ("parser.missing_command", "缺少命令", "Missing command"),
+    ("parser.unexpected", "意外的 '{}'", "Unexpected '{}'"),
+    ("parser.newline", "换行", "newline"),
+
+    // history
+    ("history.not_found", "{}: 事件未找到", "{}: event not found"),
+
+    // editor
+    ("editor.search", "反向搜索", "reverse-i-search"),
+    ("editor.search_failed", "反向搜索失败", "failed reverse-i-search"),
+
+    // cli
+    ("cli.script_status", "脚本 {} 的退出状态为 {}", "Script {} exited with status {}"),
+
+    // help
+    ("help.usage_heading", "用法:", "Usage:"),
+    ("help.commands_heading", "命令", "Commands"),
+    ("help.arguments_heading", "参数", "Arguments"),
+    ("help.options_heading", "选项", "Options"),
+    ("help.help_flag", "显示帮助", "Print help"),
+    ("help.version_flag", "显示版本", "Print version"),
+    ("help.unknown_command", "help: 没有这个命令: {}", "help: no such command: {}"),
+    ("help.catshell", "🐱 CatShell：跨平台的命令行工具集和交互式shell", "🐱 CatShell: a cross-platform command toolkit and interactive shell"),
+    ("help.catshell.json", "以JSON格式输出结果，错误也以JSON对象输出到标准错误", "Print results as JSON; errors are printed to stderr as JSON objects"),
+    ("help.catshell.lang", "界面语言（zh-CN 或 en），默认根据 LC_ALL、LC_MESSAGES、LANG 决定", "Interface language (zh-CN or en); defaults to LC_ALL, LC_MESSAGES or LANG"),
+    ("help.catshell.emoji", "何时显示emoji图标：auto（输出是终端时）、always、never", "When to show emoji icons: auto (when output is a terminal), always, never"),
+    ("help.catshell.color", "何时使用颜色：auto（输出是终端且没有设置 NO_COLOR 时）、always、never", "When to use colors: auto (when output is a terminal and NO_COLOR is not set), always, never"),
+    ("help.catshell.install", "在指定目录中创建ls、cat、rm等命令的链接，通过链接调用时catshell就是对应的命令", "Create links for ls, cat, rm and other commands in DIR; invoked through a link, catshell acts as that command"),
+    ("help.ls", "列出目录内容", "List directory contents"),
+    ("help.ls.paths", "要列出的路径（默认是当前目录）", "Paths to list (default is current directory)"),
+    ("help.ls.all", "显示隐藏文件", "Show hidden files"),
+    ("help.ls.long", "使用长格式列出", "Long format listing"),
+    ("help.ls.recursive", "递归列出子目录", "List subdirectories recursively"),
+    ("help.ls.directory", "列出目录本身，而不是目录的内容", "List directories themselves, not their contents"),
+    ("help.ls.inode", "显示每个文件的inode号", "Print the inode number of each file"),
+    ("help.ls.human_readable", "以1K、234M、2G这样的单位显示大小（1024进制）", "Print sizes like 1K 234M 2G (powers of 1024)"),
+    ("help.ls.si", "和-h相同，但使用1000进制", "Like -h, but use powers of 1000"),
+    ("help.ls.time_style", "时间格式：locale、iso、long-iso、full-iso或+FORMAT", "Time format: locale, iso, long-iso, full-iso or +FORMAT"),
+    ("help.ls.no_git", "长格式中不显示git状态标记", "Don't show git status markers in the long format"),
+    ("help.ls.sort_time", "按修改时间排序，最新的在前", "Sort by modification time, newest first"),
+    ("help.ls.sort_size", "按文件大小排序，最大的在前", "Sort by file size, largest first"),
+    ("help.ls.sort_extension", "按扩展名的字母顺序排序", "Sort alphabetically by extension"),
+    ("help.ls.reverse", "反转排序结果（递归列出子目录请使用 -R）", "Reverse the sort order (use -R to list subdirectories recursively)"),
+    ("help.ls.group_directories_first", "目录排在文件前面", "Group directories before files"),
+    ("help.ls.classify", "在名称后面显示类型标记（*/=@| 之一）", "Append an indicator (one of */=@|) to entries"),
+    ("help.ls.one_per_line", "每行显示一个文件", "List one file per line"),
+    ("help.ls.columns", "分多列显示（输出是终端时的默认方式）", "List entries in columns (default when output is a terminal)"),
+    ("help.tree", "以树形显示目录结构", "Show the directory structure as a tree"),
+    ("help.tree.path", "要显示的目录（默认是当前目录）", "Directory to show (default is current directory)"),
+    ("help.tree.all", "显示隐藏文件", "Show hidden files"),
+    ("help.tree.dirs_only", "只显示目录", "List directories only"),
+    ("help.tree.level", "最多显示LEVEL层目录", "Descend at most LEVEL directories deep"),
+    ("help.tree.follow", "进入指向目录的符号链接", "Follow symbolic links to directories"),
+    ("help.tree.gitignore", "隐藏被.gitignore排除的文件", "Hide files excluded by .gitignore"),
+    ("help.tree.no_git", "不显示git状态标记", "Don't show git status markers"),
+    ("help.pwd", "显示当前工作目录", "Print working directory"),
+    ("help.rm", "删除文件或目录", "Remove files or directories"),
+    ("help.rm.paths", "要删除的文件或目录", "Files or directories to remove"),
+    ("help.rm.recursive", "递归删除目录及其内容", "Remove directories and their contents recursively"),
+    ("help.rm.force", "忽略不存在的文件，不输出提示", "Ignore nonexistent files and arguments, never prompt"),
+    ("help.uname", "显示系统信息", "Print system information"),
+    ("help.uname.all", "显示全部系统信息", "Print all system information"),
+    ("help.cd", "切换目录", "Change directory"),
+    ("help.cd.path", "要切换到的目录", "Directory to change to"),
+    ("help.mkdir", "创建目录", "Make directories"),
+    ("help.mkdir.paths", "要创建的目录", "Directories to create"),
+    ("help.mkdir.parents", "需要时创建上级目录", "Create parent directories as needed"),
+    ("help.cat", "连接并显示文件内容", "Concatenate and display files"),
+    ("help.cat.paths", "要显示的文件，- 表示标准输入", "Files to display; - means standard input"),
+    ("help.cat.number_lines", "为所有输出行编号", "Number all output lines"),
+    ("help.cat.number_nonblank", "为非空输出行编号", "Number nonempty output lines"),
+    ("help.curl", "发起HTTP请求 (curl-like)", "Make HTTP requests (curl-like)"),
+    ("help.curl.url", "请求的URL", "URL to request"),
+    ("help.curl.request", "HTTP方法 (GET, POST, PUT, DELETE, HEAD等)", "HTTP method (GET, POST, PUT, DELETE, HEAD, ...)"),
+    ("help.curl.include", "显示响应头", "Include response headers"),
+    ("help.curl.silent", "不输出任何内容", "Print nothing"),
+    ("help.curl.data", "发送的数据", "Data to send"),
+    ("help.curl.output", "保存响应到文件", "Save the response to a file"),
+    ("help.curl.head", "只显示HTTP状态码", "Only print the HTTP status code"),
+    ("help.curl.header", "添加自定义请求头", "Add a custom request header"),
+    ("help.cmatrix", "显示矩阵风格的字符下落动画", "Show a Matrix-style falling characters animation"),
+    ("help.cmatrix.matrix_color", "设置字符颜色 (green, red, blue, yellow, cyan, magenta, white)", "Character color (green, red, blue, yellow, cyan, magenta, white)"),
+    ("help.cmatrix.speed", "设置动画速度 (1-100, 值越小越快)", "Animation speed (1-100, smaller is faster)"),
+    ("help.cmatrix.density", "设置字符密度 (1-100)", "Character density (1-100)"),
+    ("help.cmatrix.no_bold", "不使用粗体字符", "Do not use bold characters"),
+    ("help.vim", "简化版vim文本编辑器", "Simplified vim text editor"),
+    ("help.vim.file", "要编辑的文件路径（可选）", "File to edit (optional)"),
+    ("help.open-browser", "打开浏览器访问指定URL", "Open a URL in the browser"),
+    ("help.open-browser.url", "要访问的URL地址", "URL to open"),
+    ("help.open", "在文件管理器中打开当前目录", "Open the current directory in the file manager"),
+    ("help.server", "启动HTTP服务器运行HTML文件", "Start an HTTP server for HTML files"),
+    ("help.server.path", "要提供服务的目录路径（默认是当前目录）", "Directory to serve (default is current directory)"),
+    ("help.server.port", "服务器端口号（默认8000）", "Server port (default 8000)"),
+    ("help.ping", "向指定主机发送ICMP回显请求", "Send ICMP echo requests to a host"),
+    ("help.ping.host", "要ping的主机名或IP地址", "Host name or IP address to ping"),
+    ("help.ping.count", "发送的回显请求数量", "Number of echo requests to send"),
+    ("help.ping.timeout", "超时时间（秒）", "Timeout in seconds"),
+    ("help.ping.size", "数据包大小（字节）", "Packet size in bytes"),
+    ("help.zip", "创建或提取zip压缩文件", "Create or extract zip archives"),
+    ("help.zip.create", "创建压缩文件", "Create an archive"),
+    ("help.zip.extract", "提取压缩文件", "Extract an archive"),
+    ("help.zip.output", "输出文件或目录路径", "Output file or directory"),
+    ("help.zip.files", "要压缩的文件或目录列表（创建模式）", "Files or directories to compress (create mode)"),
+    ("help.tui", "启动TUI界面", "Start the TUI"),
+    ("help.run", "执行catshell脚本", "Run a catshell script"),
+    ("help.run.script", "脚本文件路径", "Script file"),
+    ("help.run.args", "传给脚本的参数，脚本中通过 $1、$2 ... 访问", "Arguments for the script, available as $1, $2, ..."),
+    ("help.plugins", "列出PATH中的插件（名为 catshell-<命令> 的可执行文件）", "List plugins on PATH (executables named catshell-<command>)"),
+
+    // shell
+    ("shell.welcome", "欢迎使用CatShell! 输入 'help' 查看可用命令，输入 'exit' 退出", "Welcome to CatShell! Type 'help' for commands, 'exit' to quit"),
+    ("shell.read_failed", "读取输入失败: {}", "Failed to read input: {}"),
+    ("shell.bye", "再见!", "Bye!"),
+    ("shell.read_script_failed", "无法读取脚本 {}: {}", "Could not read script {}: {}"),
+    ("shell.background_single", "只有单个命令可以在后台执行", "Only a single command can run in the background"),
+    ("shell.background_forbidden", "{}: 不能在后台执行", "{}: cannot run in the background"),
+    ("shell.ambiguous_redirect", "重定向目标不明确: {}", "Ambiguous redirect: {}"),
+    ("shell.read_file_failed", "无法读取 {}: {}", "Could not read {}: {}"),
+    ("shell.open_failed", "无法打开 {}: {}", "Could not open {}: {}"),
+    ("shell.duplicate_failed", "无法复制输出: {}", "Could not duplicate output: {}"),
+    ("shell.numeric_arg", "{}: 需要数字参数: {}", "{}: numeric argument required: {}"),
+    ("shell.shift_range", "shift: 移动的数量超出范围: {}", "shift: shift count out of range: {}"),
+    ("shell.invalid_name", "export: 无效的变量名: {}", "export: not a valid identifier: {}"),
+    ("shell.set_option", "set: 不支持的选项: {}", "set: unsupported option: {}"),
+    ("shell.missing_bracket", "[: 缺少 ']'", "[: missing ']'"),
+    ("shell.loop_only", "{}: 只能在循环中使用", "{}: only meaningful in a loop"),
+    ("shell.invalid_alias", "alias: 无效的别名: {}", "alias: invalid alias name: {}"),
+    ("shell.alias_not_found", "{}: 未找到别名: {}", "{}: alias not found: {}"),
+    ("shell.kill_signal", "kill: 无效的信号: {}", "kill: invalid signal: {}"),
+    ("shell.kill_usage", "kill: 用法: kill [-信号] %任务号|进程号...", "kill: usage: kill [-signal] %job|pid..."),
+    ("shell.kill_target", "{}: 参数必须是进程号或 %任务号", "{}: arguments must be process or job IDs"),
+    ("shell.return_outside", "return: 只能在函数中使用", "return: can only be used in a function"),
+    ("shell.wait_failed", "等待 {} 结束失败: {}", "Failed to wait for {}: {}"),
+    ("shell.pipe_failed", "无法创建管道: {}", "Could not create a pipe: {}"),
+
+    // which
+    ("which.alias", "{}: 别名 '{}'", "{}: aliased to '{}'"),
+    ("which.function", "{}: shell函数", "{}: shell function"),
+    ("which.builtin", "{}: catshell内置命令", "{}: catshell builtin"),
+    ("which.plugin", "{}: catshell插件 {}", "{}: catshell plugin {}"),
+    ("which.not_found", "which: 未找到命令: {}", "which: command not found: {}"),
+
+    // tui
+    ("tui.starting", "正在启动CatShell TUI...", "Starting CatShell TUI..."),
+    ("tui.title", "CatShell 终端用户界面", "CatShell Terminal UI"),
+    ("tui.commands", "可用命令：", "Available commands:"),
+    ("tui.exit_hint", "提示：输入 'exit' 退出TUI", "Tip: type 'exit' to leave the TUI"),
+    ("tui.command_failed", "命令执行错误", "Command failed"),
+    ("tui.thanks", "感谢使用CatShell!", "Thanks for using CatShell!"),
+    ("tui.plugin", "插件", "plugin"),
+    ("tui.exited", "TUI已退出", "TUI exited"),
+
+    // i18n
+    ("i18n.unknown_lang", "不支持的语言: {}（可选 zh-CN、en）", "Unsupported language: {} (use zh-CN or en)"),
+];
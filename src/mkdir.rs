@@ -1,18 +1,28 @@
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
-/// 实现mkdir命令功能，创建目录
-pub fn create_directories(paths: &[&str], parents: bool) {
+use crate::error::{CatError, Result};
+use crate::json::Value;
+use crate::streams::Streams;
+use crate::style;
+
+/// 实现mkdir命令功能，创建目录。某个目录创建失败时继续创建其他目录，最后返回所有失败。
+/// `json` 时在最后输出创建成功的目录列表
+pub fn create_directories(paths: &[&str], parents: bool, json: bool, io: &mut Streams) -> Result<()> {
+    let mut created = Vec::new();
+    let mut errors = Vec::new();
     for path in paths {
         let target_path = Path::new(path);
         
         // 检查目录是否已存在
         if target_path.exists() {
-            if target_path.is_dir() {
-                eprintln!("❌ Error: Directory '{}' already exists", path);
+            let message = if target_path.is_dir() {
+                tr!("mkdir.dir_exists", path)
             } else {
-                eprintln!("❌ Error: File '{}' exists and is not a directory", path);
-            }
+                tr!("mkdir.file_exists", path)
+            };
+            errors.push(CatError::Failed(message));
             continue;
         }
         
@@ -27,8 +37,13 @@ pub fn create_directories(paths: &[&str], parents: bool) {
         
         // 处理结果
         match result {
-            Ok(_) => println!("✅ Created directory: {}", path),
-            Err(err) => eprintln!("❌ Error: Failed to create directory '{}': {}", path, err),
+            Ok(_) if json => created.push(*path),
+            Ok(_) => outln!(io, "{}{}", style::icon(&io.stdout, "✅"), tr!("mkdir.created", path)),
+            Err(err) => errors.push(CatError::Failed(tr!("mkdir.failed", path, err))),
         }
     }
+    if json {
+        outln!(io, "{}", Value::object([("created", created.into())]));
+    }
+    CatError::collect(errors)
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use clap::CommandFactory;
+
+use crate::cli::Cli;
+use crate::error::{CatError, Result};
+use crate::json::Value;
+use crate::streams::Streams;
+use crate::style;
+
+/// `catshell --install` 创建链接的命令。`cd` 在单独的进程中没有意义，
+/// `curl`、`ping`、`vim` 这类名字会覆盖系统中已有的同名程序，所以不在其中
+pub const COMMANDS: [&str; 7] = ["ls", "cat", "rm", "mkdir", "pwd", "uname", "zip"];
+
+/// 命令行参数。通过链接或副本以子命令的名字调用时（例如 `ls -l`），
+/// 在前面补上 `catshell`，相当于执行 `catshell ls -l`
+pub fn argv() -> Vec<OsString> {
+    let mut args: Vec<OsString> = env::args_os().collect();
+    if let Some(name) = args.first().and_then(|arg0| command_name(Path::new(arg0))) {
+        args[0] = name.into();
+        args.insert(0, "catshell".into());
+    }
+    args
+}
+
+/// 从程序路径中取出子命令名，不是子命令时返回 `None`。Windows上文件名不区分大小写
+fn command_name(program: &Path) -> Option<String> {
+    let stem = program.file_stem()?.to_str()?;
+    let name = if cfg!(windows) { stem.to_lowercase() } else { stem.to_string() };
+    if name == "catshell" || Cli::command().find_subcommand(&name).is_none() {
+        return None;
+    }
+    Some(name)
+}
+
+/// 在 `dir` 中为 [`COMMANDS`] 创建指向catshell的链接。已经指向catshell的链接会被保留，
+/// 其他同名文件不会被覆盖
+pub fn install(dir: &Path, json: bool, io: &mut Streams) -> Result<()> {
+    let exe = env::current_exe()
+        .and_then(fs::canonicalize)
+        .map_err(|err| CatError::Failed(tr!("install.exe_failed", err)))?;
+    if let Err(err) = fs::create_dir_all(dir) {
+        return Err(CatError::Failed(tr!("install.mkdir_failed", dir.display(), err)));
+    }
+
+    let mut installed = Vec::new();
+    let mut errors = Vec::new();
+    for name in COMMANDS {
+        let link = dir.join(format!("{}{}", name, env::consts::EXE_SUFFIX));
+        // symlink_metadata 对失效的符号链接也会成功
+        if link.symlink_metadata().is_ok() {
+            if fs::canonicalize(&link).is_ok_and(|target| target == exe) {
+                installed.push(link.display().to_string());
+            } else {
+                errors.push(CatError::Failed(tr!("install.exists", link.display())));
+            }
+            continue;
+        }
+        match create_link(&exe, &link) {
+            Ok(()) => {
+                if !json {
+                    outln!(io, "{}{} -> {}", style::icon(&io.stdout, "✅"), link.display(), exe.display());
+                }
+                installed.push(link.display().to_string());
+            },
+            Err(err) => errors.push(CatError::Failed(tr!("install.link_failed", link.display(), err))),
+        }
+    }
+
+    if json {
+        outln!(io, "{}", Value::object([("installed", installed.into())]));
+    } else if errors.is_empty() {
+        outln!(io, "{}", tr!("install.done", dir.display(), COMMANDS.join(", ")));
+    }
+    CatError::collect(errors)
+}
+
+/// Unix上创建符号链接
+#[cfg(unix)]
+fn create_link(exe: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(exe, link)
+}
+
+/// Windows上创建符号链接需要管理员权限或开发者模式，所以先尝试硬链接，
+/// 跨磁盘时硬链接会失败，再退回到复制
+#[cfg(not(unix))]
+fn create_link(exe: &Path, link: &Path) -> io::Result<()> {
+    fs::hard_link(exe, link).or_else(|_| fs::copy(exe, link).map(|_| ()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{output, streams, TempDir};
+
+    #[test]
+    fn recognizes_subcommand_names() {
+        assert_eq!(command_name(Path::new("/usr/local/bin/ls")).as_deref(), Some("ls"));
+        assert_eq!(command_name(Path::new("cat.exe")).as_deref(), Some("cat"));
+        assert_eq!(command_name(Path::new("/opt/catshell")), None);
+        assert_eq!(command_name(Path::new("bash")), None);
+        assert_eq!(command_name(Path::new("")), None);
+    }
+
+    #[test]
+    fn installs_links_once() {
+        let dir = TempDir::new();
+        let target = dir.path().join("bin");
+        let mut io = streams(b"");
+        install(&target, true, &mut io).unwrap();
+        for name in COMMANDS {
+            assert!(target.join(format!("{}{}", name, env::consts::EXE_SUFFIX)).exists(), "{}", name);
+        }
+
+        // 已经安装的链接保留，其他同名文件不覆盖
+        fs::remove_file(target.join(format!("rm{}", env::consts::EXE_SUFFIX))).unwrap();
+        fs::write(target.join(format!("rm{}", env::consts::EXE_SUFFIX)), "other").unwrap();
+        let mut io = streams(b"");
+        let error = install(&target, true, &mut io).unwrap_err();
+        assert_eq!(error.exit_code(), 1);
+        assert!(error.to_string().contains("rm"));
+        let installed = crate::json::parse(output(io).0.trim()).unwrap();
+        assert!(matches!(installed.get("installed"), Some(Value::Array(items)) if items.len() == COMMANDS.len() - 1));
+    }
+}
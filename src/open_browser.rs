@@ -1,20 +1,19 @@
+use std::io::Write;
 use std::process::Command;
 
+use crate::error::{CatError, Result};
+use crate::streams::Streams;
+
 /// 打开浏览器访问指定的URL
-pub fn open_browser(url: &str) {
-    println!("正在打开浏览器访问: {}", url);
+pub fn open_browser(url: &str, io: &mut Streams) -> Result<()> {
+    outln!(io, "{}", tr!("browser.opening", url));
     
     // 在Windows上使用start命令打开默认浏览器
     let result = Command::new("cmd")
         .args(["/c", "start", url])
         .spawn();
     
-    match result {
-        Ok(_) => {
-            println!("浏览器已成功打开");
-        },
-        Err(err) => {
-            eprintln!("无法打开浏览器: {}", err);
-        }
-    }
+    result.map_err(|err| CatError::spawn("cmd", err))?;
+    outln!(io, "{}", tr!("browser.opened"));
+    Ok(())
 }
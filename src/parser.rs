@@ -0,0 +1,589 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::i18n;
+use crate::lexer::{self, Token};
+
+/// 重定向的种类
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedirectKind {
+    /// `< file`
+    Stdin,
+    /// `> file`
+    Stdout,
+    /// `>> file`
+    StdoutAppend,
+    /// `2> file`
+    Stderr,
+    /// `2>> file`
+    StderrAppend,
+    /// `2>&1`，没有目标文件
+    StderrToStdout,
+    /// `>&2`，没有目标文件
+    StdoutToStderr,
+}
+
+/// 一个重定向，例如 `> out.html`，目标在执行时展开。`2>&1` 和 `>&2` 的目标为空
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    pub kind: RedirectKind,
+    pub target: String,
+}
+
+/// 管道中的一个阶段：命令及其参数，以及它自己的重定向。单词保留原始形式，执行时展开
+#[derive(Debug, Clone, Default)]
+pub struct Stage {
+    pub words: Vec<String>,
+    pub redirects: Vec<Redirect>,
+}
+
+/// 由 `|` 连接起来的若干阶段
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    pub stages: Vec<Stage>,
+}
+
+/// `&&` 或 `||`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Connector {
+    And,
+    Or,
+}
+
+/// 一条命令：普通管道或者控制结构
+#[derive(Debug, Clone)]
+pub enum Command {
+    Pipeline(Pipeline),
+    /// `if ...; then ...; elif ...; then ...; else ...; fi`
+    If {
+        branches: Vec<(List, List)>,
+        otherwise: Option<List>,
+    },
+    /// `for NAME in WORDS; do ...; done`，省略 `in` 时遍历位置参数
+    For {
+        var: String,
+        words: Option<Vec<String>>,
+        body: List,
+    },
+    /// `while ...; do ...; done`
+    While { condition: List, body: List },
+    /// `name() { ... }` 或 `function name { ... }`
+    Function { name: String, body: Rc<List> },
+    /// `{ ...; }`
+    Group(List),
+}
+
+/// 由 `&&` 和 `||` 连接的命令链
+#[derive(Debug, Clone)]
+pub struct AndOr {
+    pub first: Command,
+    pub rest: Vec<(Connector, Command)>,
+    /// 以 `&` 结尾，在后台执行
+    pub background: bool,
+}
+
+/// 由 `;` 或换行分隔的命令链序列，也就是一个脚本或一段代码块
+pub type List = Vec<AndOr>;
+
+/// 解析错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// 输入还没有结束，例如引号未闭合或 `if` 缺少 `fi`，交互模式下可以继续读入
+    Incomplete,
+    Syntax(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Incomplete => write!(f, "{}", tr!("parser.incomplete")),
+            ParseError::Syntax(message) => write!(f, "{}", tr!("parser.syntax", message)),
+        }
+    }
+}
+
+/// 把一段源代码（一行命令或整个脚本）解析成命令序列
+pub fn parse(source: &str) -> Result<List, ParseError> {
+    parse_with_aliases(source, &HashMap::new())
+}
+
+/// 解析源代码，命令位置上的别名会被替换成它的内容，和bash一样在解析时展开
+pub fn parse_with_aliases(source: &str, aliases: &HashMap<String, String>) -> Result<List, ParseError> {
+    let tokens = lexer::tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0, aliases, alias_spans: Vec::new() };
+    let list = parser.parse_list(&[])?;
+
+    match parser.peek() {
+        None => Ok(list),
+        Some(token) => Err(unexpected(token)),
+    }
+}
+
+/// 出现在命令位置时有特殊含义的单词，它们不会被当作别名展开
+const RESERVED_WORDS: &[&str] = &[
+    "if", "then", "elif", "else", "fi", "for", "in", "do", "done", "while", "function", "{", "}",
+];
+
+/// 递归下降解析器
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    aliases: &'a HashMap<String, String>,
+    /// 别名展开得到的词法单元的结束位置和别名名称，展开结果中的同名别名不会再次展开，避免无限递归
+    alias_spans: Vec<(usize, String)>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// 下一个单词是否是给定的保留字
+    fn peek_keyword(&self, keywords: &[&str]) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if keywords.contains(&word.as_str()))
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek() == Some(&Token::Newline) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while matches!(self.peek(), Some(Token::Newline) | Some(Token::Semi)) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        match self.next() {
+            Some(Token::Word(word)) if word == keyword => Ok(()),
+            Some(token) => Err(ParseError::Syntax(tr!("parser.expected", keyword, describe(&token)))),
+            None => Err(ParseError::Incomplete),
+        }
+    }
+
+    fn expect_token(&mut self, expected: Token) -> Result<(), ParseError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(unexpected(&token)),
+            None => Err(ParseError::Incomplete),
+        }
+    }
+
+    fn expect_name(&mut self) -> Result<String, ParseError> {
+        match self.next() {
+            Some(Token::Word(name)) => Ok(name),
+            Some(token) => Err(unexpected(&token)),
+            None => Err(ParseError::Incomplete),
+        }
+    }
+
+    /// 如果当前单词是别名，把它替换成别名内容的词法单元。别名的第一个单词也是别名时继续展开
+    fn expand_alias(&mut self) -> Result<(), ParseError> {
+        loop {
+            let name = match self.peek() {
+                Some(Token::Word(word)) if !RESERVED_WORDS.contains(&word.as_str()) => word.clone(),
+                _ => return Ok(()),
+            };
+            let Some(value) = self.aliases.get(&name) else { return Ok(()) };
+            let pos = self.pos;
+            if self.alias_spans.iter().any(|(end, alias)| *end > pos && *alias == name) {
+                return Ok(());
+            }
+
+            let tokens = lexer::tokenize(value).map_err(|_| ParseError::Syntax(tr!("parser.incomplete_alias", name)))?;
+            let len = tokens.len();
+            self.tokens.splice(pos..pos + 1, tokens);
+            for (end, _) in self.alias_spans.iter_mut().filter(|(end, _)| *end > pos) {
+                *end = *end + len - 1;
+            }
+            self.alias_spans.push((pos + len, name));
+        }
+    }
+
+    /// 解析命令序列，遇到输入结束或 `terminators` 中的保留字时停止
+    fn parse_list(&mut self, terminators: &[&str]) -> Result<List, ParseError> {
+        let mut list = Vec::new();
+
+        loop {
+            self.skip_separators();
+            if self.peek().is_none() || self.peek_keyword(terminators) {
+                break;
+            }
+
+            let mut and_or = self.parse_and_or()?;
+            // `&` 同时也是命令之间的分隔符
+            if self.peek() == Some(&Token::Background) {
+                self.pos += 1;
+                and_or.background = true;
+                list.push(and_or);
+                continue;
+            }
+            list.push(and_or);
+
+            match self.peek() {
+                Some(Token::Semi) | Some(Token::Newline) => self.pos += 1,
+                None => break,
+                _ if self.peek_keyword(terminators) => break,
+                Some(token) => return Err(unexpected(token)),
+            }
+        }
+
+        // 控制结构还没有遇到结束的保留字
+        if !terminators.is_empty() && self.peek().is_none() {
+            return Err(ParseError::Incomplete);
+        }
+        Ok(list)
+    }
+
+    fn parse_and_or(&mut self) -> Result<AndOr, ParseError> {
+        let first = self.parse_command()?;
+        let mut rest = Vec::new();
+
+        loop {
+            let connector = match self.peek() {
+                Some(Token::And) => Connector::And,
+                Some(Token::Or) => Connector::Or,
+                _ => break,
+            };
+            self.pos += 1;
+            // `&&` 和 `||` 后面允许换行
+            self.skip_newlines();
+            rest.push((connector, self.parse_command()?));
+        }
+
+        Ok(AndOr { first, rest, background: false })
+    }
+
+    fn parse_command(&mut self) -> Result<Command, ParseError> {
+        self.expand_alias()?;
+        let word = match self.peek() {
+            None => return Err(ParseError::Incomplete),
+            Some(Token::Word(word)) => word.clone(),
+            Some(Token::Redirect(_)) => return self.parse_pipeline().map(Command::Pipeline),
+            Some(token) => return Err(unexpected(token)),
+        };
+
+        match word.as_str() {
+            "if" => self.parse_if(),
+            "for" => self.parse_for(),
+            "while" => self.parse_while(),
+            "{" => {
+                self.pos += 1;
+                let body = self.parse_list(&["}"])?;
+                self.expect_keyword("}")?;
+                Ok(Command::Group(body))
+            },
+            "function" => {
+                self.pos += 1;
+                let name = self.expect_name()?;
+                if self.peek() == Some(&Token::LParen) {
+                    self.pos += 1;
+                    self.expect_token(Token::RParen)?;
+                }
+                self.parse_function_body(name)
+            },
+            _ if self.tokens.get(self.pos + 1) == Some(&Token::LParen) => {
+                self.pos += 2;
+                self.expect_token(Token::RParen)?;
+                self.parse_function_body(word)
+            },
+            _ => self.parse_pipeline().map(Command::Pipeline),
+        }
+    }
+
+    /// 函数体必须是 `{ ... }`
+    fn parse_function_body(&mut self, name: String) -> Result<Command, ParseError> {
+        self.skip_newlines();
+        self.expect_keyword("{")?;
+        let body = self.parse_list(&["}"])?;
+        self.expect_keyword("}")?;
+        Ok(Command::Function { name, body: Rc::new(body) })
+    }
+
+    fn parse_if(&mut self) -> Result<Command, ParseError> {
+        self.expect_keyword("if")?;
+        let mut branches = Vec::new();
+        let mut otherwise = None;
+
+        loop {
+            let condition = self.parse_list(&["then"])?;
+            self.expect_keyword("then")?;
+            let body = self.parse_list(&["elif", "else", "fi"])?;
+            branches.push((condition, body));
+
+            match self.next() {
+                Some(Token::Word(word)) if word == "elif" => continue,
+                Some(Token::Word(word)) if word == "else" => {
+                    otherwise = Some(self.parse_list(&["fi"])?);
+                    self.expect_keyword("fi")?;
+                    break;
+                },
+                Some(Token::Word(word)) if word == "fi" => break,
+                Some(token) => return Err(unexpected(&token)),
+                None => return Err(ParseError::Incomplete),
+            }
+        }
+
+        Ok(Command::If { branches, otherwise })
+    }
+
+    fn parse_for(&mut self) -> Result<Command, ParseError> {
+        self.expect_keyword("for")?;
+        let var = self.expect_name()?;
+
+        self.skip_newlines();
+        let mut words = None;
+        if self.peek_keyword(&["in"]) {
+            self.pos += 1;
+            let mut list = Vec::new();
+            while let Some(Token::Word(word)) = self.peek() {
+                list.push(word.clone());
+                self.pos += 1;
+            }
+            words = Some(list);
+        }
+
+        self.skip_separators();
+        self.expect_keyword("do")?;
+        let body = self.parse_list(&["done"])?;
+        self.expect_keyword("done")?;
+
+        Ok(Command::For { var, words, body })
+    }
+
+    fn parse_while(&mut self) -> Result<Command, ParseError> {
+        self.expect_keyword("while")?;
+        let condition = self.parse_list(&["do"])?;
+        self.expect_keyword("do")?;
+        let body = self.parse_list(&["done"])?;
+        self.expect_keyword("done")?;
+
+        Ok(Command::While { condition, body })
+    }
+
+    fn parse_pipeline(&mut self) -> Result<Pipeline, ParseError> {
+        let mut pipeline = Pipeline::default();
+        let mut stage = Stage::default();
+
+        loop {
+            match self.peek() {
+                Some(Token::Word(word)) => {
+                    stage.words.push(word.clone());
+                    self.pos += 1;
+                },
+                Some(Token::Redirect(kind @ (RedirectKind::StderrToStdout | RedirectKind::StdoutToStderr))) => {
+                    stage.redirects.push(Redirect { kind: *kind, target: String::new() });
+                    self.pos += 1;
+                },
+                Some(Token::Redirect(kind)) => {
+                    let kind = *kind;
+                    self.pos += 1;
+                    match self.next() {
+                        Some(Token::Word(target)) => stage.redirects.push(Redirect { kind, target }),
+                        _ => return Err(ParseError::Syntax(tr!("parser.redirect_target"))),
+                    }
+                },
+                Some(Token::Pipe) => {
+                    if stage.words.is_empty() {
+                        return Err(ParseError::Syntax(tr!("parser.pipe_command")));
+                    }
+                    pipeline.stages.push(std::mem::take(&mut stage));
+                    self.pos += 1;
+                    // `|` 后面允许换行，输入在这里结束说明命令还没写完
+                    self.skip_newlines();
+                    if self.peek().is_none() {
+                        return Err(ParseError::Incomplete);
+                    }
+                    self.expand_alias()?;
+                },
+                _ => break,
+            }
+        }
+
+        if stage.words.is_empty() {
+            return Err(ParseError::Syntax(tr!("parser.missing_command")));
+        }
+        pipeline.stages.push(stage);
+
+        Ok(pipeline)
+    }
+}
+
+fn unexpected(token: &Token) -> ParseError {
+    ParseError::Syntax(tr!("parser.unexpected", describe(token)))
+}
+
+/// 词法单元在错误信息中的写法
+fn describe(token: &Token) -> String {
+    let text = match token {
+        Token::Word(word) => return word.clone(),
+        Token::Pipe => "|",
+        Token::Redirect(RedirectKind::Stdin) => "<",
+        Token::Redirect(RedirectKind::Stdout) => ">",
+        Token::Redirect(RedirectKind::StdoutAppend) => ">>",
+        Token::Redirect(RedirectKind::Stderr) => "2>",
+        Token::Redirect(RedirectKind::StderrAppend) => "2>>",
+        Token::Redirect(RedirectKind::StderrToStdout) => "2>&1",
+        Token::Redirect(RedirectKind::StdoutToStderr) => ">&2",
+        Token::And => "&&",
+        Token::Or => "||",
+        Token::Background => "&",
+        Token::Semi => ";",
+        Token::Newline => i18n::text("parser.newline"),
+        Token::LParen => "(",
+        Token::RParen => ")",
+    };
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 把解析结果写回源代码，用来比较解析得到的结构
+    fn render(list: &List) -> String {
+        let items: Vec<String> = list
+            .iter()
+            .map(|and_or| {
+                let mut text = render_command(&and_or.first);
+                for (connector, command) in &and_or.rest {
+                    text.push_str(if *connector == Connector::And { " && " } else { " || " });
+                    text.push_str(&render_command(command));
+                }
+                if and_or.background {
+                    text.push_str(" &");
+                }
+                text
+            })
+            .collect();
+        items.join("; ")
+    }
+
+    fn render_command(command: &Command) -> String {
+        match command {
+            Command::Pipeline(pipeline) => {
+                let stages: Vec<String> = pipeline.stages.iter().map(render_stage).collect();
+                stages.join(" | ")
+            },
+            Command::If { branches, otherwise } => {
+                let mut text = String::new();
+                for (index, (condition, body)) in branches.iter().enumerate() {
+                    let keyword = if index == 0 { "if" } else { " elif" };
+                    text.push_str(&format!("{} {}; then {};", keyword, render(condition), render(body)));
+                }
+                if let Some(body) = otherwise {
+                    text.push_str(&format!(" else {};", render(body)));
+                }
+                text + " fi"
+            },
+            Command::For { var, words, body } => match words {
+                Some(words) => format!("for {} in {}; do {}; done", var, words.join(" "), render(body)),
+                None => format!("for {}; do {}; done", var, render(body)),
+            },
+            Command::While { condition, body } => format!("while {}; do {}; done", render(condition), render(body)),
+            Command::Function { name, body } => format!("{}() {{ {}; }}", name, render(body)),
+            Command::Group(body) => format!("{{ {}; }}", render(body)),
+        }
+    }
+
+    fn render_stage(stage: &Stage) -> String {
+        let mut parts = stage.words.clone();
+        for redirect in &stage.redirects {
+            let operator = describe(&Token::Redirect(redirect.kind));
+            parts.push(if redirect.target.is_empty() { operator } else { format!("{} {}", operator, redirect.target) });
+        }
+        parts.join(" ")
+    }
+
+    /// 解析 `source`，检查写回的源代码是 `expected`，并且再次解析得到相同的结构
+    fn round_trip(source: &str, expected: &str) {
+        let rendered = render(&parse(source).unwrap());
+        assert_eq!(rendered, expected, "{}", source);
+        assert_eq!(render(&parse(&rendered).unwrap()), rendered);
+    }
+
+    #[test]
+    fn parses_pipelines_and_redirects() {
+        round_trip("ls -l | cat -n", "ls -l | cat -n");
+        round_trip("sort<in.txt|cat -n>out.txt", "sort < in.txt | cat -n > out.txt");
+        round_trip("cat a >> b 2> err 2>>log", "cat a >> b 2> err 2>> log");
+        round_trip("ls x 2>&1 | cat; echo a >&2", "ls x 2>&1 | cat; echo a >&2");
+        round_trip("< in.txt sort", "sort < in.txt");
+        // `|` 后面可以换行
+        round_trip("ls |\n  cat", "ls | cat");
+        round_trip("a && b || c; d &\ne", "a && b || c; d &; e");
+    }
+
+    #[test]
+    fn rejects_broken_pipelines() {
+        assert!(matches!(parse("| cat"), Err(ParseError::Syntax(_))));
+        assert!(matches!(parse("ls | | cat"), Err(ParseError::Syntax(_))));
+        assert!(matches!(parse("ls >"), Err(ParseError::Syntax(_))));
+        assert!(matches!(parse("ls > | cat"), Err(ParseError::Syntax(_))));
+        assert_eq!(parse("ls |").unwrap_err(), ParseError::Incomplete);
+        assert_eq!(parse("ls &&").unwrap_err(), ParseError::Incomplete);
+    }
+
+    #[test]
+    fn parses_control_flow() {
+        round_trip("if true; then echo a; fi", "if true; then echo a; fi");
+        round_trip(
+            "if a\nthen\n  b\nelif c; then d\nelse\n  e\nfi",
+            "if a; then b; elif c; then d; else e; fi",
+        );
+        round_trip("for x in a b *.rs; do echo $x; done", "for x in a b *.rs; do echo $x; done");
+        round_trip("for x\ndo echo $x\ndone", "for x; do echo $x; done");
+        round_trip("while test -f x\ndo sleep 1; done", "while test -f x; do sleep 1; done");
+        round_trip("greet() { echo hi $1; }", "greet() { echo hi $1; }");
+        round_trip("{ a; b; } && if x; then y; fi", "{ a; b; } && if x; then y; fi");
+        round_trip(
+            "for f in a b; do if test $f = a; then continue; fi; echo $f; done",
+            "for f in a b; do if test $f = a; then continue; fi; echo $f; done",
+        );
+    }
+
+    #[test]
+    fn unfinished_blocks_are_incomplete() {
+        for source in ["if true; then", "if true; then a; else", "for x in a b", "while true; do a", "f() {", "{ a;"] {
+            assert_eq!(parse(source).unwrap_err(), ParseError::Incomplete, "{}", source);
+        }
+        for source in ["if a; then b; fi fi", "for; do a; done", "for x in; a; done", "f() echo", "while a; do b; done > log"] {
+            assert!(matches!(parse(source), Err(ParseError::Syntax(_))), "{}", source);
+        }
+    }
+
+    #[test]
+    fn expands_aliases_in_command_position() {
+        let aliases: HashMap<String, String> = [
+            ("ll", "ls -l"),
+            ("ls", "ls -a"),
+            ("loop", "loop x"),
+            ("a", "b 1"),
+            ("b", "a 2"),
+            ("both", "echo a; echo b |"),
+            ("broken", "echo 'x"),
+        ]
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        let expand = |source: &str| parse_with_aliases(source, &aliases).map(|list| render(&list));
+
+        assert_eq!(expand("ll src | ll").unwrap(), "ls -a -l src | ls -a -l");
+        assert_eq!(expand("echo ll && ls").unwrap(), "echo ll && ls -a");
+        assert_eq!(expand("if ll; then 'll'; fi").unwrap(), "if ls -a -l; then 'll'; fi");
+        // 展开结果中的同名别名不再展开
+        assert_eq!(expand("loop").unwrap(), "loop x");
+        assert_eq!(expand("a").unwrap(), "a 2 1");
+        assert_eq!(expand("both cat").unwrap(), "echo a; echo b | cat");
+        assert!(matches!(expand("broken"), Err(ParseError::Syntax(_))));
+    }
+}
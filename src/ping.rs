@@ -1,7 +1,74 @@
-use std::process::{Command, Output};
+use std::io::Write;
+use std::process::Command;
 
-/// 执行ping命令
-pub fn ping_host(host: &str, count: u32, timeout: u32, packet_size: u32) {
+use crate::error::{CatError, Result};
+use crate::json::{ToJson, Value};
+use crate::streams::Streams;
+use crate::style;
+
+/// [`ping`] 的结果，`output` 是系统ping命令的原始输出
+#[derive(Debug, Clone)]
+pub struct PingResult {
+    pub host: String,
+    pub output: String,
+    pub reachable: bool,
+    /// 发送的回显请求数量
+    pub transmitted: u32,
+    /// 从输出中解析出的每一个回复
+    pub replies: Vec<Reply>,
+}
+
+/// 一个回显回复，Windows的ping不显示序号，此时 `seq` 为 `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reply {
+    pub seq: Option<u32>,
+    pub ttl: Option<u32>,
+    pub time_ms: f64,
+}
+
+impl PingResult {
+    /// 丢包率（百分比）
+    pub fn loss_percent(&self) -> f64 {
+        if self.transmitted == 0 {
+            return 0.0;
+        }
+        let lost = self.transmitted.saturating_sub(self.replies.len() as u32);
+        lost as f64 * 100.0 / self.transmitted as f64
+    }
+}
+
+impl ToJson for Reply {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("seq", self.seq.into()),
+            ("ttl", self.ttl.into()),
+            ("time_ms", self.time_ms.into()),
+        ])
+    }
+}
+
+impl ToJson for PingResult {
+    fn to_json(&self) -> Value {
+        let times: Vec<f64> = self.replies.iter().map(|reply| reply.time_ms).collect();
+        let min = times.iter().copied().reduce(f64::min);
+        let max = times.iter().copied().reduce(f64::max);
+        let avg = (!times.is_empty()).then(|| times.iter().sum::<f64>() / times.len() as f64);
+        Value::object([
+            ("host", self.host.as_str().into()),
+            ("reachable", self.reachable.into()),
+            ("transmitted", self.transmitted.into()),
+            ("received", self.replies.len().into()),
+            ("loss_percent", self.loss_percent().into()),
+            ("min_ms", min.into()),
+            ("avg_ms", avg.into()),
+            ("max_ms", max.into()),
+            ("replies", Value::Array(self.replies.iter().map(Reply::to_json).collect())),
+        ])
+    }
+}
+
+/// 调用系统的ping命令，`timeout` 以秒为单位。ping命令无法启动或报错时返回错误
+pub fn ping(host: &str, count: u32, timeout: u32, packet_size: u32) -> Result<PingResult> {
     // 根据操作系统选择合适的ping命令参数
     #[cfg(windows)]
     let output = Command::new("ping")
@@ -27,33 +94,61 @@ let output = Command::new("ping")
     .arg(host)
     .output();
     
-    match output {
-        Ok(output) => {
-            print_ping_result(&output, host);
-        },
-        Err(err) => {
-            eprintln!("执行ping命令失败: {}", err);
-        }
-    }
-}
-
-/// 打印ping命令的结果
-fn print_ping_result(output: &Output, host: &str) {
+    let output = output.map_err(|err| CatError::spawn("ping", err))?;
+    
     // 将输出转换为字符串
-    let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
-    
     if !stderr.is_empty() {
-        eprintln!("错误: {}", stderr);
-        return;
+        return Err(CatError::Failed(tr!("ping.error", stderr.trim())));
     }
     
-    println!("{}", stdout);
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    Ok(PingResult {
+        host: host.to_string(),
+        replies: stdout.lines().filter_map(parse_reply).collect(),
+        output: stdout,
+        reachable: output.status.success(),
+        transmitted: count,
+    })
+}
+
+/// 从ping输出的一行中解析回复，兼容Linux/macOS和中英文Windows的格式，例如
+/// `64 bytes from 1.1.1.1: icmp_seq=1 ttl=57 time=12.3 ms` 和 `来自 1.1.1.1 的回复: 字节=32 时间<1ms TTL=57`
+fn parse_reply(line: &str) -> Option<Reply> {
+    let line = line.to_lowercase();
+    let time_ms = ["time=", "time<", "时间=", "时间<"]
+        .iter()
+        .find_map(|key| number_after(&line, key))?;
+    Some(Reply {
+        seq: number_after(&line, "icmp_seq=").map(|seq| seq as u32),
+        ttl: number_after(&line, "ttl=").map(|ttl| ttl as u32),
+        time_ms,
+    })
+}
+
+// 解析紧跟在key后面的数字
+fn number_after(line: &str, key: &str) -> Option<f64> {
+    let rest = &line[line.find(key)? + key.len()..];
+    let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+/// 执行ping命令并打印结果，主机不可访问时返回错误
+pub fn ping_host(host: &str, count: u32, timeout: u32, packet_size: u32, json: bool, io: &mut Streams) -> Result<()> {
+    let result = ping(host, count, timeout, packet_size)?;
+    if json {
+        outln!(io, "{}", result.to_json());
+    } else {
+        outln!(io, "{}", result.output);
+    }
     
     // 检查是否ping成功
-    if output.status.success() {
-        println!("✅ {} 可访问", host);
+    if result.reachable {
+        if !json {
+            outln!(io, "{}{}", style::icon(&io.stdout, "✅"), tr!("ping.reachable", host));
+        }
+        Ok(())
     } else {
-        println!("❌ {} 不可访问", host);
+        Err(CatError::Failed(tr!("ping.unreachable", host)))
     }
 }
\ No newline at end of file
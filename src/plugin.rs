@@ -0,0 +1,271 @@
+use std::env;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::CommandFactory;
+
+use crate::cli::Cli;
+use crate::config;
+use crate::error::{CatError, Result};
+use crate::i18n;
+use crate::json::{self, ToJson, Value};
+use crate::shell;
+use crate::streams::Streams;
+use crate::style;
+use crate::which;
+
+/// 插件可执行文件名的前缀，`PATH` 中的 `catshell-hello` 提供 `hello` 命令
+pub const PREFIX: &str = "catshell-";
+
+/// 插件协议的版本，通过环境变量 `CATSHELL_PLUGIN_PROTOCOL` 传给插件
+pub const PROTOCOL_VERSION: i64 = 1;
+
+/// 查询插件描述的参数，插件应当输出一个JSON对象后退出
+pub const DESCRIBE_FLAG: &str = "--catshell-describe";
+
+/// 等待插件回答描述查询的最长时间，不认识该参数的插件可能会一直运行
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `PATH` 中找到的一个插件
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// 插件对 `--catshell-describe` 的回答，例如 `{"protocol": 1, "description": "问候"}`
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub protocol: i64,
+    pub description: String,
+}
+
+/// 查找提供 `name` 命令的插件。内置子命令优先，不能被插件覆盖。
+/// 插件名只能包含ASCII字母、数字、`_` 和 `-`，因为它会被写进tui的PowerShell脚本
+pub fn find(name: &str) -> Option<Plugin> {
+    if !is_valid_name(name) || Cli::command().find_subcommand(name).is_some() {
+        return None;
+    }
+    let path = which::find_program(&format!("{}{}", PREFIX, name))?;
+    Some(Plugin { name: name.to_string(), path })
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-')
+}
+
+/// `PATH` 中的所有插件，按名称排序
+pub fn discover() -> Vec<Plugin> {
+    which::program_names()
+        .iter()
+        .filter_map(|program| find(program.strip_prefix(PREFIX)?))
+        .collect()
+}
+
+impl Plugin {
+    /// 启动插件的命令。插件从环境变量中得到协议版本、输出格式（`text` 或 `json`）、
+    /// 界面语言（`zh-CN` 或 `en`）和catshell自身的路径，标准输入输出和普通程序一样接到管道上
+    pub fn command(&self, args: &[String], json: bool) -> Command {
+        let mut command = Command::new(&self.path);
+        command
+            .args(args)
+            .env("CATSHELL_PLUGIN_PROTOCOL", PROTOCOL_VERSION.to_string())
+            .env("CATSHELL_OUTPUT", if json { "json" } else { "text" })
+            .env("CATSHELL_LANG", i18n::lang().code());
+        config::apply_environment(&mut command);
+        if let Ok(exe) = env::current_exe() {
+            command.env("CATSHELL", exe);
+        }
+        command
+    }
+
+    /// 用 `--catshell-describe` 查询插件的描述。不支持协议的插件（例如只是放在 `PATH` 中的脚本）返回 `None`
+    pub fn describe(&self) -> Option<Manifest> {
+        let mut child = self
+            .command(&[DESCRIBE_FLAG.to_string()], true)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+
+        // 在单独的线程中读取输出，超时后不等待它结束
+        let mut stdout = child.stdout.take()?;
+        let reader = thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stdout.read_to_string(&mut output);
+            output
+        });
+        let start = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) if status.success() => break,
+                Ok(None) if start.elapsed() < DESCRIBE_TIMEOUT => thread::sleep(Duration::from_millis(20)),
+                _ => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+            }
+        }
+
+        let value = json::parse(&reader.join().ok()?)?;
+        Some(Manifest {
+            protocol: value.get("protocol")?.as_i64()?,
+            description: value.get("description").and_then(Value::as_str).unwrap_or("").to_string(),
+        })
+    }
+}
+
+/// 同时查询多个插件的描述，结果与 `plugins` 一一对应。每个插件最多等待 `DESCRIBE_TIMEOUT`，
+/// 逐个查询时总的等待时间会随插件数量增长
+pub fn describe_all(plugins: &[Plugin]) -> Vec<Option<Manifest>> {
+    let lang = i18n::lang();
+    let environment = config::shell_environment();
+    thread::scope(|scope| {
+        let handles: Vec<_> = plugins
+            .iter()
+            .map(|plugin| {
+                let environment = environment.clone();
+                scope.spawn(move || {
+                    i18n::set_lang(Some(lang));
+                    match environment {
+                        Some(environment) => config::with_environment(environment, || plugin.describe()),
+                        None => plugin.describe(),
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().ok().flatten()).collect()
+    })
+}
+
+impl ToJson for Plugin {
+    fn to_json(&self) -> Value {
+        plugin_json(self, self.describe())
+    }
+}
+
+fn plugin_json(plugin: &Plugin, manifest: Option<Manifest>) -> Value {
+    Value::object([
+        ("name", plugin.name.as_str().into()),
+        ("path", plugin.path.display().to_string().into()),
+        ("protocol", manifest.as_ref().map(|manifest| manifest.protocol).into()),
+        ("description", manifest.map(|manifest| manifest.description).into()),
+    ])
+}
+
+/// 执行插件命令，`args[0]` 是命令名。返回插件的退出状态
+pub fn run(args: &[String], json: bool, io: &mut Streams) -> i32 {
+    let Some(plugin) = find(&args[0]) else {
+        let err = CatError::NotFound(tr!("plugin.not_found", args[0], PREFIX));
+        if json {
+            err.report_json(io);
+        } else {
+            err.report(io);
+        }
+        return err.exit_code();
+    };
+    let json = json || args.iter().any(|arg| arg == "--json");
+    shell::run_process(plugin.command(&args[1..], json), &args[0], io)
+}
+
+/// 实现plugins命令，列出 `PATH` 中的插件和它们的描述
+pub fn list(json: bool, io: &mut Streams) -> Result<()> {
+    let plugins = discover();
+    let manifests = describe_all(&plugins);
+    if json {
+        let plugins = plugins.iter().zip(manifests).map(|(plugin, manifest)| plugin_json(plugin, manifest));
+        outln!(io, "{}", Value::Array(plugins.collect()));
+        return Ok(());
+    }
+    if plugins.is_empty() {
+        outln!(io, "{}", tr!("plugin.none", PREFIX));
+        return Ok(());
+    }
+    for (plugin, manifest) in plugins.iter().zip(manifests) {
+        let description = match manifest {
+            Some(manifest) if !manifest.description.is_empty() => manifest.description,
+            _ => plugin.path.display().to_string(),
+        };
+        outln!(io, "{}{:<12} {}", style::icon(&io.stdout, "🔌"), plugin.name, description);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[cfg(unix)]
+    fn script(dir: &TempDir, name: &str, body: &str) -> Plugin {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.file(&format!("{}{}", PREFIX, name), format!("#!/bin/sh\n{}\n", body));
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        Plugin { name: name.to_string(), path }
+    }
+
+    #[test]
+    fn builtins_cannot_be_plugins() {
+        for name in ["", "ls", "a/b", "a\\b", "a'b", "a b", "a;b", "$(x)", "名字"] {
+            assert!(find(name).is_none(), "{}", name);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_names_unsafe_for_scripts() {
+        let dir = TempDir::new();
+        for name in ["hello_2-x", "a'b", "a b", "a;b", "名字"] {
+            script(&dir, name, "exit 0");
+        }
+        let path = [("PATH".into(), dir.path().as_os_str().to_owned())].into_iter().collect();
+        let names: Vec<String> = config::with_environment(std::sync::Arc::new(path), || discover().into_iter().map(|plugin| plugin.name).collect());
+        assert_eq!(names, ["hello_2-x"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn describes_plugins() {
+        let dir = TempDir::new();
+        let hello = script(&dir, "hello", r#"[ "$1" = --catshell-describe ] && echo '{"protocol": 1, "description": "问候"}'"#);
+        let manifest = hello.describe().unwrap();
+        assert_eq!((manifest.protocol, manifest.description.as_str()), (1, "问候"));
+        let json = hello.to_json();
+        assert_eq!(json.get("description").and_then(Value::as_str), Some("问候"));
+
+        assert!(script(&dir, "plain", "echo not json").describe().is_none());
+        assert!(script(&dir, "failing", "echo '{\"protocol\": 1}'; exit 1").describe().is_none());
+        assert!(script(&dir, "old", "echo '{\"description\": \"x\"}'").describe().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn describes_plugins_in_parallel() {
+        let dir = TempDir::new();
+        let plugins: Vec<Plugin> = ["a", "b", "c"]
+            .iter()
+            .map(|name| script(&dir, name, &format!(r#"sleep 1; echo '{{"protocol": 1, "description": "{}"}}'"#, name)))
+            .collect();
+        let start = Instant::now();
+        let descriptions: Vec<String> = describe_all(&plugins).into_iter().map(|manifest| manifest.unwrap().description).collect();
+        assert_eq!(descriptions, ["a", "b", "c"]);
+        assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn passes_protocol_environment() {
+        let dir = TempDir::new();
+        let env = script(&dir, "env", r#"echo "$CATSHELL_PLUGIN_PROTOCOL $CATSHELL_OUTPUT $*""#);
+        let output = env.command(&["a".to_string(), "b".to_string()], true).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "1 json a b\n");
+        let output = env.command(&[], false).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "1 text \n");
+    }
+}
@@ -0,0 +1,192 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::streams::Output;
+use crate::{config, pwd, style};
+
+/// 没有设置 `PROMPT` 时使用的提示符格式
+pub const DEFAULT_FORMAT: &str = "{green}cat@shell{reset}:{blue}{cwd}{reset} {yellow}{git_branch}{reset} {red}{status}{reset}$ ";
+
+/// 按格式字符串生成提示符。支持的占位符：
+///
+/// - `{user}`、`{host}`：用户名和主机名
+/// - `{cwd}`：当前目录，主目录显示为 `~`；`{cwd:short}` 只显示最后一级目录
+/// - `{git_branch}`：当前git分支，不在git仓库中时为空
+/// - `{status}`：上一条命令失败时显示退出状态码，成功时为空
+/// - `{red}`、`{green}`、`{yellow}`、`{blue}`、`{magenta}`、`{cyan}`、`{white}`、`{bold}`、`{reset}`：颜色，和命令输出一样遵循 `--color` 和 `NO_COLOR`
+///
+/// 占位符的内容为空时，它前面的一个空格也会被去掉。`{{` 和 `}}` 表示花括号本身
+pub fn render(format: &str, status: i32) -> String {
+    let color = style::color(&Output::Stdout);
+    let mut prompt = String::new();
+    // 最近输出的普通字符如果是空格，记录它的位置
+    let mut last_space = None;
+    let mut rest = format;
+
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            prompt.push(c);
+            last_space = None;
+            rest = &rest[2..];
+            continue;
+        }
+
+        if c == '{' {
+            if let Some(end) = rest.find('}') {
+                let placeholder = &rest[1..end];
+                rest = &rest[end + 1..];
+                if let Some(code) = color_code(placeholder) {
+                    if color {
+                        prompt.push_str(code);
+                    }
+                    continue;
+                }
+                match segment(placeholder, status) {
+                    Some(value) if value.is_empty() => {
+                        if let Some(index) = last_space.take() {
+                            prompt.remove(index);
+                        }
+                    },
+                    Some(value) => {
+                        prompt.push_str(&value);
+                        last_space = None;
+                    },
+                    // 无法识别的占位符原样显示
+                    None => {
+                        prompt.push_str(&format!("{{{}}}", placeholder));
+                        last_space = None;
+                    }
+                }
+                continue;
+            }
+        }
+
+        last_space = (c == ' ').then_some(prompt.len());
+        prompt.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    prompt
+}
+
+fn color_code(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "red" => "\x1b[31m",
+        "green" => "\x1b[32m",
+        "yellow" => "\x1b[33m",
+        "blue" => "\x1b[34m",
+        "magenta" => "\x1b[35m",
+        "cyan" => "\x1b[36m",
+        "white" => "\x1b[37m",
+        "bold" => "\x1b[1m",
+        "reset" => "\x1b[0m",
+        _ => return None,
+    })
+}
+
+fn segment(placeholder: &str, status: i32) -> Option<String> {
+    Some(match placeholder {
+        "user" => config::var("USER").or_else(|| config::var("USERNAME")).unwrap_or_default(),
+        "host" => hostname(),
+        "cwd" => current_dir(false),
+        "cwd:short" => current_dir(true),
+        "git_branch" => env::current_dir().ok().and_then(|dir| git_branch(&dir)).unwrap_or_default(),
+        "status" if status == 0 => String::new(),
+        "status" => format!("✘{}", status),
+        _ => return None,
+    })
+}
+
+fn hostname() -> String {
+    config::var("HOSTNAME")
+        .or_else(|| config::var("COMPUTERNAME"))
+        .or_else(|| fs::read_to_string("/etc/hostname").ok().map(|name| name.trim().to_string()))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+/// 当前目录，主目录及其子目录用 `~` 表示，和 `cd ~` 使用同一个主目录
+fn current_dir(short: bool) -> String {
+    let Ok(dir) = env::current_dir() else { return pwd::get_current_directory() };
+    let home = config::home_dir().and_then(|home| home.canonicalize().ok());
+
+    if let Some(relative) = home.as_ref().and_then(|home| dir.strip_prefix(home).ok()) {
+        if relative.as_os_str().is_empty() {
+            return "~".to_string();
+        }
+        if !short {
+            return Path::new("~").join(relative).display().to_string();
+        }
+    }
+
+    if short {
+        if let Some(name) = dir.file_name() {
+            return name.to_string_lossy().into_owned();
+        }
+    }
+    dir.display().to_string()
+}
+
+/// 从 `dir` 开始向上查找git仓库，直接读取 `.git/HEAD` 得到当前分支。
+/// 分离头指针时返回提交的前7位
+pub fn git_branch(dir: &Path) -> Option<String> {
+    let git_dir = find_git_dir(dir)?;
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(reference.strip_prefix("refs/heads/").unwrap_or(reference).to_string()),
+        None => Some(head.chars().take(7).collect()),
+    }
+}
+
+/// 找到包含 `dir` 的仓库的git目录。工作树和子模块中的 `.git` 是一个指向真正git目录的文件
+pub fn find_git_dir(dir: &Path) -> Option<PathBuf> {
+    for ancestor in dir.ancestors() {
+        let git = ancestor.join(".git");
+        if git.is_dir() {
+            return Some(git);
+        }
+        if git.is_file() {
+            let content = fs::read_to_string(&git).ok()?;
+            let target = content.trim().strip_prefix("gitdir:")?.trim();
+            return Some(ancestor.join(target));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::{with_style, When};
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn renders_placeholders() {
+        let plain = |format: &str, status: i32| with_style(None, Some(When::Never), || render(format, status));
+        assert_eq!(plain("{green}$ {status}> ", 0), "$> ");
+        assert_eq!(plain("{green}$ {status}> ", 2), "$ ✘2> ");
+        assert_eq!(plain("{{x}} {unknown} {", 0), "{x} {unknown} {");
+        assert_eq!(
+            with_style(None, Some(When::Always), || render("{red}x{reset}", 0)),
+            "\x1b[31mx\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn reads_git_branch() {
+        let dir = TempDir::new();
+        dir.file("repo/.git/HEAD", "ref: refs/heads/main\n");
+        dir.file("repo/src/lib.rs", "");
+        assert_eq!(git_branch(&dir.path().join("repo/src")).as_deref(), Some("main"));
+
+        dir.file("detached/.git/HEAD", "0123456789abcdef\n");
+        assert_eq!(git_branch(&dir.path().join("detached")).as_deref(), Some("0123456"));
+
+        // 工作树中的 `.git` 文件指向真正的git目录
+        dir.file("worktree/.git", "gitdir: ../repo/.git\n");
+        assert_eq!(find_git_dir(&dir.path().join("worktree")), Some(dir.path().join("worktree/../repo/.git")));
+        assert_eq!(git_branch(&dir.path().join("worktree")).as_deref(), Some("main"));
+    }
+}
@@ -1,16 +1,23 @@
 use std::env;
+use std::io::Write;
+
+use crate::error::{CatError, Result};
+use crate::json::Value;
+use crate::streams::Streams;
+use crate::style;
 
 /// 实现pwd命令功能，显示当前工作目录
-pub fn print_working_directory() {
-    if let Ok(path) = env::current_dir() {
-        if let Some(path_str) = path.to_str() {
-            println!("{} 位于: {}", "📍", path_str);
-        } else {
-            eprintln!("❌ 无法将当前目录转换为字符串");
-        }
+pub fn print_working_directory(json: bool, io: &mut Streams) -> Result<()> {
+    let path = env::current_dir().map_err(|_| CatError::Failed(tr!("pwd.current_dir_failed")))?;
+    let Some(path_str) = path.to_str() else {
+        return Err(CatError::Failed(tr!("pwd.not_utf8")));
+    };
+    if json {
+        outln!(io, "{}", Value::object([("path", path_str.into())]));
     } else {
-        eprintln!("❌ 无法获取当前工作目录");
+        outln!(io, "{}{}", style::icon(&io.stdout, "📍"), tr!("pwd.location", path_str));
     }
+    Ok(())
 }
 
 /// 获取当前目录路径字符串，用于TUI显示
@@ -20,5 +27,5 @@ pub fn get_current_directory() -> String {
             return path_str.to_string();
         }
     }
-    "未知目录".to_string()
+    tr!("pwd.unknown")
 }
\ No newline at end of file
@@ -1,19 +1,31 @@
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
+use crate::error::{CatError, Result};
+use crate::json::Value;
+use crate::streams::Streams;
+use crate::style;
+
 /// 实现rm命令功能，删除文件或目录
 /// 
 /// # 参数
 /// - `paths`: 要删除的文件或目录路径列表
 /// - `recursive`: 是否递归删除目录及其内容
 /// - `force`: 是否强制删除，忽略不存在的文件和权限错误
-pub fn remove_files(paths: &[&str], recursive: bool, force: bool) { 
+/// - `json`: 不逐个显示结果，最后输出删除成功的路径列表
+/// - `io`: 命令的输入输出流
+///
+/// 某个路径删除失败时继续删除其他路径，最后返回所有失败。`force` 时不存在的文件不算失败
+pub fn remove_files(paths: &[&str], recursive: bool, force: bool, json: bool, io: &mut Streams) -> Result<()> {
+    let mut removed = Vec::new();
+    let mut errors = Vec::new();
     for path_str in paths {
         let path = Path::new(path_str);
         
         if !path.exists() {
             if !force {
-                eprintln!("❌ Error: '{}' does not exist", path_str);
+                errors.push(CatError::Failed(tr!("rm.not_found", path_str)));
             }
             continue;
         }
@@ -22,36 +34,39 @@ pub fn remove_files(paths: &[&str], recursive: bool, force: bool) {
             // 删除文件
             if let Err(err) = fs::remove_file(path) {
                 if !force {
-                    eprintln!("❌ Error removing file '{}': {}", path_str, err);
-                }
-            } else {
-                if !force {
-                    println!("✅ Removed file '{}'", path_str);
+                    errors.push(CatError::Failed(tr!("rm.file_failed", path_str, err)));
                 }
+            } else if json {
+                removed.push(*path_str);
+            } else if !force {
+                outln!(io, "{}{}", style::icon(&io.stdout, "✅"), tr!("rm.removed_file", path_str));
             }
         } else if path.is_dir() {
             // 删除目录
             if recursive {
                 if let Err(err) = fs::remove_dir_all(path) {
                     if !force {
-                        eprintln!("❌ Error removing directory '{}': {}", path_str, err);
-                    }
-                } else {
-                    if !force {
-                        println!("✅ Removed directory '{}' and all its contents", path_str);
+                        errors.push(CatError::Failed(tr!("rm.dir_failed", path_str, err)));
                     }
+                } else if json {
+                    removed.push(*path_str);
+                } else if !force {
+                    outln!(io, "{}{}", style::icon(&io.stdout, "✅"), tr!("rm.removed_tree", path_str));
                 }
             } else {
                 // 非递归模式下，目录必须为空才能删除
                 if let Err(err) = fs::remove_dir(path) {
-                    eprintln!("❌ Error removing directory '{}': {}", path_str, err);
-                    eprintln!("Use -r or --recursive to remove non-empty directories");
-                } else {
-                    if !force {
-                        println!("✅ Removed directory '{}'", path_str);
-                    }
+                    errors.push(CatError::Failed(tr!("rm.dir_not_empty", path_str, err)));
+                } else if json {
+                    removed.push(*path_str);
+                } else if !force {
+                    outln!(io, "{}{}", style::icon(&io.stdout, "✅"), tr!("rm.removed_dir", path_str));
                 }
             }
         }
     }
+    if json {
+        outln!(io, "{}", Value::object([("removed", removed.into())]));
+    }
+    CatError::collect(errors)
 }
\ No newline at end of file
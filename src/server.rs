@@ -1,58 +1,121 @@
 use std::fs::File;
-use std::io::{Read, Write};
-use std::net::{TcpListener, TcpStream};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
 use std::thread;
+use std::time::Duration;
 
-// 启动HTTP服务器的函数
-pub fn start_server(path: Option<String>, port: u16) {
-    let directory = path.as_deref().unwrap_or(".");
-    let dir_path = Path::new(directory);
-    
-    // 检查目录是否存在
-    if !dir_path.exists() || !dir_path.is_dir() {
-        eprintln!("错误: 目录 '{}' 不存在或不是一个有效目录", directory);
-        return;
-    }
-    
-    let address = format!("127.0.0.1:{}", port);
-    let listener = match TcpListener::bind(&address) {
-        Ok(listener) => listener,
-        Err(err) => {
-            eprintln!("无法绑定到地址 {}: {}", address, err);
-            return;
+use crate::error::{CatError, Result};
+use crate::i18n;
+use crate::json::Value;
+use crate::signal;
+use crate::streams::Streams;
+use crate::style;
+
+/// 提供静态文件的HTTP服务器，只监听本机地址
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    root: PathBuf,
+}
+
+impl Server {
+    /// 在 `127.0.0.1:port` 上监听，`port` 为0时由系统分配端口，可以用 [`Server::local_addr`] 查询
+    pub fn bind(root: impl Into<PathBuf>, port: u16) -> Result<Server> {
+        let root = root.into();
+        
+        // 检查目录是否存在
+        if !root.is_dir() {
+            return Err(CatError::Failed(tr!("server.bad_root", root.display())));
         }
-    };
-    
-    println!("正在启动HTTP服务器，服务目录: {}", dir_path.display());
-    println!("服务器将在 http://localhost:{}/ 上运行", port);
-    println!("注意：此实现无法通过Ctrl+C停止，请手动关闭终端窗口来停止服务器");
-    
-    // 接受连接并处理请求
-    for stream in listener.incoming() {
-        match stream {
-            Ok(stream) => {
-                let dir_path = dir_path.to_path_buf();
-                thread::spawn(move || {
-                    handle_client(stream, &dir_path);
-                });
-            }
+        
+        let address = format!("127.0.0.1:{}", port);
+        let listener = match TcpListener::bind(&address) {
+            Ok(listener) => listener,
             Err(err) => {
-                eprintln!("接受连接失败: {}", err);
+                return Err(CatError::Failed(tr!("server.bind_failed", address, err)));
+            }
+        };
+
+        // 使用非阻塞的监听，这样可以定期检查是否需要停止
+        if let Err(err) = listener.set_nonblocking(true) {
+            return Err(CatError::Failed(tr!("server.nonblocking_failed", err)));
+        }
+        Ok(Server { listener, root })
+    }
+
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// 服务的根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 接受连接并处理请求，直到 `should_stop` 返回真。每个连接在单独的线程中处理，
+    /// 接受连接或读取请求失败时服务器继续运行，错误交给 `on_error` 在调用 `run` 的线程中处理
+    pub fn run(&self, should_stop: impl Fn() -> bool, mut on_error: impl FnMut(CatError)) -> Result<()> {
+        let (errors, failures) = mpsc::channel();
+        while !should_stop() {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    // 在Windows上接受的连接会继承非阻塞模式
+                    let _ = stream.set_nonblocking(false);
+                    let root = self.root.clone();
+                    let lang = i18n::lang();
+                    let errors = errors.clone();
+                    thread::spawn(move || {
+                        i18n::set_lang(Some(lang));
+                        if let Err(err) = handle_client(stream, &root) {
+                            let _ = errors.send(err);
+                        }
+                    });
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(100));
+                }
+                Err(err) => on_error(CatError::Failed(tr!("server.accept_failed", err))),
             }
+            failures.try_iter().for_each(&mut on_error);
         }
+        Ok(())
     }
 }
 
-// 处理客户端请求
-fn handle_client(mut stream: TcpStream, root_dir: &Path) {
+// 启动HTTP服务器的函数，目录无效或端口无法绑定时返回错误。
+// json时只在启动后输出一行包含地址的对象，方便脚本读取系统分配的端口
+pub fn start_server(path: Option<String>, port: u16, json: bool, io: &mut Streams) -> Result<()> {
+    let server = Server::bind(path.as_deref().unwrap_or("."), port)?;
+    let port = server.local_addr()?.port();
+    
+    if json {
+        outln!(io, "{}", Value::object([
+            ("url", format!("http://localhost:{}/", port).into()),
+            ("root", server.root().display().to_string().into()),
+            ("port", port.into()),
+        ]));
+        let _ = io.stdout.flush();
+        return server.run(signal::interrupted, |err| err.report_json(io));
+    }
+    
+    outln!(io, "{}", tr!("server.starting", server.root().display()));
+    outln!(io, "{}", tr!("server.url", port));
+    outln!(io, "{}", tr!("server.stop_hint"));
+
+    server.run(signal::interrupted, |err| err.report(io))?;
+    outln!(io, "{}{}", style::icon(&io.stdout, "🛑"), tr!("server.stopped"));
+    Ok(())
+}
+
+// 处理客户端请求，读取请求失败时返回错误
+fn handle_client(mut stream: TcpStream, root_dir: &Path) -> Result<()> {
     let mut buffer = [0; 1024];
     let bytes_read = match stream.read(&mut buffer) {
         Ok(n) => n,
-        Err(err) => {
-            eprintln!("读取请求失败: {}", err);
-            return;
-        }
+        Err(err) => return Err(CatError::Failed(tr!("server.read_failed", err))),
     };
     
     let request = String::from_utf8_lossy(&buffer[..bytes_read]);
@@ -72,6 +135,7 @@ fn handle_client(mut stream: TcpStream, root_dir: &Path) {
     } else {
         send_404(&mut stream);
     }
+    Ok(())
 }
 
 // 构建文件路径
@@ -138,22 +202,24 @@ fn send_file(stream: &mut TcpStream, file_path: &Path) {
 
 // 发送404响应
 fn send_404(stream: &mut TcpStream) {
-    let response = "HTTP/1.1 404 Not Found\r\nContent-Type: text/html\r\n\r\n"
-        .to_string() + 
-        "<html><body><h1>404 Not Found</h1><p>请求的资源不存在</p></body></html>";
+    let response = format!(
+        "HTTP/1.1 404 Not Found\r\nContent-Type: text/html; charset=utf-8\r\n\r\n<html><body><h1>404 Not Found</h1><p>{}</p></body></html>",
+        tr!("server.not_found")
+    );
     stream.write_all(response.as_bytes()).ok();
 }
 
 // 发送500响应
 fn send_500(stream: &mut TcpStream) {
-    let response = "HTTP/1.1 500 Internal Server Error\r\nContent-Type: text/html\r\n\r\n"
-        .to_string() + 
-        "<html><body><h1>500 Internal Server Error</h1><p>服务器内部错误</p></body></html>";
+    let response = format!(
+        "HTTP/1.1 500 Internal Server Error\r\nContent-Type: text/html; charset=utf-8\r\n\r\n<html><body><h1>500 Internal Server Error</h1><p>{}</p></body></html>",
+        tr!("server.internal_error")
+    );
     stream.write_all(response.as_bytes()).ok();
 }
 
-// 获取文件的MIME类型
-fn get_content_type(path: &Path) -> &str {
+// 获取文件的MIME类型，ls也用它识别图片
+pub fn get_content_type(path: &Path) -> &str {
     if let Some(extension) = path.extension() {
         if let Some(ext_str) = extension.to_str() {
             match ext_str.to_lowercase().as_str() {
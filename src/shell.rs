@@ -0,0 +1,1622 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Cursor, PipeWriter, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use clap::CommandFactory;
+
+use crate::completion;
+use crate::condition;
+use crate::config::{self, Environment};
+use crate::editor::{self, ReadLine};
+use crate::error::{self, CatError};
+use crate::expand;
+use crate::history::History;
+use crate::i18n;
+use crate::jobs::{self, Jobs, Signal};
+use crate::parser::{self, AndOr, Connector, List, ParseError, Pipeline, Redirect, RedirectKind, Stage};
+use crate::plugin;
+use crate::streams::{Input, Output, Streams};
+use crate::prompt;
+use crate::signal;
+use crate::style;
+use crate::which;
+use crate::cli::{self, execute, Cli, Commands};
+
+/// 由shell自身实现的命令，它们需要修改shell的状态，因此不在 `Commands` 中
+const SHELL_BUILTINS: &[&str] = &[
+    "exit", "quit", "export", "unset", "echo", "set", "shift", "true", "false", "test", "[", "break",
+    "continue", "return", "history", "alias", "unalias", "which", "jobs", "fg", "bg", "kill",
+];
+
+/// 不能在后台执行的 `Commands` 命令：`cd` 会改变shell自己的工作目录，其余需要读取终端输入
+const FOREGROUND_ONLY: &[&str] = &["cd", "vim", "tui"];
+
+/// `break`、`continue` 和 `return` 引起的控制流跳转
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    Break,
+    Continue,
+    Return,
+}
+
+/// shell的状态，在多条命令之间保留
+pub struct Shell {
+    /// 未导出的shell变量，`export` 之后移到 `env` 中
+    vars: HashMap<String, String>,
+    /// 导出的环境变量，启动时复制自进程的环境变量。`Commands` 中的命令通过 [`config::var`] 读取，
+    /// 外部程序启动时只传入这些变量
+    env: Environment,
+    /// 已定义的函数
+    functions: HashMap<String, Rc<List>>,
+    /// `alias` 定义的别名，在解析命令时展开
+    aliases: HashMap<String, String>,
+    /// 位置参数，`$0` 是脚本名，`$1` 开始是参数
+    positional: Vec<String>,
+    /// 上一条命令的退出状态，对应 `$?`
+    pub last_status: i32,
+    /// 执行 `exit` 后记录的退出状态码
+    exit_code: Option<i32>,
+    /// `set -e`：命令失败时立即退出
+    errexit: bool,
+    /// 正在计算的 if/while 条件层数，条件中的失败不会触发 `set -e`
+    condition_depth: usize,
+    loop_depth: usize,
+    function_depth: usize,
+    control: Option<Control>,
+    /// 命令替换、函数和脚本中最后一个管道阶段的输出目标，为空时输出到终端
+    captures: Vec<Capture>,
+    /// 命令历史，只有交互模式会从文件加载
+    pub history: History,
+    /// `&` 启动的后台任务
+    jobs: Jobs,
+    /// 最近一个后台外部程序的进程号，对应 `$!`
+    pub last_background: Option<u32>,
+    /// 交互模式下启动后台任务时显示任务号
+    interactive: bool,
+}
+
+/// 启动交互式REPL，所有命令在同一进程内执行，工作目录等状态会保留。
+/// 返回shell的退出状态码
+pub fn run_repl() -> i32 {
+    println!("{}{}", style::icon(&Output::Stdout, "🐱"), tr!("shell.welcome"));
+
+    // 交互模式下 Ctrl+C 只中断当前命令，不会退出shell
+    signal::install();
+    let mut shell = Shell::new("catshell", &[]);
+    shell.interactive = true;
+    shell.history = History::load();
+    shell.load_rc();
+    if let Some(code) = shell.exit_code {
+        return code;
+    }
+    // 多行输入（例如没有写完的 if）会累积在这里
+    let mut source = String::new();
+
+    loop {
+        // 和bash一样在显示提示符之前报告已经结束的后台任务
+        if source.is_empty() {
+            for job in shell.jobs.remove_finished() {
+                println!("{}", job.describe(false));
+            }
+        }
+        let prompt = if source.is_empty() { shell.prompt() } else { "> ".to_string() };
+        // Tab补全按shell导出的 `PATH` 查找程序
+        let line = match config::with_environment(shell.env.clone(), || editor::read_line(&prompt, &shell)) {
+            Ok(ReadLine::Line(line)) => line,
+            // Ctrl+C 丢弃当前输入，包括没有写完的多行命令
+            Ok(ReadLine::Interrupted) => {
+                source.clear();
+                shell.last_status = 130;
+                continue;
+            },
+            // 读到EOF（Ctrl+D / Ctrl+Z）时退出
+            Ok(ReadLine::Eof) => {
+                if !source.is_empty() {
+                    failln!(Streams::inherit(), "{}", ParseError::Incomplete);
+                }
+                println!();
+                break;
+            },
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", tr!("shell.read_failed", err));
+                break;
+            }
+        };
+
+        // 展开 `!!` 和 `!n`，并像bash一样显示展开后的命令
+        let line = match shell.history.expand(&line) {
+            Ok(Some(expanded)) => {
+                println!("{}", expanded);
+                expanded
+            },
+            Ok(None) => line,
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", err);
+                shell.last_status = 1;
+                continue;
+            }
+        };
+        shell.history.add(&line);
+
+        source.push_str(&line);
+        source.push('\n');
+        signal::clear();
+        match shell.run_source(&source) {
+            Err(ParseError::Incomplete) => continue,
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", err);
+                shell.last_status = 2;
+            },
+            Ok(()) => {},
+        }
+        source.clear();
+
+        // 被 Ctrl+C 中断的命令，终端上的 ^C 后面还没有换行
+        if signal::interrupted() {
+            println!();
+            if shell.last_status == 0 {
+                shell.last_status = 130;
+            }
+        }
+
+        if shell.exit_code.is_some() {
+            break;
+        }
+    }
+
+    println!("{}{}", style::icon(&Output::Stdout, "👋"), tr!("shell.bye"));
+    shell.exit_code.unwrap_or(shell.last_status)
+}
+
+/// 执行脚本文件，返回脚本的退出状态。脚本在新的shell中执行，不会继承当前shell的变量和函数
+pub fn run_script(path: &str, args: &[String], io: &mut Streams) -> i32 {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            failln!(io, "{}", tr!("shell.read_script_failed", path, err));
+            return 127;
+        }
+    };
+    let list = match parser::parse(&source) {
+        Ok(list) => list,
+        Err(err) => {
+            failln!(io, "{}: {}", path, err);
+            return 2;
+        }
+    };
+
+    let mut shell = Shell::new(path, args);
+    match Capture::to(&io.stdout) {
+        Ok(capture) => shell.captures.push(capture),
+        Err(err) => {
+            failln!(io, "{}", tr!("shell.pipe_failed", err));
+            return 1;
+        }
+    }
+
+    let status = shell.run_list(&list);
+
+    if let Some(capture) = shell.captures.pop() {
+        let _ = io.stdout.write_all(&capture.finish());
+    }
+    shell.exit_code.unwrap_or(status)
+}
+
+impl Shell {
+    pub fn new(name: &str, args: &[String]) -> Self {
+        let mut positional = vec![name.to_string()];
+        positional.extend_from_slice(args);
+
+        Shell {
+            vars: HashMap::new(),
+            env: config::environment(),
+            functions: HashMap::new(),
+            aliases: HashMap::new(),
+            positional,
+            last_status: 0,
+            exit_code: None,
+            errexit: false,
+            condition_depth: 0,
+            loop_depth: 0,
+            function_depth: 0,
+            control: None,
+            captures: Vec::new(),
+            history: History::default(),
+            jobs: Jobs::default(),
+            last_background: None,
+            interactive: false,
+        }
+    }
+
+    /// 在当前shell中执行 `~/.catshellrc`，可以在其中定义别名、函数、环境变量和提示符。文件不存在时什么也不做
+    fn load_rc(&mut self) {
+        let Some(path) = config::home_dir().map(|home| home.join(".catshellrc")) else { return };
+        let Ok(source) = fs::read_to_string(&path) else { return };
+
+        if let Err(err) = self.run_source(&source) {
+            failln!(Streams::inherit(), "{}: {}", path.display(), err);
+        }
+    }
+
+    /// 交互模式的提示符。设置了 `PROMPT` 变量时用它作为格式字符串，其中的 `$VAR` 和 `$(...)` 每次显示时展开，
+    /// 占位符见 [`prompt::render`]
+    fn prompt(&mut self) -> String {
+        // 提示符中的命令替换不能改变 `$?`
+        let status = self.last_status;
+        let format = match self.get_var("PROMPT") {
+            Some(format) => expand::expand_assignment(&format, self),
+            None => prompt::DEFAULT_FORMAT.to_string(),
+        };
+        self.last_status = status;
+        config::with_environment(self.env.clone(), || prompt::render(&format, status))
+    }
+
+    /// 解析并执行一段源代码，结果记录在 `last_status` 中。解析失败时不会执行任何命令
+    pub fn run_source(&mut self, source: &str) -> Result<(), ParseError> {
+        let list = parser::parse_with_aliases(source, &self.aliases)?;
+        self.run_list(&list);
+        Ok(())
+    }
+
+    /// 执行命令替换 `$(...)`，返回去掉末尾换行的标准输出
+    pub fn capture(&mut self, source: &str) -> String {
+        let list = match parser::parse_with_aliases(source, &self.aliases) {
+            Ok(list) => list,
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", err);
+                self.last_status = 2;
+                return String::new();
+            }
+        };
+
+        let capture = match Capture::collect() {
+            Ok(capture) => capture,
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", tr!("shell.pipe_failed", err));
+                self.last_status = 1;
+                return String::new();
+            }
+        };
+
+        // 命令替换中的exit和break不会影响外面的shell
+        let (exit_code, control) = (self.exit_code, self.control);
+        self.captures.push(capture);
+        self.run_list(&list);
+        let bytes = self.captures.pop().map(Capture::finish).unwrap_or_default();
+        self.exit_code = exit_code;
+        self.control = control;
+
+        let text = String::from_utf8_lossy(&bytes);
+        text.trim_end_matches(['\n', '\r']).to_string()
+    }
+
+    /// 查找变量，数字名称表示位置参数，其余先找shell变量，再找环境变量
+    pub fn get_var(&self, name: &str) -> Option<String> {
+        if let Ok(index) = name.parse::<usize>() {
+            return self.positional.get(index).cloned();
+        }
+        self.vars.get(name).cloned().or_else(|| Some(self.env.get(OsStr::new(name))?.to_string_lossy().into_owned()))
+    }
+
+    /// 可以在命令位置使用的名称：子命令、shell内置命令、已定义的函数、别名、`PATH` 中的程序和插件，用于Tab补全
+    pub fn command_names(&self) -> Vec<String> {
+        let mut names = completion::subcommand_names();
+        names.push("help".to_string());
+        names.extend(SHELL_BUILTINS.iter().map(|name| name.to_string()));
+        names.extend(self.functions.keys().cloned());
+        names.extend(self.aliases.keys().cloned());
+        let programs = which::program_names();
+        names.extend(programs.iter().filter_map(|program| program.strip_prefix(plugin::PREFIX)).map(str::to_string));
+        names.extend(programs);
+        names
+    }
+
+    /// `$1` 开始的位置参数
+    pub fn args(&self) -> &[String] {
+        &self.positional[1..]
+    }
+
+    /// 遇到exit、break、continue、return或者按下 Ctrl+C 时停止执行后面的命令
+    fn should_stop(&self) -> bool {
+        self.exit_code.is_some() || self.control.is_some() || signal::interrupted()
+    }
+
+    /// 依次执行命令序列，返回最后一条命令的状态
+    fn run_list(&mut self, list: &List) -> i32 {
+        let mut status = 0;
+        for and_or in list {
+            status = if and_or.background { self.run_background(and_or) } else { self.run_and_or(and_or) };
+            if self.should_stop() {
+                break;
+            }
+        }
+        status
+    }
+
+    fn run_and_or(&mut self, and_or: &AndOr) -> i32 {
+        let mut status = self.run_command(&and_or.first);
+        // 只有链中最后一条命令失败时才触发 set -e，`a && b` 中a的失败不会退出
+        let mut last_ran = and_or.rest.is_empty();
+
+        for (index, (connector, command)) in and_or.rest.iter().enumerate() {
+            if self.should_stop() {
+                break;
+            }
+            self.last_status = status;
+
+            let run = match connector {
+                Connector::And => status == 0,
+                Connector::Or => status != 0,
+            };
+            if run {
+                status = self.run_command(command);
+                last_ran = index + 1 == and_or.rest.len();
+            }
+        }
+        self.last_status = status;
+
+        if self.errexit && status != 0 && last_ran && self.condition_depth == 0 && !self.should_stop() {
+            self.exit_code = Some(status);
+        }
+        status
+    }
+
+    /// 在后台执行以 `&` 结尾的命令，不等待它结束。只支持单个命令：`Commands` 中的命令在新线程中执行，
+    /// 外部程序在自己的进程组中启动。函数和shell自身的命令需要修改shell的状态，不能在后台执行。
+    /// 后台任务不会从终端读取输入
+    fn run_background(&mut self, and_or: &AndOr) -> i32 {
+        let stage = match and_or {
+            AndOr { first: parser::Command::Pipeline(pipeline), rest, .. } if rest.is_empty() && pipeline.stages.len() == 1 => {
+                &pipeline.stages[0]
+            },
+            _ => {
+                failln!(Streams::inherit(), "{}", tr!("shell.background_single"));
+                self.last_status = 1;
+                return 1;
+            }
+        };
+
+        let mut io = Streams::inherit();
+        let status = config::with_environment(self.env.clone(), || match self.apply_redirects(&stage.redirects, &mut io) {
+            Ok(()) => self.start_job(stage, io),
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", err);
+                1
+            }
+        });
+        self.last_status = status;
+        status
+    }
+
+    /// 启动后台任务，返回启动是否成功
+    fn start_job(&mut self, stage: &Stage, mut io: Streams) -> i32 {
+        let args = match self.expand_words(&stage.words) {
+            Ok(args) => args,
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", err);
+                return 1;
+            }
+        };
+        let Some(name) = args.first() else {
+            return 0;
+        };
+        if self.functions.contains_key(name)
+            || SHELL_BUILTINS.contains(&name.as_str())
+            || FOREGROUND_ONLY.contains(&name.as_str())
+        {
+            failln!(Streams::inherit(), "{}", tr!("shell.background_forbidden", name));
+            return 1;
+        }
+
+        let text = stage.words.join(" ");
+        let job = if is_builtin(name) {
+            let (command, cli) = match parse_builtin(&args, &mut io) {
+                Ok(parsed) => parsed,
+                Err(status) => return status,
+            };
+            if io.stdin.is_terminal() {
+                io.stdin = Input::from_bytes(Vec::new());
+            }
+            self.jobs.spawn_thread(text, move || cli.with_options(|| run_builtin(&command, cli.json, &mut io)))
+        } else {
+            match spawn_background(&args, io) {
+                Ok(child) => self.jobs.add_process(text, child),
+                Err(status) => return status,
+            }
+        };
+
+        if job.pid().is_some() {
+            self.last_background = job.pid();
+        }
+        if self.interactive {
+            match job.pid() {
+                Some(pid) => eprintln!("[{}] {}", job.id, pid),
+                None => eprintln!("[{}]", job.id),
+            }
+        }
+        0
+    }
+
+    fn run_command(&mut self, command: &parser::Command) -> i32 {
+        match command {
+            parser::Command::Pipeline(pipeline) => self.run_pipeline(pipeline),
+            parser::Command::If { branches, otherwise } => {
+                for (condition, body) in branches {
+                    let status = self.run_condition(condition);
+                    if self.should_stop() {
+                        return status;
+                    }
+                    if status == 0 {
+                        return self.run_list(body);
+                    }
+                }
+                match otherwise {
+                    Some(body) => self.run_list(body),
+                    None => 0,
+                }
+            },
+            parser::Command::For { var, words, body } => {
+                let values = match words {
+                    Some(words) => {
+                        let mut values = Vec::new();
+                        for word in words {
+                            match expand::expand_word(word, self) {
+                                Ok(expanded) => values.extend(expanded),
+                                Err(err) => {
+                                    failln!(Streams::inherit(), "{}", err);
+                                    return 1;
+                                }
+                            }
+                        }
+                        values
+                    },
+                    None => self.args().to_vec(),
+                };
+
+                let mut status = 0;
+                self.loop_depth += 1;
+                for value in values {
+                    self.vars.insert(var.clone(), value);
+                    status = self.run_list(body);
+                    if self.finish_iteration() {
+                        break;
+                    }
+                }
+                self.loop_depth -= 1;
+                status
+            },
+            parser::Command::While { condition, body } => {
+                let mut status = 0;
+                self.loop_depth += 1;
+                loop {
+                    if self.run_condition(condition) != 0 || self.should_stop() {
+                        break;
+                    }
+                    status = self.run_list(body);
+                    if self.finish_iteration() {
+                        break;
+                    }
+                }
+                self.loop_depth -= 1;
+                status
+            },
+            parser::Command::Function { name, body } => {
+                self.functions.insert(name.clone(), Rc::clone(body));
+                0
+            },
+            parser::Command::Group(body) => self.run_list(body),
+        }
+    }
+
+    /// 计算 if/while 的条件
+    fn run_condition(&mut self, condition: &List) -> i32 {
+        self.condition_depth += 1;
+        let status = self.run_list(condition);
+        self.condition_depth -= 1;
+        status
+    }
+
+    /// 处理循环体中的 break/continue，返回是否应该结束循环
+    fn finish_iteration(&mut self) -> bool {
+        match self.control {
+            Some(Control::Break) => {
+                self.control = None;
+                true
+            },
+            Some(Control::Continue) => {
+                self.control = None;
+                false
+            },
+            Some(Control::Return) => true,
+            None => self.exit_code.is_some(),
+        }
+    }
+
+    /// 执行管道，返回最后一个阶段的状态。最后一个阶段输出到 `captures` 的最后一个，没有时输出到终端。
+    /// 只有一个阶段时直接在当前线程中执行。有多个阶段时各阶段同时运行，通过管道逐块传递数据：
+    /// 外部程序和 `Commands` 中的命令在子进程或者单独的线程中执行，需要使用shell状态的阶段依次在当前线程中执行
+    /// 管道中的命令使用执行时shell导出的环境变量，其中的 `export` 对后面的命令生效
+    fn run_pipeline(&mut self, pipeline: &Pipeline) -> i32 {
+        config::with_environment(self.env.clone(), || self.run_pipeline_stages(pipeline))
+    }
+
+    fn run_pipeline_stages(&mut self, pipeline: &Pipeline) -> i32 {
+        let stdout = match self.stdout() {
+            Ok(stdout) => stdout,
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", tr!("shell.pipe_failed", err));
+                return 1;
+            }
+        };
+        if let [stage] = pipeline.stages.as_slice() {
+            let mut io = Streams { stdin: Input::Terminal, stdout, stderr: Output::Stderr };
+            let status = match self.apply_redirects(&stage.redirects, &mut io) {
+                Ok(()) => self.run_stage(stage, &mut io),
+                Err(err) => {
+                    failln!(Streams::inherit(), "{}", err);
+                    1
+                }
+            };
+            let _ = io.stdout.flush();
+            return status;
+        }
+
+        let pipes = match (1..pipeline.stages.len()).map(|_| io::pipe()).collect::<io::Result<Vec<_>>>() {
+            Ok(pipes) => pipes,
+            Err(err) => {
+                failln!(Streams::inherit(), "{}", tr!("shell.pipe_failed", err));
+                return 1;
+            }
+        };
+        let mut inputs = vec![Input::Terminal];
+        let mut outputs = Vec::new();
+        for (reader, writer) in pipes {
+            inputs.push(Input::Pipe(reader));
+            outputs.push(Output::Pipe(writer));
+        }
+        outputs.push(stdout);
+
+        // 先启动可以同时运行的阶段，其余的阶段记下来，等它们都启动以后再依次执行，
+        // 否则当前线程中的阶段写满管道以后，会一直等待还没有启动的下一个阶段
+        let mut stages = Vec::new();
+        let mut deferred = Vec::new();
+        for (stage, (stdin, stdout)) in pipeline.stages.iter().zip(inputs.into_iter().zip(outputs)) {
+            let mut io = Streams { stdin, stdout, stderr: Output::Stderr };
+            let args = match self.apply_redirects(&stage.redirects, &mut io) {
+                Ok(()) => self.expand_stage(stage, &mut io),
+                Err(err) => {
+                    failln!(Streams::inherit(), "{}", err);
+                    Err(1)
+                }
+            };
+            match args {
+                Ok(args) if self.runs_in_shell(&args) => {
+                    deferred.push((stages.len(), args, io));
+                    stages.push(Running::Done(0));
+                },
+                Ok(args) => stages.push(start_stage(&args, io)),
+                Err(status) => stages.push(Running::Done(status)),
+            }
+        }
+
+        let mut feeders = Vec::new();
+        let count = deferred.len();
+        for (position, (index, args, mut io)) in deferred.into_iter().enumerate() {
+            // 后面还有在当前线程中执行的阶段时，先把输出收集起来，再由单独的线程写入管道
+            let pipe = match io.stdout {
+                Output::Pipe(_) if position + 1 < count => Some(std::mem::replace(&mut io.stdout, Output::Buffer(Vec::new()))),
+                _ => None,
+            };
+            stages[index] = Running::Done(self.run_args(&args, &mut io));
+            let _ = io.stdout.flush();
+            if let Some(mut pipe) = pipe {
+                let bytes = io.stdout.into_bytes();
+                feeders.push(thread::spawn(move || {
+                    let _ = pipe.write_all(&bytes);
+                }));
+            }
+        }
+
+        // 管道的状态是最后一个阶段的状态，其余阶段也要等待结束
+        let mut status = 0;
+        for running in stages {
+            status = running.wait();
+        }
+        for feeder in feeders {
+            let _ = feeder.join();
+        }
+        status
+    }
+
+    /// 最后一个管道阶段的标准输出
+    fn stdout(&self) -> io::Result<Output> {
+        match self.captures.last() {
+            Some(capture) => capture.output(),
+            None => Ok(Output::Stdout),
+        }
+    }
+
+    /// 按顺序应用阶段上的重定向，后面的重定向覆盖前面的
+    fn apply_redirects(&mut self, redirects: &[Redirect], io: &mut Streams) -> Result<(), String> {
+        for redirect in redirects {
+            // 复制的是到这里为止的目标，`2>&1 > file` 中标准错误仍然是原来的标准输出
+            match redirect.kind {
+                RedirectKind::StderrToStdout => {
+                    io.stderr = io.stdout.try_clone().map_err(|err| tr!("shell.duplicate_failed", err))?;
+                    continue;
+                },
+                RedirectKind::StdoutToStderr => {
+                    io.stdout = io.stderr.try_clone().map_err(|err| tr!("shell.duplicate_failed", err))?;
+                    continue;
+                },
+                _ => {},
+            }
+
+            let target = match expand::expand_word(&redirect.target, self)?.as_slice() {
+                [target] => target.clone(),
+                _ => return Err(tr!("shell.ambiguous_redirect", redirect.target)),
+            };
+
+            match redirect.kind {
+                RedirectKind::Stdin => {
+                    let file = File::open(&target).map_err(|err| tr!("shell.read_file_failed", target, err))?;
+                    io.stdin = Input::File(file);
+                },
+                kind => {
+                    let append = matches!(kind, RedirectKind::StdoutAppend | RedirectKind::StderrAppend);
+                    let file = OpenOptions::new()
+                        .write(true)
+                        .create(true)
+                        .append(append)
+                        .truncate(!append)
+                        .open(&target)
+                        .map_err(|err| tr!("shell.open_failed", target, err))?;
+
+                    if matches!(kind, RedirectKind::Stderr | RedirectKind::StderrAppend) {
+                        io.stderr = Output::File(file);
+                    } else {
+                        io.stdout = Output::File(file);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 执行管道中的一个阶段，返回退出状态
+    fn run_stage(&mut self, stage: &Stage, io: &mut Streams) -> i32 {
+        match self.expand_stage(stage, io) {
+            Ok(args) => self.run_args(&args, io),
+            Err(status) => status,
+        }
+    }
+
+    /// 展开阶段中的单词。只由 `NAME=value` 组成的阶段是变量赋值，直接完成并返回 `Err(0)`，展开失败时返回 `Err(1)`
+    fn expand_stage(&mut self, stage: &Stage, io: &mut Streams) -> Result<Vec<String>, i32> {
+        if stage.words.iter().all(|word| split_assignment(word).is_some()) {
+            for word in &stage.words {
+                if let Some((name, value)) = split_assignment(word) {
+                    let value = expand::expand_assignment(value, self);
+                    self.vars.insert(name.to_string(), value);
+                }
+            }
+            return Err(0);
+        }
+
+        self.expand_words(&stage.words).map_err(|err| {
+            failln!(io, "{}", err);
+            1
+        })
+    }
+
+    /// 命令是否需要在shell所在的线程中执行：函数、shell自身的命令和 `FOREGROUND_ONLY` 中的命令
+    fn runs_in_shell(&self, args: &[String]) -> bool {
+        args.first().is_some_and(|name| {
+            self.functions.contains_key(name)
+                || SHELL_BUILTINS.contains(&name.as_str())
+                || FOREGROUND_ONLY.contains(&name.as_str())
+        })
+    }
+
+    /// 执行展开后的命令，返回退出状态。查找顺序是函数、shell自身的命令、
+    /// `Commands` 中的命令，其余作为外部程序启动
+    fn run_args(&mut self, args: &[String], io: &mut Streams) -> i32 {
+        let Some(name) = args.first() else {
+            return 0;
+        };
+
+        if let Some(body) = self.functions.get(name).cloned() {
+            return self.call_function(&body, args, io);
+        }
+        if SHELL_BUILTINS.contains(&name.as_str()) {
+            return self.run_shell_builtin(args, io);
+        }
+        if !is_builtin(name) {
+            return run_external(args, io);
+        }
+
+        match parse_builtin(args, io) {
+            Ok((command, cli)) => cli.with_options(|| run_builtin(&command, cli.json, io)),
+            Err(status) => status,
+        }
+    }
+
+    /// 展开命令的所有单词
+    fn expand_words(&mut self, words: &[String]) -> Result<Vec<String>, String> {
+        let mut args = Vec::new();
+        for word in words {
+            args.extend(expand::expand_word(word, self)?);
+        }
+        Ok(args)
+    }
+
+    /// 调用函数，参数作为函数内的位置参数
+    fn call_function(&mut self, body: &List, args: &[String], io: &mut Streams) -> i32 {
+        match Capture::to(&io.stdout) {
+            Ok(capture) => self.captures.push(capture),
+            Err(err) => {
+                failln!(io, "{}", tr!("shell.pipe_failed", err));
+                return 1;
+            }
+        }
+
+        let mut positional = vec![self.positional[0].clone()];
+        positional.extend_from_slice(&args[1..]);
+        let saved = std::mem::replace(&mut self.positional, positional);
+        self.function_depth += 1;
+
+        let status = self.run_list(body);
+        if self.control == Some(Control::Return) {
+            self.control = None;
+        }
+
+        self.function_depth -= 1;
+        self.positional = saved;
+
+        if let Some(capture) = self.captures.pop() {
+            let _ = io.stdout.write_all(&capture.finish());
+        }
+        status
+    }
+
+    /// `shift [N]`：去掉前N个位置参数，N默认为1。N不是数字或者超过参数个数时是用法错误，参数不变
+    fn shift(&mut self, count: Option<&String>) -> error::Result<()> {
+        let count = match count {
+            Some(arg) => arg.parse().map_err(|_| CatError::Usage(tr!("shell.numeric_arg", "shift", arg)))?,
+            None => 1,
+        };
+        if count > self.args().len() {
+            return Err(CatError::Usage(tr!("shell.shift_range", count)));
+        }
+        self.positional.drain(1..=count);
+        Ok(())
+    }
+
+    /// 执行shell自身实现的命令
+    fn run_shell_builtin(&mut self, args: &[String], io: &mut Streams) -> i32 {
+        match args[0].as_str() {
+            "exit" | "quit" => {
+                let code = match args.get(1) {
+                    Some(arg) => match arg.parse() {
+                        Ok(code) => code,
+                        Err(_) => {
+                            failln!(io, "{}", tr!("shell.numeric_arg", "exit", arg));
+                            2
+                        }
+                    },
+                    None => self.last_status,
+                };
+                self.exit_code = Some(code);
+                code
+            },
+            "export" => {
+                if args.len() == 1 {
+                    for (name, value) in self.env.iter() {
+                        outln!(io, "export {}=\"{}\"", name.to_string_lossy(), value.to_string_lossy());
+                    }
+                    return 0;
+                }
+
+                let mut status = 0;
+                for arg in &args[1..] {
+                    let (name, value) = match arg.split_once('=') {
+                        Some((name, value)) => (name, Some(value.to_string())),
+                        None => (arg.as_str(), None),
+                    };
+                    if !is_valid_name(name) {
+                        failln!(io, "{}", tr!("shell.invalid_name", name));
+                        status = 1;
+                        continue;
+                    }
+
+                    // `export NAME` 把已有的shell变量导出到环境变量
+                    let value = value.or_else(|| self.vars.get(name).cloned());
+                    self.vars.remove(name);
+                    if let Some(value) = value {
+                        Arc::make_mut(&mut self.env).insert(name.into(), value.into());
+                    }
+                }
+                status
+            },
+            "unset" => {
+                for name in &args[1..] {
+                    self.vars.remove(name);
+                    self.functions.remove(name);
+                    Arc::make_mut(&mut self.env).remove(OsStr::new(name));
+                }
+                0
+            },
+            "echo" => {
+                let newline = args.get(1).map(String::as_str) != Some("-n");
+                let words = if newline { &args[1..] } else { &args[2..] };
+                out!(io, "{}", words.join(" "));
+                if newline {
+                    outln!(io);
+                }
+                0
+            },
+            "set" => {
+                match args.get(1).map(String::as_str) {
+                    Some("-e") => self.errexit = true,
+                    Some("+e") => self.errexit = false,
+                    Some(option) => {
+                        failln!(io, "{}", tr!("shell.set_option", option));
+                        return 2;
+                    },
+                    None => {
+                        let mut vars: Vec<(&String, &String)> = self.vars.iter().collect();
+                        vars.sort();
+                        for (name, value) in vars {
+                            outln!(io, "{}={}", name, value);
+                        }
+                    }
+                }
+                0
+            },
+            "shift" => {
+                match self.shift(args.get(1)) {
+                    Ok(()) => 0,
+                    Err(err) => {
+                        err.report(io);
+                        err.exit_code()
+                    },
+                }
+            },
+            "true" => 0,
+            "false" => 1,
+            "test" | "[" => {
+                let mut operands = &args[1..];
+                if args[0] == "[" {
+                    match operands.split_last() {
+                        Some((last, rest)) if last == "]" => operands = rest,
+                        _ => {
+                            failln!(io, "{}", tr!("shell.missing_bracket"));
+                            return 2;
+                        }
+                    }
+                }
+                match condition::evaluate(operands) {
+                    Ok(true) => 0,
+                    Ok(false) => 1,
+                    Err(err) => {
+                        failln!(io, "{}: {}", args[0], err);
+                        2
+                    }
+                }
+            },
+            "break" | "continue" => {
+                if self.loop_depth == 0 {
+                    failln!(io, "{}", tr!("shell.loop_only", args[0]));
+                    return 1;
+                }
+                self.control = Some(if args[0] == "break" { Control::Break } else { Control::Continue });
+                0
+            },
+            "history" => {
+                let entries = self.history.entries();
+                let count = match args.get(1).map(String::as_str) {
+                    Some("-c") => {
+                        self.history.clear();
+                        return 0;
+                    },
+                    Some(arg) => match arg.parse() {
+                        Ok(count) => count,
+                        Err(_) => {
+                            failln!(io, "{}", tr!("shell.numeric_arg", "history", arg));
+                            return 2;
+                        }
+                    },
+                    None => entries.len(),
+                };
+
+                // 编号和 `!n` 使用的编号一致
+                let start = entries.len().saturating_sub(count);
+                for (index, entry) in entries.iter().enumerate().skip(start) {
+                    outln!(io, "{:>5}  {}", index + 1, entry);
+                }
+                0
+            },
+            "alias" => {
+                if args.len() == 1 {
+                    let mut aliases: Vec<(&String, &String)> = self.aliases.iter().collect();
+                    aliases.sort();
+                    for (name, value) in aliases {
+                        outln!(io, "alias {}='{}'", name, value);
+                    }
+                    return 0;
+                }
+
+                let mut status = 0;
+                for arg in &args[1..] {
+                    match arg.split_once('=') {
+                        Some((name, value)) if !name.is_empty() && !name.contains('/') => {
+                            self.aliases.insert(name.to_string(), value.to_string());
+                        },
+                        Some((name, _)) => {
+                            failln!(io, "{}", tr!("shell.invalid_alias", name));
+                            status = 1;
+                        },
+                        None => match self.aliases.get(arg) {
+                            Some(value) => outln!(io, "alias {}='{}'", arg, value),
+                            None => {
+                                failln!(io, "{}", tr!("shell.alias_not_found", "alias", arg));
+                                status = 1;
+                            }
+                        },
+                    }
+                }
+                status
+            },
+            "unalias" => {
+                if args.get(1).map(String::as_str) == Some("-a") {
+                    self.aliases.clear();
+                    return 0;
+                }
+
+                let mut status = 0;
+                for name in &args[1..] {
+                    if self.aliases.remove(name).is_none() {
+                        failln!(io, "{}", tr!("shell.alias_not_found", "unalias", name));
+                        status = 1;
+                    }
+                }
+                status
+            },
+            "which" => {
+                let mut status = 0;
+                for name in &args[1..] {
+                    if let Some(value) = self.aliases.get(name) {
+                        outln!(io, "{}", tr!("which.alias", name, value));
+                    } else if self.functions.contains_key(name) {
+                        outln!(io, "{}", tr!("which.function", name));
+                    } else if SHELL_BUILTINS.contains(&name.as_str()) || is_builtin(name) {
+                        outln!(io, "{}", tr!("which.builtin", name));
+                    } else if let Some(path) = which::find_program(name) {
+                        outln!(io, "{}", path.display());
+                    } else if let Some(plugin) = plugin::find(name) {
+                        outln!(io, "{}", tr!("which.plugin", name, plugin.path.display()));
+                    } else {
+                        failln!(io, "{}", tr!("which.not_found", name));
+                        status = 1;
+                    }
+                }
+                status
+            },
+            "jobs" => {
+                let show_pid = args.get(1).map(String::as_str) == Some("-l");
+                for job in self.jobs.list() {
+                    outln!(io, "{}", job.describe(show_pid));
+                }
+                // 已经显示过的结束任务不会在提示符前再报告一次
+                self.jobs.remove_finished();
+                0
+            },
+            "fg" => match self.jobs.find(args.get(1).map(String::as_str)) {
+                Ok(index) => {
+                    outln!(io, "{}", self.jobs.get(index).command);
+                    let _ = io.stdout.flush();
+                    self.jobs.foreground(index)
+                },
+                Err(err) => {
+                    failln!(io, "fg: {}", err);
+                    1
+                }
+            },
+            "bg" => {
+                let result = self.jobs.find(args.get(1).map(String::as_str)).and_then(|index| {
+                    self.jobs.resume(index)?;
+                    Ok(index)
+                });
+                match result {
+                    Ok(index) => {
+                        let job = self.jobs.get(index);
+                        outln!(io, "[{}]  {} &", job.id, job.command);
+                        0
+                    },
+                    Err(err) => {
+                        failln!(io, "bg: {}", err);
+                        1
+                    }
+                }
+            },
+            "kill" => {
+                let mut signal = Signal::Term;
+                let mut targets = &args[1..];
+                match targets.first().map(String::as_str) {
+                    Some("-l") => {
+                        for signal in Signal::ALL {
+                            outln!(io, "{:>2}) SIG{}", signal.number(), signal.name());
+                        }
+                        return 0;
+                    },
+                    Some(option) if option.starts_with('-') && option.len() > 1 => match Signal::parse(&option[1..]) {
+                        Some(parsed) => {
+                            signal = parsed;
+                            targets = &targets[1..];
+                        },
+                        None => {
+                            failln!(io, "{}", tr!("shell.kill_signal", &option[1..]));
+                            return 2;
+                        }
+                    },
+                    _ => {},
+                }
+                if targets.is_empty() {
+                    failln!(io, "{}", tr!("shell.kill_usage"));
+                    return 2;
+                }
+
+                let mut status = 0;
+                for target in targets {
+                    let result = if target.starts_with('%') {
+                        self.jobs.find(Some(target)).and_then(|index| self.jobs.signal(index, signal))
+                    } else {
+                        match target.parse() {
+                            Ok(pid) => jobs::kill_pid(pid, signal),
+                            Err(_) => Err(tr!("shell.kill_target", target)),
+                        }
+                    };
+                    if let Err(err) = result {
+                        failln!(io, "kill: {}", err);
+                        status = 1;
+                    }
+                }
+                status
+            },
+            "return" => {
+                if self.function_depth == 0 {
+                    failln!(io, "{}", tr!("shell.return_outside"));
+                    return 1;
+                }
+                self.control = Some(Control::Return);
+                args.get(1).and_then(|arg| arg.parse().ok()).unwrap_or(self.last_status)
+            },
+            _ => 127,
+        }
+    }
+}
+
+/// 复用clap的定义解析 `Commands` 中命令的参数，保证REPL和命令行的用法一致。
+/// 参数错误或者显示帮助时返回退出状态，否则返回命令和其余的全局选项（`--json`、`--lang` 等）
+fn parse_builtin(args: &[String], io: &mut Streams) -> Result<(Commands, Cli), i32> {
+    let argv = std::iter::once("catshell").chain(args.iter().map(String::as_str));
+    let parsed = i18n::with_lang(i18n::lang_arg(args), || {
+        cli::parse(argv)
+    });
+    match parsed {
+        Ok(mut cli) => match cli.command.take() {
+            Some(command) => Ok((command, cli)),
+            None => Err(0),
+        },
+        Err(err) => {
+            // help和version输出到标准输出，其余错误输出到标准错误
+            let text = err.render().to_string();
+            if err.use_stderr() {
+                let _ = write!(io.stderr, "{}", text);
+                Err(2)
+            } else {
+                out!(io, "{}", text);
+                Err(0)
+            }
+        }
+    }
+}
+
+/// 管道中已经启动的一个阶段
+enum Running {
+    /// 已经结束的阶段，记录退出状态
+    Done(i32),
+    /// 在单独线程中执行的 `Commands` 命令
+    Thread(JoinHandle<i32>),
+    /// 外部程序
+    Process(Process),
+}
+
+impl Running {
+    /// 等待阶段结束，返回退出状态
+    fn wait(self) -> i32 {
+        match self {
+            Running::Done(status) => status,
+            Running::Thread(handle) => handle.join().unwrap_or(1),
+            Running::Process(process) => process.wait(&mut Streams::inherit()),
+        }
+    }
+}
+
+/// 在多个阶段的管道中启动一个不需要shell状态的阶段：外部程序在子进程中启动，
+/// `Commands` 中的命令在单独的线程中执行，线程使用当前的语言、输出样式和环境变量
+fn start_stage(args: &[String], mut io: Streams) -> Running {
+    let Some(name) = args.first() else {
+        return Running::Done(0);
+    };
+    if !is_builtin(name) {
+        let Some(command) = find_command(args) else {
+            failln!(io, "{}", tr!("error.command_not_found", name));
+            return Running::Done(127);
+        };
+        return match spawn_process(command, name, &mut io) {
+            Ok(process) => Running::Process(process),
+            Err(status) => Running::Done(status),
+        };
+    }
+
+    match parse_builtin(args, &mut io) {
+        Ok((command, cli)) => {
+            let lang = i18n::lang();
+            let output_style = style::current();
+            let environment = config::environment();
+            Running::Thread(thread::spawn(move || {
+                i18n::set_lang(Some(lang));
+                style::set(output_style);
+                let status = config::with_environment(environment, || cli.with_options(|| run_builtin(&command, cli.json, &mut io)));
+                let _ = io.stdout.flush();
+                status
+            }))
+        },
+        Err(status) => Running::Done(status),
+    }
+}
+
+/// 命令替换、函数和脚本的输出目标，其中的命令的输出都写到这里
+enum Capture {
+    /// 复制的终端、文件或管道，直接写入
+    Stream(Output),
+    /// 通过管道收集到内存中，由单独的线程读取，输出再多也不会阻塞
+    Collect { writer: PipeWriter, reader: JoinHandle<Vec<u8>> },
+}
+
+impl Capture {
+    /// 把输出收集到内存中
+    fn collect() -> io::Result<Capture> {
+        let (mut reader, writer) = io::pipe()?;
+        let reader = thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _ = reader.read_to_end(&mut bytes);
+            bytes
+        });
+        Ok(Capture::Collect { writer, reader })
+    }
+
+    /// 输出到 `output`，不能复制的内存缓冲区先收集起来，由 [`finish`](Capture::finish) 返回
+    fn to(output: &Output) -> io::Result<Capture> {
+        match output.try_clone() {
+            Ok(output) => Ok(Capture::Stream(output)),
+            Err(_) => Capture::collect(),
+        }
+    }
+
+    /// 给一个命令使用的输出
+    fn output(&self) -> io::Result<Output> {
+        match self {
+            Capture::Stream(output) => output.try_clone(),
+            Capture::Collect { writer, .. } => writer.try_clone().map(Output::Pipe),
+        }
+    }
+
+    /// 结束收集，返回收集到的内容。所有使用它的命令都要已经结束，否则会一直等待
+    fn finish(self) -> Vec<u8> {
+        match self {
+            Capture::Stream(_) => Vec::new(),
+            Capture::Collect { writer, reader } => {
+                drop(writer);
+                reader.join().unwrap_or_default()
+            },
+        }
+    }
+}
+
+/// 执行 `Commands` 中的命令，返回退出状态
+fn run_builtin(command: &Commands, json: bool, io: &mut Streams) -> i32 {
+    match command {
+        Commands::Run { script, args } => run_script(script, args, io),
+        command => match execute(command, json, io) {
+            Ok(()) => 0,
+            Err(err) if json => {
+                err.report_json(io);
+                err.exit_code()
+            },
+            Err(err) => {
+                err.report(io);
+                err.exit_code()
+            }
+        },
+    }
+}
+
+/// 判断命令名是否是 `Commands` 中定义的子命令
+fn is_builtin(name: &str) -> bool {
+    name == "help" || Cli::command().get_subcommands().any(|sub| sub.get_name() == name)
+}
+
+/// 变量名只能由字母、数字和下划线组成，且不能以数字开头
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 把 `NAME=value` 形式的原始单词拆分成变量名和未展开的值
+fn split_assignment(word: &str) -> Option<(&str, &str)> {
+    let (name, value) = word.split_once('=')?;
+    is_valid_name(name).then_some((name, value))
+}
+
+/// 程序的退出状态码。Unix上被信号终止的程序和bash一样返回 128 加信号编号，例如 Ctrl+C 是130
+pub fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+/// 在 `PATH` 中查找外部程序，找不到时再查找同名的插件 `catshell-<name>`
+fn find_command(args: &[String]) -> Option<Command> {
+    if let Some(program) = which::find_program(&args[0]) {
+        let mut command = Command::new(program);
+        command.args(&args[1..]);
+        config::apply_environment(&mut command);
+        return Some(command);
+    }
+    let plugin = plugin::find(&args[0])?;
+    Some(plugin.command(&args[1..], args.iter().any(|arg| arg == "--json")))
+}
+
+/// 在 `PATH` 中查找并启动外部程序，返回程序的退出状态
+fn run_external(args: &[String], io: &mut Streams) -> i32 {
+    let Some(command) = find_command(args) else {
+        failln!(io, "{}", tr!("error.command_not_found", args[0]));
+        return 127;
+    };
+    run_process(command, &args[0], io)
+}
+
+/// 启动外部程序并等待它结束，返回程序的退出状态。
+/// 标准输入是终端时程序直接从终端读入，Ctrl+C 会直接发给它
+pub fn run_process(command: Command, name: &str, io: &mut Streams) -> i32 {
+    match spawn_process(command, name, io) {
+        Ok(process) => process.wait(io),
+        Err(status) => status,
+    }
+}
+
+/// 已经启动的外部程序，以及和它交换内存中数据的线程
+struct Process {
+    name: String,
+    child: Child,
+    /// 把 `Input::Data` 写入标准输入的线程
+    feeder: Option<JoinHandle<()>>,
+    /// 收集输出到 `Output::Buffer` 的线程
+    stdout: Option<JoinHandle<Vec<u8>>>,
+    stderr: Option<JoinHandle<Vec<u8>>>,
+}
+
+impl Process {
+    /// 等待程序结束，收集到的输出写入 `io`，返回退出状态
+    fn wait(mut self, io: &mut Streams) -> i32 {
+        let status = match self.child.wait() {
+            Ok(status) => exit_code(status),
+            Err(err) => {
+                failln!(io, "{}", tr!("shell.wait_failed", self.name, err));
+                1
+            }
+        };
+        if let Some(feeder) = self.feeder {
+            let _ = feeder.join();
+        }
+        if let Some(bytes) = self.stdout.and_then(|reader| reader.join().ok()) {
+            let _ = io.stdout.write_all(&bytes);
+        }
+        if let Some(bytes) = self.stderr.and_then(|reader| reader.join().ok()) {
+            let _ = io.stderr.write_all(&bytes);
+        }
+        status
+    }
+}
+
+/// 启动外部程序，标准流直接接到终端、文件或管道上，程序的输出不经过catshell。
+/// 只有内存中的输入数据和 `Output::Buffer` 需要单独的线程读写
+fn spawn_process(mut command: Command, name: &str, io: &mut Streams) -> Result<Process, i32> {
+    let stdin = std::mem::replace(&mut io.stdin, Input::from_bytes(Vec::new()));
+    let (stdin, data) = input_stdio(stdin, Stdio::inherit());
+    let outputs = output_stdio(&io.stdout).and_then(|stdout| Ok((stdout, output_stdio(&io.stderr)?)));
+    let (stdout, stderr) = match outputs {
+        Ok(outputs) => outputs,
+        Err(err) => {
+            failln!(io, "{}", tr!("error.spawn_failed", name, err));
+            return Err(126);
+        }
+    };
+    command.stdin(stdin).stdout(stdout).stderr(stderr);
+
+    let spawned = command.spawn();
+    // `Command` 中还留着管道的副本，释放以后下一个阶段才能在程序结束时读到文件结尾
+    drop(command);
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            failln!(io, "{}", tr!("error.spawn_failed", name, err));
+            return Err(126);
+        }
+    };
+
+    let feeder = child.stdin.take().zip(data).map(|(mut stdin, mut data)| {
+        thread::spawn(move || {
+            let _ = io::copy(&mut data, &mut stdin);
+        })
+    });
+    let stdout = child.stdout.take().map(read_all);
+    let stderr = child.stderr.take().map(read_all);
+    Ok(Process { name: name.to_string(), child, feeder, stdout, stderr })
+}
+
+/// 外部程序的标准输入，`terminal` 是输入来自终端时的设置。内存中的数据需要由调用者写入管道，一起返回
+fn input_stdio(input: Input, terminal: Stdio) -> (Stdio, Option<Cursor<Vec<u8>>>) {
+    match input {
+        Input::Terminal => (terminal, None),
+        Input::Pipe(pipe) => (Stdio::from(pipe), None),
+        Input::File(file) => (Stdio::from(file), None),
+        Input::Data(data) => (Stdio::piped(), Some(data)),
+    }
+}
+
+/// 外部程序的标准输出或标准错误，终端、文件和管道复制一份交给程序，内存缓冲区需要由调用者读取管道
+fn output_stdio(output: &Output) -> io::Result<Stdio> {
+    Ok(match output {
+        Output::Stdout => Stdio::from(io::stdout()),
+        Output::Stderr => Stdio::from(io::stderr()),
+        Output::Buffer(_) => Stdio::piped(),
+        Output::File(file) => Stdio::from(file.try_clone()?),
+        Output::Pipe(pipe) => Stdio::from(pipe.try_clone()?),
+    })
+}
+
+/// 在单独的线程中读取程序的全部输出
+fn read_all(mut reader: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = reader.read_to_end(&mut bytes);
+        bytes
+    })
+}
+
+/// 在后台启动外部程序。没有重定向时标准输入是空的，标准输出和标准错误直接输出到终端
+fn spawn_background(args: &[String], io: Streams) -> Result<Child, i32> {
+    let Some(mut command) = find_command(args) else {
+        failln!(Streams::inherit(), "{}", tr!("error.command_not_found", args[0]));
+        return Err(127);
+    };
+
+    let (stdin, data) = input_stdio(io.stdin, Stdio::null());
+    command.stdin(stdin).stdout(background_stdio(io.stdout)).stderr(background_stdio(io.stderr));
+    jobs::detach(&mut command);
+
+    let mut child = command.spawn().map_err(|err| {
+        failln!(Streams::inherit(), "{}", tr!("error.spawn_failed", args[0], err));
+        126
+    })?;
+
+    // 内存中的输入在单独的线程中写入，不等待它写完
+    if let Some((mut stdin, mut data)) = child.stdin.take().zip(data) {
+        thread::spawn(move || {
+            let _ = io::copy(&mut data, &mut stdin);
+        });
+    }
+    Ok(child)
+}
+
+/// 后台程序的输出：重定向到文件时直接写入文件，否则继承终端
+fn background_stdio(output: Output) -> Stdio {
+    match output {
+        Output::File(file) => Stdio::from(file),
+        Output::Pipe(pipe) => Stdio::from(pipe),
+        Output::Buffer(_) => Stdio::null(),
+        Output::Stdout | Output::Stderr => Stdio::inherit(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::test_util::{output, streams, TempDir};
+
+    fn shell(args: &[&str]) -> Shell {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Shell::new("test", &args)
+    }
+
+    fn builtin(shell: &mut Shell, args: &[&str]) -> (i32, String) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut io = streams(b"");
+        let status = shell.run_shell_builtin(&args, &mut io);
+        (status, output(io).1)
+    }
+
+    #[test]
+    fn shift_removes_positional_parameters() {
+        let mut shell = shell(&["a", "b", "c"]);
+        assert_eq!(builtin(&mut shell, &["shift"]).0, 0);
+        assert_eq!(shell.args(), ["b", "c"]);
+        assert_eq!(builtin(&mut shell, &["shift", "2"]).0, 0);
+        assert!(shell.args().is_empty());
+    }
+
+    #[test]
+    fn shift_rejects_bad_counts() {
+        let mut shell = shell(&["a", "b"]);
+        for count in ["x", "-1", "3"] {
+            let (status, stderr) = builtin(&mut shell, &["shift", count]);
+            assert_eq!(status, 2, "shift {}", count);
+            assert!(stderr.contains("shift"), "{}", stderr);
+        }
+        assert_eq!(shell.args(), ["a", "b"]);
+    }
+
+    #[test]
+    fn runs_control_flow() {
+        let mut shell = shell(&["a", "b", "c"]);
+        let script = "
+            for x in 1 2 3 4; do
+                if [ $x = 2 ]; then continue; elif [ $x = 4 ]; then break; fi
+                echo $x
+            done
+            while [ $# -gt 0 ]; do echo $1; shift; done
+            first() { echo $1; return 3; echo never; }
+            first x y || echo status $?
+            { echo group; false; } && echo skipped
+        ";
+        assert_eq!(shell.capture(script), "1\n3\na\nb\nc\nx\nstatus 3\ngroup");
+        assert_eq!(shell.last_status, 1);
+        // 函数结束后恢复外层的位置参数，它们已经被 while 循环移走了
+        assert!(shell.args().is_empty());
+    }
+
+    #[test]
+    fn defines_and_removes_aliases() {
+        let mut shell = shell(&[]);
+        // 别名在定义以后解析的源代码中才生效
+        assert_eq!(shell.capture("alias greet='echo hi' say=echo; greet"), "");
+        assert_eq!(shell.capture("greet there; say x"), "hi there\nx");
+        assert_eq!(shell.capture("alias"), "alias greet='echo hi'\nalias say='echo'");
+        assert!(shell.capture("which greet").contains("echo hi"));
+        assert_eq!(builtin(&mut shell, &["alias", "=x"]).0, 1);
+        assert_eq!(builtin(&mut shell, &["unalias", "greet", "missing"]).0, 1);
+        assert_eq!(shell.capture("alias"), "alias say='echo'");
+        assert_eq!(builtin(&mut shell, &["unalias", "-a"]).0, 0);
+        assert_eq!(shell.capture("alias"), "");
+    }
+
+    #[test]
+    fn exports_without_touching_the_process_environment() {
+        let mut shell = shell(&[]);
+        shell.capture("CATSHELL_TEST_VAR=local; export CATSHELL_TEST_VAR CATSHELL_TEST_OTHER=2");
+        assert_eq!(env::var_os("CATSHELL_TEST_VAR"), None);
+        assert!(shell.capture("export").contains("export CATSHELL_TEST_VAR=\"local\""));
+        assert_eq!(shell.capture("echo $CATSHELL_TEST_VAR $CATSHELL_TEST_OTHER"), "local 2");
+
+        // 管道中其他线程里的命令也看到导出的变量
+        let environment = shell.env.clone();
+        assert_eq!(config::with_environment(environment, || config::var("CATSHELL_TEST_OTHER")).as_deref(), Some("2"));
+        shell.capture("unset CATSHELL_TEST_OTHER");
+        assert_eq!(shell.get_var("CATSHELL_TEST_OTHER"), None);
+        assert!(!shell.capture("export").contains("CATSHELL_TEST_OTHER"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn passes_exported_variables_to_programs() {
+        let mut shell = shell(&[]);
+        shell.capture("export CATSHELL_TEST_CHILD_VAR=exported");
+        assert_eq!(shell.capture("sh -c 'echo $CATSHELL_TEST_CHILD_VAR' | cat"), "exported");
+        assert_eq!(shell.capture("unset PATH; sh -c true"), "");
+        assert_eq!(shell.last_status, 127);
+    }
+
+    /// 在新的shell中执行 `source` 并返回标准输出，超过时间还没有结束说明管道互相阻塞了
+    fn run(source: &str) -> String {
+        let source = source.to_string();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(shell(&[]).capture(&source));
+        });
+        receiver.recv_timeout(Duration::from_secs(30)).expect("管道没有结束")
+    }
+
+    #[test]
+    fn pipeline_connects_builtins() {
+        assert_eq!(run("echo hello | cat -n"), "     1  hello");
+        assert_eq!(run("echo a | cat | cat -n"), "     1  a");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipeline_stops_endless_producer() {
+        assert_eq!(run("yes | head -n 2"), "y\ny");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipeline_streams_large_output() {
+        assert_eq!(run("seq 1 200000 | tail -n 1"), "200000");
+        assert_eq!(run("x=$(seq 1 200000 | cat); echo \"$x\" | tail -n 1"), "200000");
+        // 两个在shell线程中执行的阶段之间的数据超过管道的容量时也不会互相等待
+        assert_eq!(run("big() { seq 1 200000; }; big | echo done"), "done");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn external_programs_use_redirected_files() {
+        let dir = TempDir::new();
+        let file = dir.path().join("out.txt").display().to_string();
+        run(&format!("seq 3 > '{0}'; seq 4 4 >> '{0}'", file));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "1\n2\n3\n4\n");
+        assert_eq!(run(&format!("sort -r < '{}' | cat", file)), "4\n3\n2\n1");
+    }
+
+    #[test]
+    fn duplicates_stderr_into_pipeline() {
+        let dir = TempDir::new();
+        let missing = dir.path().join("missing").display().to_string();
+        assert!(run(&format!("cat '{}' 2>&1 | cat -n", missing)).contains("missing"));
+        let err = dir.path().join("err.txt");
+        assert_eq!(run(&format!("cat '{}' 2>&1 >&2 2>'{}' | cat -n", missing, err.display())), "");
+        assert!(fs::read_to_string(&err).unwrap().contains("missing"));
+    }
+
+    #[test]
+    fn cd_persists_between_commands() {
+        // 工作目录是整个进程共用的，为了不影响并行执行的其他测试，在只执行这个测试的子进程中切换
+        if env::var_os("CATSHELL_TEST_CHILD").is_none() {
+            let output = Command::new(env::current_exe().unwrap())
+                .args(["--exact", "shell::tests::cd_persists_between_commands", "--nocapture"])
+                .env("CATSHELL_TEST_CHILD", "1")
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+            return;
+        }
+
+        let dir = TempDir::new();
+        dir.file("sub/file", "");
+        let sub = dir.path().join("sub").canonicalize().unwrap();
+        let mut shell = shell(&[]);
+        shell.capture(&format!("cd '{}'", sub.display()));
+        assert_eq!(shell.capture("pwd"), tr!("pwd.location", sub.display()));
+        shell.capture("cd ..");
+        assert_eq!(shell.capture("pwd"), tr!("pwd.location", sub.parent().unwrap().display()));
+        shell.capture("cd sub/file");
+        assert_eq!(shell.last_status, 1);
+    }
+}
@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 收到 Ctrl+C 后被设置，由正在执行的命令自己检查并停止
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// 后台任务线程的取消标志，由 `kill %n` 设置。后台任务不响应 Ctrl+C
+    static CANCEL: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// 安装 Ctrl+C 处理函数，之后 Ctrl+C 不会结束catshell进程，只会设置中断标志。
+/// 前台运行的外部程序和catshell在同一个终端（控制台）中，仍然会直接收到 Ctrl+C
+pub fn install() {
+    imp::install();
+}
+
+/// 从上次 [`clear`] 以来是否按下过 Ctrl+C。在后台任务的线程中表示任务是否被要求停止
+pub fn interrupted() -> bool {
+    CANCEL.with(|cancel| match &*cancel.borrow() {
+        Some(flag) => flag.load(Ordering::SeqCst),
+        None => INTERRUPTED.load(Ordering::SeqCst),
+    })
+}
+
+/// 让当前线程的 [`interrupted`] 改为检查 `flag`，在后台任务的线程开始时调用
+pub fn set_cancel_flag(flag: Arc<AtomicBool>) {
+    CANCEL.with(|cancel| *cancel.borrow_mut() = Some(flag));
+}
+
+/// 清除中断标志，在执行新的命令之前调用
+pub fn clear() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::sync::atomic::Ordering;
+
+    use super::INTERRUPTED;
+
+    const SIGINT: i32 = 2;
+
+    extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+    }
+
+    extern "C" fn handle(_signum: i32) {
+        // 信号处理函数中只能做异步信号安全的操作，写原子变量是安全的
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    pub fn install() {
+        // SAFETY: 处理函数只写一个原子变量
+        unsafe {
+            signal(SIGINT, handle);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::sync::atomic::Ordering;
+
+    use super::INTERRUPTED;
+
+    const CTRL_C_EVENT: u32 = 0;
+    const CTRL_BREAK_EVENT: u32 = 1;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: extern "system" fn(u32) -> i32, add: i32) -> i32;
+    }
+
+    extern "system" fn handle(ctrl_type: u32) -> i32 {
+        match ctrl_type {
+            CTRL_C_EVENT | CTRL_BREAK_EVENT => {
+                INTERRUPTED.store(true, Ordering::SeqCst);
+                1
+            },
+            // 关闭窗口等事件交给默认的处理函数
+            _ => 0,
+        }
+    }
+
+    pub fn install() {
+        // SAFETY: 处理函数只写一个原子变量
+        unsafe {
+            SetConsoleCtrlHandler(handle, 1);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::fs::File;
+use std::io::{self, Cursor, IsTerminal, PipeReader, PipeWriter, Read, Write};
+
+/// 向命令的标准输出写入一行，忽略写入错误（例如管道已关闭）
+macro_rules! outln {
+    ($io:expr) => {{
+        let _ = writeln!($io.stdout);
+    }};
+    ($io:expr, $($arg:tt)*) => {{
+        let _ = writeln!($io.stdout, $($arg)*);
+    }};
+}
+
+/// 向命令的标准输出写入内容，不追加换行
+macro_rules! out {
+    ($io:expr, $($arg:tt)*) => {{
+        let _ = write!($io.stdout, $($arg)*);
+    }};
+}
+
+/// 向命令的标准错误写入一行
+macro_rules! errln {
+    ($io:expr, $($arg:tt)*) => {{
+        let _ = writeln!($io.stderr, $($arg)*);
+    }};
+}
+
+/// 向命令的标准错误写入一行错误信息，emoji开启时前面带有 ❌，颜色开启时显示为红色
+macro_rules! failln {
+    ($io:expr, $($arg:tt)*) => {{
+        let _ = writeln!(
+            $io.stderr,
+            "{}{}",
+            $crate::style::icon(&$io.stderr, "❌"),
+            $crate::style::paint(&$io.stderr, $crate::style::RED, format_args!($($arg)*))
+        );
+    }};
+}
+
+/// 命令的输入来源
+pub enum Input {
+    /// 进程自身的标准输入（终端）
+    Terminal,
+    /// 上一个管道阶段的输出
+    Pipe(PipeReader),
+    /// `<` 重定向的文件
+    File(File),
+    /// 内存中的数据
+    Data(Cursor<Vec<u8>>),
+}
+
+/// 命令的输出目标
+pub enum Output {
+    /// 进程自身的标准输出
+    Stdout,
+    /// 进程自身的标准错误
+    Stderr,
+    /// 缓存在内存中，例如函数的输出还要交给后面在同一线程中执行的阶段时
+    Buffer(Vec<u8>),
+    /// `>` 或 `>>` 重定向到的文件
+    File(File),
+    /// 下一个管道阶段的输入
+    Pipe(PipeWriter),
+}
+
+/// 一个命令可用的三个标准流，内置命令通过它输出而不是直接调用 `println!`
+pub struct Streams {
+    pub stdin: Input,
+    pub stdout: Output,
+    pub stderr: Output,
+}
+
+impl Streams {
+    /// 直接使用进程自身的标准流
+    pub fn inherit() -> Self {
+        Streams {
+            stdin: Input::Terminal,
+            stdout: Output::Stdout,
+            stderr: Output::Stderr,
+        }
+    }
+}
+
+impl Input {
+    /// 从字节数据构造输入
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Input::Data(Cursor::new(bytes))
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Input::Terminal)
+    }
+}
+
+impl Output {
+    /// 是否直接写到进程自身的标准流，外部程序可以直接继承
+    pub fn is_inherited(&self) -> bool {
+        matches!(self, Output::Stdout | Output::Stderr)
+    }
+
+    /// 是否写到终端，管道、文件和重定向都不是终端
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Output::Stdout => io::stdout().is_terminal(),
+            Output::Stderr => io::stderr().is_terminal(),
+            Output::Buffer(_) | Output::File(_) | Output::Pipe(_) => false,
+        }
+    }
+
+    /// 复制一个写到同一目标的输出，内存中的缓冲区不能复制
+    pub fn try_clone(&self) -> io::Result<Output> {
+        match self {
+            Output::Stdout => Ok(Output::Stdout),
+            Output::Stderr => Ok(Output::Stderr),
+            Output::Buffer(_) => Err(io::Error::from(io::ErrorKind::Unsupported)),
+            Output::File(file) => file.try_clone().map(Output::File),
+            Output::Pipe(pipe) => pipe.try_clone().map(Output::Pipe),
+        }
+    }
+
+    /// 取出缓存的数据，非缓存输出返回空
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Output::Buffer(bytes) => bytes,
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Terminal => io::stdin().read(buf),
+            Input::Pipe(pipe) => pipe.read(buf),
+            Input::File(file) => file.read(buf),
+            Input::Data(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout => io::stdout().write(buf),
+            Output::Stderr => io::stderr().write(buf),
+            Output::Buffer(bytes) => bytes.write(buf),
+            Output::File(file) => file.write(buf),
+            Output::Pipe(pipe) => pipe.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout => io::stdout().flush(),
+            Output::Stderr => io::stderr().flush(),
+            Output::Buffer(_) => Ok(()),
+            Output::File(file) => file.flush(),
+            Output::Pipe(pipe) => pipe.flush(),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! 输出样式：emoji图标和ANSI颜色的开关
+
+use std::cell::Cell;
+use std::fmt;
+
+use clap::ValueEnum;
+
+use crate::config;
+use crate::streams::Output;
+use crate::terminal;
+
+/// 红色，用于错误信息
+pub const RED: &str = "31";
+/// 绿色
+pub const GREEN: &str = "32";
+/// 黄色
+pub const YELLOW: &str = "33";
+/// 粗体蓝色，用于目录
+pub const BLUE_BOLD: &str = "1;34";
+/// 青色，用于符号链接
+pub const CYAN: &str = "36";
+/// 灰色，用于不重要的内容
+pub const GRAY: &str = "90";
+
+/// `--emoji` 和 `--color` 的取值。`auto` 在输出是终端时开启，颜色还要求没有设置 `NO_COLOR`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum When {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// 一个线程的输出样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub emoji: When,
+    pub color: When,
+}
+
+thread_local! {
+    static STYLE: Cell<Style> = const { Cell::new(Style { emoji: When::Auto, color: When::Auto }) };
+}
+
+/// 当前线程的输出样式
+pub fn current() -> Style {
+    STYLE.with(Cell::get)
+}
+
+/// 设置当前线程的输出样式
+pub fn set(style: Style) {
+    STYLE.with(|cell| cell.set(style));
+}
+
+/// 在指定的样式下执行 `f`，结束后恢复原来的设置。为 `None` 的项保持不变
+pub fn with_style<T>(emoji: Option<When>, color: Option<When>, f: impl FnOnce() -> T) -> T {
+    let previous = current();
+    set(Style {
+        emoji: emoji.unwrap_or(previous.emoji),
+        color: color.unwrap_or(previous.color),
+    });
+    let result = f();
+    set(previous);
+    result
+}
+
+/// 写到 `output` 的内容是否带有emoji
+pub fn emoji(output: &Output) -> bool {
+    match current().emoji {
+        When::Always => true,
+        When::Never => false,
+        When::Auto => output.is_terminal(),
+    }
+}
+
+/// 写到 `output` 的内容是否使用颜色。`auto` 遵循 https://no-color.org 的约定，
+/// `NO_COLOR` 为非空值或者 `TERM=dumb` 时不使用颜色。Windows上使用颜色前会开启控制台的ANSI支持
+pub fn color(output: &Output) -> bool {
+    match current().color {
+        When::Always => {
+            terminal::enable_ansi();
+            true
+        },
+        When::Never => false,
+        When::Auto => {
+            output.is_terminal()
+                && config::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                && config::var_os("TERM").is_none_or(|term| term != "dumb")
+                && terminal::enable_ansi()
+        },
+    }
+}
+
+/// 文本前面的图标，emoji关闭时为空
+pub struct Icon(Option<&'static str>);
+
+/// 写到 `output` 时显示的图标，带有一个分隔用的空格
+pub fn icon(output: &Output, icon: &'static str) -> Icon {
+    Icon(emoji(output).then_some(icon))
+}
+
+impl fmt::Display for Icon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(icon) => write!(f, "{} ", icon),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 带颜色的值，颜色关闭时和值本身一样。宽度和对齐等格式作用于值本身
+pub struct Painted<T> {
+    value: T,
+    code: Option<String>,
+}
+
+/// 用ANSI颜色代码 `code`（例如 [`RED`] 或者 `LS_COLORS` 中的 `01;34`）显示 `value`，`code` 为空时不使用颜色
+pub fn paint<T: fmt::Display>(output: &Output, code: &str, value: T) -> Painted<T> {
+    Painted { value, code: (!code.is_empty() && color(output)).then(|| code.to_string()) }
+}
+
+impl<T: fmt::Display> fmt::Display for Painted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.code {
+            Some(code) => {
+                write!(f, "\x1b[{}m", code)?;
+                self.value.fmt(f)?;
+                write!(f, "\x1b[0m")
+            },
+            None => self.value.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer() -> Output {
+        Output::Buffer(Vec::new())
+    }
+
+    #[test]
+    fn follows_the_when_switches() {
+        // 缓存不是终端，auto 时既没有emoji也没有颜色
+        assert_eq!(format!("{}ok", icon(&buffer(), "✅")), "ok");
+        assert_eq!(paint(&buffer(), RED, "x").to_string(), "x");
+
+        with_style(Some(When::Always), Some(When::Always), || {
+            assert_eq!(format!("{}ok", icon(&buffer(), "✅")), "✅ ok");
+            assert_eq!(paint(&buffer(), BLUE_BOLD, "dir").to_string(), "\x1b[1;34mdir\x1b[0m");
+            assert_eq!(paint(&buffer(), "", "plain").to_string(), "plain");
+            // 对齐只作用于值本身，不计算颜色代码
+            assert_eq!(format!("{:<4}|", paint(&buffer(), RED, "a")), "\x1b[31ma   \x1b[0m|");
+        });
+
+        with_style(None, Some(When::Never), || {
+            assert_eq!(current().emoji, When::Auto);
+            assert_eq!(format!("{:>3}", paint(&buffer(), RED, 7)), "  7");
+        });
+        assert_eq!(current(), Style::default());
+    }
+}
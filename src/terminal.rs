@@ -0,0 +1,499 @@
+use std::io::{self, Read};
+use std::time::Duration;
+
+/// 方向键等转义序列的字节是一起到达的。ESC 之后这么久还没有输入时，认为只按了 Esc 键
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// [`TerminalInput`] 每次读入的字节数，不小于标准库中标准输入的缓冲区，这样读入的字节不会留在标准库的缓冲区中
+const READ_SIZE: usize = 8 * 1024;
+
+/// 行编辑器能识别的按键
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Char(char),
+    /// Ctrl 加字母，字母为小写
+    Ctrl(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Escape,
+    /// 无法识别的控制字符或转义序列
+    Unknown,
+}
+
+/// 终端的原始模式：输入不回显，按键立即送给程序，Ctrl+C 也作为普通按键读入。
+/// 离开作用域时恢复终端原来的设置
+pub struct RawMode(imp::State);
+
+impl RawMode {
+    /// 把标准输入所在的终端切换到原始模式，标准输入不是终端或者切换失败时返回 `None`
+    pub fn enable() -> Option<RawMode> {
+        imp::enable().map(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        imp::restore(&self.0);
+    }
+}
+
+/// [`read_key`] 的输入
+pub trait KeyInput: Read {
+    /// 最多等待 `timeout`，返回是否有可以立即读入的字节
+    fn ready(&mut self, timeout: Duration) -> io::Result<bool>;
+}
+
+/// 原始模式下终端的输入。每次读入终端中已经到达的所有字节，可以判断 ESC 后面是否还有转义序列的其余部分
+#[derive(Default)]
+pub struct TerminalInput {
+    buffer: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for TerminalInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buffer.len() {
+            self.buffer.resize(READ_SIZE, 0);
+            self.pos = 0;
+            match io::stdin().read(&mut self.buffer) {
+                Ok(len) => self.buffer.truncate(len),
+                Err(err) => {
+                    self.buffer.clear();
+                    return Err(err);
+                }
+            }
+        }
+        let len = buf.len().min(self.buffer.len() - self.pos);
+        buf[..len].copy_from_slice(&self.buffer[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+impl KeyInput for TerminalInput {
+    fn ready(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.pos < self.buffer.len() {
+            return Ok(true);
+        }
+        imp::ready(timeout)
+    }
+}
+
+impl KeyInput for &[u8] {
+    fn ready(&mut self, _timeout: Duration) -> io::Result<bool> {
+        Ok(!self.is_empty())
+    }
+}
+
+/// 让控制台解释颜色等ANSI转义序列，返回是否成功。Windows控制台需要开启虚拟终端处理，其他平台的终端本来就支持
+pub fn enable_ansi() -> bool {
+    imp::enable_ansi()
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::ffi::{c_int, c_short};
+    use std::io;
+    use std::os::fd::AsRawFd;
+    use std::process::{Command, Stdio};
+    use std::time::Duration;
+
+    const POLLIN: c_short = 0x0001;
+
+    #[repr(C)]
+    struct PollFd {
+        fd: c_int,
+        events: c_short,
+        revents: c_short,
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    type NFds = std::ffi::c_ulong;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    type NFds = std::ffi::c_uint;
+
+    extern "C" {
+        fn poll(fds: *mut PollFd, count: NFds, timeout: c_int) -> c_int;
+    }
+
+    /// `stty -g` 输出的原始设置
+    pub struct State(String);
+
+    pub fn enable() -> Option<State> {
+        let output = Command::new("stty").arg("-g").stdin(Stdio::inherit()).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let saved = String::from_utf8(output.stdout).ok()?.trim().to_string();
+
+        // 保留输出处理（opost），这样 '\n' 仍然会换到下一行的开头
+        let status = Command::new("stty")
+            .args(["-icanon", "-echo", "-isig", "-ixon", "-iexten", "min", "1", "time", "0"])
+            .stdin(Stdio::inherit())
+            .status()
+            .ok()?;
+        status.success().then_some(State(saved))
+    }
+
+    pub fn restore(state: &State) {
+        let _ = Command::new("stty").arg(&state.0).stdin(Stdio::inherit()).status();
+    }
+
+    pub fn enable_ansi() -> bool {
+        true
+    }
+
+    /// 用 `poll` 等待标准输入可读，被信号打断时重新等待
+    pub fn ready(timeout: Duration) -> io::Result<bool> {
+        let mut fd = PollFd { fd: io::stdin().as_raw_fd(), events: POLLIN, revents: 0 };
+        let timeout = c_int::try_from(timeout.as_millis()).unwrap_or(c_int::MAX);
+        loop {
+            // SAFETY: fd指向有效的局部变量，数量为1
+            match unsafe { poll(&mut fd, 1, timeout) } {
+                -1 => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                },
+                count => return Ok(count > 0),
+            }
+        }
+    }
+
+    /// `stty size` 输出 "行数 列数"
+    pub fn width() -> Option<usize> {
+        let output = Command::new("stty").arg("size").stdin(Stdio::inherit()).output().ok()?;
+        let size = String::from_utf8(output.stdout).ok()?;
+        size.split_whitespace().nth(1)?.parse().ok()
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::ffi::c_void;
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    const ENABLE_PROCESSED_INPUT: u32 = 0x0001;
+    const ENABLE_LINE_INPUT: u32 = 0x0002;
+    const ENABLE_ECHO_INPUT: u32 = 0x0004;
+    const ENABLE_VIRTUAL_TERMINAL_INPUT: u32 = 0x0200;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+    const WAIT_OBJECT_0: u32 = 0;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Coord {
+        x: i16,
+        y: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct SmallRect {
+        left: i16,
+        top: i16,
+        right: i16,
+        bottom: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ConsoleScreenBufferInfo {
+        size: Coord,
+        cursor_position: Coord,
+        attributes: u16,
+        window: SmallRect,
+        maximum_window_size: Coord,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetConsoleMode(handle: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(handle: *mut c_void, mode: u32) -> i32;
+        fn GetConsoleScreenBufferInfo(handle: *mut c_void, info: *mut ConsoleScreenBufferInfo) -> i32;
+        fn WaitForSingleObject(handle: *mut c_void, milliseconds: u32) -> u32;
+    }
+
+    /// 控制台输入和输出原来的模式
+    pub struct State {
+        input: u32,
+        output: u32,
+    }
+
+    pub fn enable() -> Option<State> {
+        let (input, output) = (io::stdin().as_raw_handle(), io::stdout().as_raw_handle());
+        let (mut input_mode, mut output_mode) = (0, 0);
+        // SAFETY: 句柄来自标准库，模式指针指向有效的局部变量
+        unsafe {
+            if GetConsoleMode(input, &mut input_mode) == 0 || GetConsoleMode(output, &mut output_mode) == 0 {
+                return None;
+            }
+            // 方向键等按键以VT转义序列的形式读入，和其他平台的处理方式一致
+            let raw = (input_mode & !(ENABLE_PROCESSED_INPUT | ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT))
+                | ENABLE_VIRTUAL_TERMINAL_INPUT;
+            if SetConsoleMode(input, raw) == 0 {
+                return None;
+            }
+            SetConsoleMode(output, output_mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING);
+        }
+        Some(State { input: input_mode, output: output_mode })
+    }
+
+    pub fn restore(state: &State) {
+        // SAFETY: 同上
+        unsafe {
+            SetConsoleMode(io::stdin().as_raw_handle(), state.input);
+            SetConsoleMode(io::stdout().as_raw_handle(), state.output);
+        }
+    }
+
+    /// 控制台的输入缓冲区中有事件时句柄处于有信号状态
+    pub fn ready(timeout: Duration) -> io::Result<bool> {
+        let timeout = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+        // SAFETY: 句柄来自标准库
+        Ok(unsafe { WaitForSingleObject(io::stdin().as_raw_handle(), timeout) } == WAIT_OBJECT_0)
+    }
+
+    pub fn enable_ansi() -> bool {
+        static ENABLED: OnceLock<bool> = OnceLock::new();
+        *ENABLED.get_or_init(|| {
+            let output = io::stdout().as_raw_handle();
+            let mut mode = 0;
+            // SAFETY: 句柄来自标准库，mode指向有效的局部变量
+            unsafe {
+                GetConsoleMode(output, &mut mode) != 0
+                    && (mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                        || SetConsoleMode(output, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0)
+            }
+        })
+    }
+
+    pub fn width() -> Option<usize> {
+        let mut info = ConsoleScreenBufferInfo::default();
+        // SAFETY: info指向有效的局部变量
+        if unsafe { GetConsoleScreenBufferInfo(io::stdout().as_raw_handle(), &mut info) } == 0 {
+            return None;
+        }
+        usize::try_from(info.window.right - info.window.left + 1).ok()
+    }
+}
+
+/// 读入一个按键，输入结束时返回 `None`。需要先切换到原始模式
+pub fn read_key(input: &mut impl KeyInput) -> io::Result<Option<Key>> {
+    let byte = match read_byte(input)? {
+        Some(byte) => byte,
+        None => return Ok(None),
+    };
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        0x1b => read_escape(input)?,
+        0x01..=0x1a => Key::Ctrl((b'a' + byte - 1) as char),
+        0x00..=0x1f => Key::Unknown,
+        _ => read_utf8(input, byte)?,
+    };
+    Ok(Some(key))
+}
+
+fn read_byte(input: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut buf = [0u8; 1];
+    match input.read(&mut buf)? {
+        0 => Ok(None),
+        _ => Ok(Some(buf[0])),
+    }
+}
+
+/// 解析 ESC 之后的转义序列，例如 `ESC [ A`（上）或 `ESC [ 3 ~`（Delete）。
+/// 后面没有紧跟着的字节时是单独的 Esc 键，不等待下一次按键
+fn read_escape(input: &mut impl KeyInput) -> io::Result<Key> {
+    if !input.ready(ESCAPE_TIMEOUT)? {
+        return Ok(Key::Escape);
+    }
+    match read_byte(input)? {
+        Some(b'[') => {
+            let mut param = String::new();
+            loop {
+                match read_byte(input)? {
+                    Some(byte @ (b'0'..=b'9' | b';')) => param.push(byte as char),
+                    Some(b'A') => return Ok(Key::Up),
+                    Some(b'B') => return Ok(Key::Down),
+                    Some(b'C') => return Ok(Key::Right),
+                    Some(b'D') => return Ok(Key::Left),
+                    Some(b'H') => return Ok(Key::Home),
+                    Some(b'F') => return Ok(Key::End),
+                    Some(b'~') => {
+                        return Ok(match param.split(';').next() {
+                            Some("1") | Some("7") => Key::Home,
+                            Some("4") | Some("8") => Key::End,
+                            Some("3") => Key::Delete,
+                            _ => Key::Unknown,
+                        })
+                    },
+                    // 其他结束字符表示不支持的序列
+                    Some(0x40..=0x7e) | None => return Ok(Key::Unknown),
+                    Some(_) => {},
+                }
+            }
+        },
+        Some(b'O') => Ok(match read_byte(input)? {
+            Some(b'A') => Key::Up,
+            Some(b'B') => Key::Down,
+            Some(b'C') => Key::Right,
+            Some(b'D') => Key::Left,
+            Some(b'H') => Key::Home,
+            Some(b'F') => Key::End,
+            _ => Key::Unknown,
+        }),
+        _ => Ok(Key::Escape),
+    }
+}
+
+/// 读入一个多字节UTF-8字符剩余的部分
+fn read_utf8(input: &mut impl Read, first: u8) -> io::Result<Key> {
+    let len = match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte(input)? {
+            Some(byte) => bytes.push(byte),
+            None => break,
+        }
+    }
+
+    Ok(match std::str::from_utf8(&bytes).ok().and_then(|s| s.chars().next()) {
+        Some(c) => Key::Char(c),
+        None => Key::Unknown,
+    })
+}
+
+/// 字符串在终端中占用的列数，中文和emoji等宽字符占两列，颜色等ANSI转义序列不占位置
+pub fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.next_if_eq(&'[').is_some() {
+            // CSI序列以 `@` 到 `~` 之间的字符结束
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+            continue;
+        }
+        width += char_width(c);
+    }
+    width
+}
+
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        // 控制字符和组合用字符不占位置
+        0x00..=0x1f | 0x7f..=0x9f | 0x0300..=0x036f | 0x200b..=0x200f | 0xfe00..=0xfe0f => 0,
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+/// 终端的宽度（列数），无法获取时使用环境变量 `COLUMNS`，默认80
+pub fn width() -> usize {
+    crate::config::var("COLUMNS")
+        .and_then(|value| value.parse().ok())
+        .or_else(imp::width)
+        .filter(|&width| width > 0)
+        .unwrap_or(80)
+}
+
+/// 把若干项按列排成多行，和 `ls` 一样先从上到下再从左到右排列，每行不超过 `width` 列
+pub fn columns(items: &[String], width: usize) -> Vec<String> {
+    let column_width = items.iter().map(|item| display_width(item)).max().unwrap_or(0) + 2;
+    let cols = (width / column_width).max(1);
+    let rows = items.len().div_ceil(cols);
+
+    (0..rows)
+        .map(|row| {
+            let mut line = String::new();
+            for col in 0..cols {
+                let Some(item) = items.get(col * rows + row) else { break };
+                line.push_str(item);
+                if items.get((col + 1) * rows + row).is_some() {
+                    line.push_str(&" ".repeat(column_width - display_width(item)));
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn measures_display_width() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("猫咪"), 4);
+        assert_eq!(display_width("\x1b[1;34mdir\x1b[0m"), 3);
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn reads_keys_and_escape_sequences() {
+        let keys = |mut input: &[u8]| {
+            let mut keys = Vec::new();
+            while let Some(key) = read_key(&mut input).unwrap() {
+                keys.push(key);
+            }
+            keys
+        };
+        assert_eq!(keys(b"a\x1b[A\x1bOB\x1b[3~\x1b[1;5C\x1b[Z"), [Key::Char('a'), Key::Up, Key::Down, Key::Delete, Key::Right, Key::Unknown]);
+        assert_eq!(keys("猫\r\x7f\x03".as_bytes()), [Key::Char('猫'), Key::Enter, Key::Backspace, Key::Ctrl('c')]);
+        // 后面没有字节的 ESC 立即作为 Esc 键返回，不等待下一次输入
+        assert_eq!(keys(b"x\x1b"), [Key::Char('x'), Key::Escape]);
+    }
+
+    #[test]
+    fn fills_columns_top_to_bottom() {
+        let names = items(&["a", "bb", "ccc", "d", "e"]);
+        // 每列宽5（最长的名称加两个空格），20列可以放4列，5项需要2行
+        assert_eq!(columns(&names, 20), ["a    ccc  e", "bb   d"]);
+        assert_eq!(columns(&names, 10), ["a    d", "bb   e", "ccc"]);
+        assert_eq!(columns(&names, 1).len(), 5);
+        assert_eq!(columns(&items(&["猫", "\x1b[31mx\x1b[0m"]), 80), ["猫  \x1b[31mx\x1b[0m"]);
+        assert!(columns(&[], 80).is_empty());
+    }
+}
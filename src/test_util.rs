@@ -0,0 +1,61 @@
+//! 单元测试共用的辅助函数：临时目录和内存中的标准流
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::streams::{Input, Output, Streams};
+
+/// 测试用的临时目录，离开作用域时删除
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> TempDir {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "catshell-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        // 规范化以后和 `fs::canonicalize` 的结果可以直接比较（macOS上的 /tmp 是符号链接）
+        TempDir(fs::canonicalize(&dir).unwrap())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// 创建文件，需要时同时创建上级目录，返回文件的路径
+    pub fn file(&self, name: &str, content: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 标准输入是 `stdin`，输出都写到缓存中的标准流
+pub fn streams(stdin: &[u8]) -> Streams {
+    Streams {
+        stdin: Input::from_bytes(stdin.to_vec()),
+        stdout: Output::Buffer(Vec::new()),
+        stderr: Output::Buffer(Vec::new()),
+    }
+}
+
+/// 取出缓存的标准输出和标准错误
+pub fn output(io: Streams) -> (String, String) {
+    (
+        String::from_utf8_lossy(&io.stdout.into_bytes()).into_owned(),
+        String::from_utf8_lossy(&io.stderr.into_bytes()).into_owned(),
+    )
+}
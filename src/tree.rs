@@ -0,0 +1,259 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::{CatError, Result};
+use crate::git::{Status, Statuses};
+use crate::gitignore::IgnoreStack;
+use crate::json::{ToJson, Value};
+use crate::ls::{self, DirId, FileInfo, FileType, ListOptions};
+use crate::streams::Streams;
+use crate::style;
+
+/// tree命令的选项
+#[derive(Debug, Clone, Default)]
+pub struct TreeOptions {
+    /// `-a`：包含隐藏文件
+    pub all: bool,
+    /// `-d`：只显示目录
+    pub dirs_only: bool,
+    /// `-L`：最多显示几层，`None` 表示不限制
+    pub level: Option<usize>,
+    /// `-l`：进入指向目录的符号链接
+    pub follow_links: bool,
+    /// `--gitignore`：跳过 `.gitignore` 排除的文件
+    pub gitignore: bool,
+    /// 在git工作树中时，在名称前面显示文件的git状态，`--no-git` 关闭
+    pub git: bool,
+}
+
+/// 树中的一个条目
+struct Node {
+    info: FileInfo,
+    /// 目录的内容，没有展开（超过层数限制、不是目录或者没有跟随的链接）时为 `None`
+    children: Option<Vec<Node>>,
+    /// 没有展开的原因：目录无法读取或者会造成循环
+    note: Option<String>,
+    /// git状态，不在工作树中时为 `None`
+    git: Option<Status>,
+}
+
+impl ToJson for Node {
+    fn to_json(&self) -> Value {
+        let mut value = self.info.to_json();
+        if let Value::Object(fields) = &mut value {
+            if let Some(children) = &self.children {
+                fields.push(("entries".to_string(), Value::Array(children.iter().map(ToJson::to_json).collect())));
+            }
+            if let Some(note) = &self.note {
+                fields.push(("error".to_string(), note.as_str().into()));
+            }
+        }
+        value
+    }
+}
+
+/// 遍历时的状态：目录和文件计数、当前路径上的目录（用来发现循环）、生效的 `.gitignore` 以及git状态
+struct Walker<'a> {
+    options: &'a TreeOptions,
+    directories: usize,
+    files: usize,
+    ancestors: Vec<DirId>,
+    ignore: Option<IgnoreStack>,
+    git: Option<Statuses>,
+}
+
+impl Walker<'_> {
+    /// 读取目录 `dir` 的内容，`abs_dir` 是它的绝对路径，用于匹配 `.gitignore`，`depth` 是子条目所在的层数
+    fn walk(&mut self, dir: &Path, abs_dir: &Path, depth: usize) -> Result<Vec<Node>> {
+        let list_options = ListOptions { all: self.options.all, ..Default::default() };
+        let entries = ls::list(dir, &list_options)?;
+        if let Some(ignore) = &mut self.ignore {
+            ignore.push(abs_dir);
+        }
+
+        let mut nodes = Vec::new();
+        for info in entries {
+            let abs_path = abs_dir.join(&info.name);
+            let is_dir = info.file_type == FileType::Directory
+                || (self.options.follow_links && info.file_type == FileType::Symlink && info.is_dir_like());
+            if self.ignore.as_ref().is_some_and(|ignore| ignore.is_ignored(&abs_path, is_dir)) {
+                continue;
+            }
+            if self.options.dirs_only && !is_dir {
+                continue;
+            }
+            if is_dir {
+                self.directories += 1;
+            } else {
+                self.files += 1;
+            }
+
+            let git = self.git.as_mut().and_then(|git| git.status(&abs_path, info.file_type == FileType::Directory));
+            let mut node = Node { info, children: None, note: None, git };
+            if is_dir && self.options.level.is_none_or(|level| depth < level) {
+                match ls::dir_id(&node.info.path) {
+                    Some(id) if self.ancestors.contains(&id) => node.note = Some(tr!("tree.recursive")),
+                    Some(id) => {
+                        self.ancestors.push(id);
+                        match self.walk(&node.info.path, &abs_path, depth + 1) {
+                            Ok(children) => node.children = Some(children),
+                            Err(_) => node.note = Some(tr!("tree.open_failed")),
+                        }
+                        self.ancestors.pop();
+                    },
+                    None => node.note = Some(tr!("tree.open_failed")),
+                }
+            }
+            nodes.push(node);
+        }
+
+        if let Some(ignore) = &mut self.ignore {
+            ignore.pop();
+        }
+        Ok(nodes)
+    }
+}
+
+/// 实现tree命令：用 `├──`、`└──` 连接线显示目录结构，最后显示目录和文件的数量
+pub fn print_tree(path: &Path, options: &TreeOptions, json: bool, io: &mut Streams) -> Result<()> {
+    if !path.is_dir() {
+        return Err(CatError::Failed(tr!("ls.read_dir_failed", path.display())));
+    }
+    let abs_root = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    let mut walker = Walker {
+        options,
+        directories: 0,
+        files: 0,
+        ancestors: ls::dir_id(path).into_iter().collect(),
+        ignore: options.gitignore.then(|| IgnoreStack::new(&abs_root)),
+        git: options.git.then(Statuses::default),
+    };
+    let nodes = walker.walk(path, &abs_root, 1)?;
+
+    if json {
+        outln!(io, "{}", Value::object([
+            ("path", path.display().to_string().into()),
+            ("entries", Value::Array(nodes.iter().map(ToJson::to_json).collect())),
+            ("directories", walker.directories.into()),
+            ("files", walker.files.into()),
+        ]));
+        return Ok(());
+    }
+
+    outln!(io, "{}", style::paint(&io.stdout, style::BLUE_BOLD, path.display()));
+    print_nodes(&nodes, "", io);
+    let summary = if options.dirs_only {
+        tr!("tree.summary_dirs", walker.directories)
+    } else {
+        tr!("tree.summary", walker.directories, walker.files)
+    };
+    outln!(io, "\n{}", summary);
+    Ok(())
+}
+
+/// 显示一层条目，`prefix` 是上面各层的竖线和空白
+fn print_nodes(nodes: &[Node], prefix: &str, io: &mut Streams) {
+    for (index, node) in nodes.iter().enumerate() {
+        let last = index + 1 == nodes.len();
+        let target = match &node.info.link_target {
+            Some(target) => format!(" -> {}", target.display()),
+            None => String::new(),
+        };
+        let note = match &node.note {
+            Some(note) => format!("  [{}]", note),
+            None => String::new(),
+        };
+        let git = match node.git {
+            Some(status) => format!("{} ", ls::git_marker(Some(status), &io.stdout)),
+            None => String::new(),
+        };
+        outln!(
+            io,
+            "{}{}{}{}{}{}{}",
+            prefix,
+            if last { "└── " } else { "├── " },
+            git,
+            ls::icon(&node.info, &io.stdout),
+            ls::paint_name(&node.info, &io.stdout),
+            target,
+            note
+        );
+        if let Some(children) = &node.children {
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            print_nodes(children, &prefix, io);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{output, streams, TempDir};
+
+    fn tree(path: &Path, options: &TreeOptions) -> String {
+        let mut io = streams(b"");
+        print_tree(path, options, false, &mut io).unwrap();
+        let stdout = output(io).0;
+        // 第一行是根目录的路径
+        stdout.split_once('\n').unwrap().1.to_string()
+    }
+
+    #[test]
+    fn draws_nested_directories() {
+        let dir = TempDir::new();
+        dir.file("a/b/c.txt", "");
+        dir.file("a/d.txt", "");
+        dir.file("e.txt", "");
+        dir.file(".hidden", "");
+
+        assert_eq!(
+            tree(dir.path(), &TreeOptions::default()),
+            format!("├── a\n│   ├── b\n│   │   └── c.txt\n│   └── d.txt\n└── e.txt\n\n{}\n", tr!("tree.summary", 2, 3))
+        );
+        assert_eq!(
+            tree(dir.path(), &TreeOptions { level: Some(1), all: true, ..Default::default() }),
+            format!("├── .hidden\n├── a\n└── e.txt\n\n{}\n", tr!("tree.summary", 1, 2))
+        );
+        assert_eq!(
+            tree(dir.path(), &TreeOptions { dirs_only: true, ..Default::default() }),
+            format!("└── a\n    └── b\n\n{}\n", tr!("tree.summary_dirs", 2))
+        );
+    }
+
+    #[test]
+    fn skips_gitignored_entries() {
+        let dir = TempDir::new();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        dir.file(".gitignore", "target/\n*.log\n");
+        dir.file("src/.gitignore", "!keep.log\n");
+        dir.file("src/keep.log", "");
+        dir.file("src/main.rs", "");
+        dir.file("src/debug.log", "");
+        dir.file("target/out", "");
+
+        let options = TreeOptions { gitignore: true, ..Default::default() };
+        assert_eq!(
+            tree(dir.path(), &options),
+            format!("└── src\n    ├── keep.log\n    └── main.rs\n\n{}\n", tr!("tree.summary", 1, 2))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stops_at_symlink_cycles() {
+        let dir = TempDir::new();
+        dir.file("a/x", "");
+        std::os::unix::fs::symlink("..", dir.path().join("a/up")).unwrap();
+
+        let options = TreeOptions { follow_links: true, ..Default::default() };
+        let mut io = streams(b"");
+        print_tree(dir.path(), &options, true, &mut io).unwrap();
+        let value = crate::json::parse(&output(io).0).unwrap();
+        let Some(Value::Array(root)) = value.get("entries") else { panic!("没有entries") };
+        let Some(Value::Array(a)) = root[0].get("entries") else { panic!("a没有展开") };
+        let up = a.iter().find(|entry| entry.get("name").and_then(Value::as_str) == Some("up")).unwrap();
+        assert_eq!(up.get("error").and_then(Value::as_str), Some(tr!("tree.recursive").as_str()));
+        assert!(up.get("entries").is_none());
+    }
+}
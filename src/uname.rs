@@ -1,18 +1,45 @@
-use std::env;
+use std::io::Write;
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// 实现uname命令功能，显示系统信息
-/// 模拟Linux的uname -a命令输出
-pub fn print_system_info() {
+use crate::config;
+use crate::error::Result;
+use crate::json::{ToJson, Value};
+use crate::streams::Streams;
+use crate::style;
+
+/// [`system_info`] 收集到的系统信息，无法获取的字段为 `Unknown`
+#[derive(Debug, Clone)]
+pub struct SystemInfo {
+    pub os_type: String,
+    pub host_name: String,
+    pub kernel_version: String,
+    pub current_time: String,
+    pub cpu_info: String,
+}
+
+impl ToJson for SystemInfo {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("os_type", self.os_type.as_str().into()),
+            ("host_name", self.host_name.as_str().into()),
+            ("kernel_version", self.kernel_version.as_str().into()),
+            ("current_time", self.current_time.as_str().into()),
+            ("cpu_info", self.cpu_info.as_str().into()),
+        ])
+    }
+}
+
+/// 获取系统信息
+pub fn system_info() -> SystemInfo {
     // 使用Rust标准库获取系统信息
     let os_type = "Windows";
     
     // 获取主机名
-    let host_name = env::var("COMPUTERNAME").unwrap_or_else(|_| "Unknown".to_string());
+    let host_name = config::var("COMPUTERNAME").unwrap_or_else(|| "Unknown".to_string());
     
     // 获取Windows版本信息
-    let kernel_version = match Command::new("cmd.exe").args(&["/c", "ver"]).output() {
+    let kernel_version = match Command::new("cmd.exe").args(["/c", "ver"]).output() {
         Ok(output) => {
             String::from_utf8_lossy(&output.stdout).to_string()
         },
@@ -20,10 +47,10 @@ pub fn print_system_info() {
     };
     
     // 获取处理器信息
-    let cpu_info = match Command::new("wmic").args(&["cpu", "get", "name"]).output() {
+    let cpu_info = match Command::new("wmic").args(["cpu", "get", "name"]).output() {
         Ok(output) => {
             let output_str = String::from_utf8_lossy(&output.stdout).to_string();
-            output_str.lines().skip(1).next().unwrap_or("Unknown").trim().to_string()
+            output_str.lines().nth(1).unwrap_or("Unknown").trim().to_string()
         },
         Err(_) => "Unknown".to_string()
     };
@@ -41,12 +68,30 @@ pub fn print_system_info() {
         Err(_) => "Unknown".to_string()
     };
     
+    SystemInfo {
+        os_type: os_type.to_string(),
+        host_name,
+        kernel_version: kernel_version.trim().to_string(),
+        current_time,
+        cpu_info,
+    }
+}
+
+/// 实现uname命令功能，显示系统信息
+/// 模拟Linux的uname -a命令输出
+pub fn print_system_info(json: bool, io: &mut Streams) -> Result<()> {
+    let info = system_info();
+    if json {
+        outln!(io, "{}", info.to_json());
+        return Ok(());
+    }
+    
     // 模拟uname -a的输出格式
-    println!("🖥️ {} {} {} {} {}", 
-             os_type,
-             host_name,
-             kernel_version.trim(),
-             current_time,
-             cpu_info
+    outln!(io, "{}{} {} {} {} {}", style::icon(&io.stdout, "🖥️"), info.os_type,
+             info.host_name,
+             info.kernel_version,
+             info.current_time,
+             info.cpu_info
     );
+    Ok(())
 }
\ No newline at end of file
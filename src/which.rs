@@ -0,0 +1,120 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+
+/// Windows上没有设置 `PATHEXT` 时使用的可执行文件扩展名
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// 查找外部程序。名称中包含路径分隔符时直接使用该路径，否则依次在 `PATH` 的目录中查找，
+/// Windows上还会尝试 `PATHEXT` 中的扩展名
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    if name.is_empty() {
+        return None;
+    }
+    if name.contains('/') || (cfg!(windows) && name.contains('\\')) {
+        return candidates(Path::new(name)).into_iter().find(|path| is_executable(path));
+    }
+
+    let path = config::var_os("PATH")?;
+    env::split_paths(&path)
+        .flat_map(|dir| candidates(&dir.join(name)))
+        .find(|path| is_executable(path))
+}
+
+/// `PATH` 中所有可执行文件的名称，用于Tab补全。Windows上会去掉 `PATHEXT` 中的扩展名
+pub fn program_names() -> Vec<String> {
+    let Some(path) = config::var_os("PATH") else { return Vec::new() };
+    let extensions = extensions();
+    let mut names = Vec::new();
+
+    for dir in env::split_paths(&path) {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !is_executable(&path) {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let name = match extensions.iter().find(|ext| name.to_uppercase().ends_with(ext.as_str())) {
+                Some(ext) => name[..name.len() - ext.len()].to_string(),
+                None => name,
+            };
+            names.push(name);
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// 同一个程序名可能对应的文件。Windows上没有可执行扩展名时依次加上 `PATHEXT` 中的扩展名
+fn candidates(path: &Path) -> Vec<PathBuf> {
+    let extensions = extensions();
+    let has_extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()).to_uppercase())
+        .is_some_and(|ext| extensions.contains(&ext));
+
+    if extensions.is_empty() || has_extension {
+        return vec![path.to_path_buf()];
+    }
+    extensions
+        .iter()
+        .map(|ext| {
+            let mut name = path.as_os_str().to_os_string();
+            name.push(ext.to_lowercase());
+            PathBuf::from(name)
+        })
+        .collect()
+}
+
+/// 可执行文件的扩展名（大写），只在Windows上有意义
+fn extensions() -> Vec<String> {
+    if !cfg!(windows) {
+        return Vec::new();
+    }
+    config::var("PATHEXT")
+        .unwrap_or_else(|| DEFAULT_PATHEXT.to_string())
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(str::to_uppercase)
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn finds_executable_paths() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new();
+        let script = dir.file("script", "#!/bin/sh\n");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let text = dir.file("notes.txt", "");
+        fs::set_permissions(&text, fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert_eq!(find_program(&script.display().to_string()), Some(script));
+        assert_eq!(find_program(&text.display().to_string()), None);
+        assert_eq!(find_program(&dir.path().display().to_string()), None);
+        assert_eq!(find_program(""), None);
+        assert!(find_program("sh").is_some());
+        assert_eq!(candidates(Path::new("tool")), [PathBuf::from("tool")]);
+    }
+}
@@ -1,18 +1,42 @@
 use std::fs::{self, File};
-use std::io;
-use std::path::{Path, PathBuf};
+use std::io::{self, Write};
+use std::path::Path;
 use zip::write::FileOptions;
 use zip::{ZipArchive, ZipWriter};
 
-/// 创建zip压缩文件
-pub fn create_zip(entries: &[String], output_path: &str) {
-    let file = match File::create(output_path) {
+use crate::error::{CatError, Result};
+use crate::json::Value;
+use crate::streams::Streams;
+
+/// [`create`] 和 [`extract`] 的结果。个别条目失败不会中断整个操作，失败的条目记录在 `errors` 中
+#[derive(Debug, Default)]
+pub struct Summary {
+    /// 成功写入的压缩文件条目名称
+    pub entries: Vec<String>,
+    /// 被跳过的条目和原因
+    pub errors: Vec<CatError>,
+}
+
+/// [`extract`] 能够解压的压缩包扩展名，jar、apk等格式也是zip文件
+const ARCHIVE_EXTENSIONS: [&str; 5] = ["zip", "jar", "war", "apk", "whl"];
+
+/// 按扩展名判断 `path` 是否是zip格式的压缩包
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ARCHIVE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// 把文件和目录压缩到 `output`。只有压缩文件本身无法创建或写入时返回错误
+pub fn create(entries: &[impl AsRef<Path>], output: impl AsRef<Path>) -> Result<Summary> {
+    let output = output.as_ref();
+    let file = match File::create(output) {
         Ok(file) => file,
         Err(err) => {
-            eprintln!("无法创建压缩文件 {}: {}", output_path, err);
-            return;
+            return Err(CatError::Failed(tr!("zip.create_failed", output.display(), err)));
         }
     };
+    let mut summary = Summary::default();
 
     let mut zip = ZipWriter::new(file);
     let options = FileOptions::default()
@@ -20,70 +44,72 @@ pub fn create_zip(entries: &[String], output_path: &str) {
         .unix_permissions(0o755);
 
     for entry in entries {
-        let path = Path::new(entry);
+        let path = entry.as_ref();
         if !path.exists() {
-            eprintln!("警告: {} 不存在，跳过", entry);
+            summary.errors.push(CatError::Failed(tr!("zip.missing", path.display())));
             continue;
         }
 
         if path.is_file() {
-            add_file_to_zip(&mut zip, path, path.file_name().unwrap().to_str().unwrap(), options).unwrap_or_else(|err| {
-                eprintln!("添加文件 {} 失败: {}", entry, err);
-            });
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if let Err(err) = add_file_to_zip(&mut zip, path, &name, options, &mut summary.entries) {
+                summary.errors.push(CatError::Failed(tr!("zip.add_file_failed", path.display(), err)));
+            }
         } else if path.is_dir() {
-            add_directory_to_zip(&mut zip, path, "", options).unwrap_or_else(|err| {
-                eprintln!("添加目录 {} 失败: {}", entry, err);
-            });
+            if let Err(err) = add_directory_to_zip(&mut zip, path, "", options, &mut summary.entries) {
+                summary.errors.push(CatError::Failed(tr!("zip.add_dir_failed", path.display(), err)));
+            }
         }
     }
 
     if let Err(err) = zip.finish() {
-        eprintln!("完成压缩文件失败: {}", err);
-        return;
+        return Err(CatError::Failed(tr!("zip.finish_failed", err)));
     }
-
-    println!("成功创建压缩文件: {}", output_path);
+    Ok(summary)
 }
 
-/// 解压缩zip文件
-pub fn extract_zip(zip_path: &str, output_dir: &str) {
+/// 把 `archive` 解压到 `output_dir`。会跑到输出目录之外的条目（例如 `../x`）会被跳过
+pub fn extract(archive: impl AsRef<Path>, output_dir: impl AsRef<Path>) -> Result<Summary> {
+    let (zip_path, output_dir) = (archive.as_ref(), output_dir.as_ref());
     let file = match File::open(zip_path) {
         Ok(file) => file,
         Err(err) => {
-            eprintln!("无法打开压缩文件 {}: {}", zip_path, err);
-            return;
+            return Err(CatError::Failed(tr!("zip.open_failed", zip_path.display(), err)));
         }
     };
 
     let mut archive = match ZipArchive::new(file) {
         Ok(archive) => archive,
         Err(err) => {
-            eprintln!("无法解析压缩文件 {}: {}", zip_path, err);
-            return;
+            return Err(CatError::Failed(tr!("zip.parse_failed", zip_path.display(), err)));
         }
     };
 
     // 确保输出目录存在
     if let Err(err) = fs::create_dir_all(output_dir) {
-        eprintln!("无法创建输出目录 {}: {}", output_dir, err);
-        return;
+        return Err(CatError::Failed(tr!("zip.output_dir_failed", output_dir.display(), err)));
     }
+    let mut summary = Summary::default();
 
     for i in 0..archive.len() {
         let mut file = match archive.by_index(i) {
             Ok(file) => file,
             Err(err) => {
-                eprintln!("读取压缩文件条目失败: {}", err);
+                summary.errors.push(CatError::Failed(tr!("zip.entry_failed", err)));
                 continue;
             }
         };
 
-        let output_path = Path::new(output_dir).join(file.name());
+        let Some(relative_path) = file.enclosed_name().map(Path::to_path_buf) else {
+            summary.errors.push(CatError::Failed(tr!("zip.unsafe_path", file.name())));
+            continue;
+        };
+        let output_path = output_dir.join(relative_path);
 
-        if file.name().ends_with('/') {
+        if file.is_dir() {
             // 是目录，创建目录
             if let Err(err) = fs::create_dir_all(&output_path) {
-                eprintln!("创建目录 {} 失败: {}", output_path.display(), err);
+                summary.errors.push(CatError::Failed(tr!("zip.mkdir_failed", output_path.display(), err)));
                 continue;
             }
         } else {
@@ -91,7 +117,7 @@ pub fn extract_zip(zip_path: &str, output_dir: &str) {
             if let Some(parent) = output_path.parent() {
                 if !parent.exists() {
                     if let Err(err) = fs::create_dir_all(parent) {
-                        eprintln!("创建父目录 {} 失败: {}", parent.display(), err);
+                        summary.errors.push(CatError::Failed(tr!("zip.parent_failed", parent.display(), err)));
                         continue;
                     }
                 }
@@ -100,37 +126,67 @@ pub fn extract_zip(zip_path: &str, output_dir: &str) {
             let mut target_file = match File::create(&output_path) {
                 Ok(file) => file,
                 Err(err) => {
-                    eprintln!("创建文件 {} 失败: {}", output_path.display(), err);
+                    summary.errors.push(CatError::Failed(tr!("zip.file_failed", output_path.display(), err)));
                     continue;
                 }
             };
 
             if let Err(err) = io::copy(&mut file, &mut target_file) {
-                eprintln!("写入文件 {} 失败: {}", output_path.display(), err);
+                summary.errors.push(CatError::Failed(tr!("zip.write_failed", output_path.display(), err)));
                 continue;
             }
         }
+        summary.entries.push(file.name().to_string());
     }
+    Ok(summary)
+}
 
-    println!("成功解压缩到: {}", output_dir);
+/// 创建zip压缩文件。不存在或无法添加的条目会被跳过，压缩文件仍然会创建，但最后返回这些错误
+pub fn create_zip(entries: &[String], output_path: &str, json: bool, io: &mut Streams) -> Result<()> {
+    let summary = create(entries, output_path)?;
+    if json {
+        outln!(io, "{}", Value::object([
+            ("archive", output_path.into()),
+            ("added", summary.entries.into()),
+        ]));
+    } else {
+        outln!(io, "{}", tr!("zip.created", output_path));
+    }
+    CatError::collect(summary.errors)
+}
+
+/// 解压缩zip文件。某个条目解压失败时继续解压其他条目，最后返回所有失败
+pub fn extract_zip(zip_path: &str, output_dir: &str, json: bool, io: &mut Streams) -> Result<()> {
+    let summary = extract(zip_path, output_dir)?;
+    if json {
+        outln!(io, "{}", Value::object([
+            ("archive", zip_path.into()),
+            ("directory", output_dir.into()),
+            ("extracted", summary.entries.into()),
+        ]));
+    } else {
+        outln!(io, "{}", tr!("zip.extracted", output_dir));
+    }
+    CatError::collect(summary.errors)
 }
 
 // 将单个文件添加到zip中
-fn add_file_to_zip(zip: &mut ZipWriter<File>, path: &Path, name: &str, options: FileOptions) -> zip::result::ZipResult<()> {
+fn add_file_to_zip(zip: &mut ZipWriter<File>, path: &Path, name: &str, options: FileOptions, added: &mut Vec<String>) -> zip::result::ZipResult<()> {
     let mut file = File::open(path)?;
     zip.start_file(name, options)?;
     io::copy(&mut file, zip)?;
+    added.push(name.to_string());
     Ok(())
 }
 
 // 将目录添加到zip中
-fn add_directory_to_zip(zip: &mut ZipWriter<File>, path: &Path, prefix: &str, options: FileOptions) -> zip::result::ZipResult<()> {
+fn add_directory_to_zip(zip: &mut ZipWriter<File>, path: &Path, prefix: &str, options: FileOptions, added: &mut Vec<String>) -> zip::result::ZipResult<()> {
     let entries = fs::read_dir(path)?;
     
     for entry in entries {
         let entry = entry?;
         let entry_path = entry.path();
-        let entry_name = entry.file_name().to_string_lossy();
+        let entry_name = entry.file_name().to_string_lossy().into_owned();
         let zip_path = if prefix.is_empty() {
             entry_name.to_string()
         } else {
@@ -138,12 +194,66 @@ fn add_directory_to_zip(zip: &mut ZipWriter<File>, path: &Path, prefix: &str, op
         };
 
         if entry_path.is_file() {
-            add_file_to_zip(zip, &entry_path, &zip_path, options)?;
+            add_file_to_zip(zip, &entry_path, &zip_path, options, added)?;
         } else if entry_path.is_dir() {
             zip.start_file(format!("{}/", zip_path), options)?;
-            add_directory_to_zip(zip, &entry_path, &zip_path, options)?;
+            added.push(format!("{}/", zip_path));
+            add_directory_to_zip(zip, &entry_path, &zip_path, options, added)?;
         }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{output, streams, TempDir};
+
+    #[test]
+    fn round_trips_files_and_directories() {
+        let dir = TempDir::new();
+        let file = dir.file("a.txt", "hello");
+        dir.file("src/lib.rs", "fn main() {}");
+        dir.file("src/nested/b.txt", "b");
+        let archive = dir.path().join("out.zip");
+        assert!(is_archive(&archive));
+        assert!(is_archive(Path::new("x.JAR")));
+        assert!(!is_archive(Path::new("x.tar")));
+
+        let summary = create(&[file, dir.path().join("src"), dir.path().join("missing")], &archive).unwrap();
+        let mut entries = summary.entries.clone();
+        entries.sort();
+        assert_eq!(entries, ["a.txt", "lib.rs", "nested/", "nested/b.txt"]);
+        assert_eq!(summary.errors.len(), 1);
+
+        let target = dir.path().join("extracted");
+        let summary = extract(&archive, &target).unwrap();
+        assert!(summary.errors.is_empty());
+        assert_eq!(fs::read_to_string(target.join("a.txt")).unwrap(), "hello");
+        assert_eq!(fs::read_to_string(target.join("nested/b.txt")).unwrap(), "b");
+    }
+
+    #[test]
+    fn skips_entries_outside_the_output_directory() {
+        let dir = TempDir::new();
+        let archive = dir.path().join("evil.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("../escape.txt", FileOptions::default()).unwrap();
+        zip.write_all(b"x").unwrap();
+        zip.start_file("safe.txt", FileOptions::default()).unwrap();
+        zip.write_all(b"y").unwrap();
+        zip.finish().unwrap();
+
+        let target = dir.path().join("out");
+        let mut io = streams(b"");
+        let error = extract_zip(&archive.display().to_string(), &target.display().to_string(), true, &mut io).unwrap_err();
+        assert_eq!(error.exit_code(), 1);
+        assert!(!dir.path().join("escape.txt").exists());
+        assert!(target.join("safe.txt").exists());
+        assert!(output(io).0.contains("\"extracted\":[\"safe.txt\"]"));
+
+        assert!(extract(dir.path().join("missing.zip"), &target).is_err());
+        assert!(extract(dir.file("broken.zip", "not a zip"), &target).is_err());
+    }
+}